@@ -1,30 +1,61 @@
-use cosmwasm_std::{BankMsg, Deps, DepsMut, Env, MessageInfo, Order, StdError};
+use cosmwasm_std::{Deps, DepsMut, Empty, Env, MessageInfo, Order, StdError, StdResult, Storage};
 use utils::{
     state::{is_valid_comment, AssetInfo},
-    types::{CosmosMsg, Response},
+    types::Response,
 };
 
 use crate::{
     error::ContractError,
     execute::{_accept_offer_raw, _internal_list_collaterals, _make_offer_raw},
-    helpers::assert_listing_fee,
+    helpers::{
+        assert_listing_fee, assert_token_traits, asset_from_funds, fungible_transfer_msg,
+        nft_collection,
+    },
     msg::{CollectionOfferResponse, MultipleCollectionOffersResponse},
     query::{DEFAULT_QUERY_LIMIT, MAX_QUERY_LIMIT},
-    state::{LoanTerms, CONFIG},
+    state::{LoanTerms, TraitConstraint, CONFIG},
 };
 
 use cosmwasm_std::Addr;
-use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, MultiIndex};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Map, MultiIndex};
 
 use crate::state::CollectionOfferInfo;
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_make_collection_offer(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    collection: String,
+    collections: Vec<String>,
+    token_count: Option<u32>,
     terms: LoanTerms,
     comment: Option<String>,
+    traits: Option<Vec<TraitConstraint>>,
+) -> Result<Response, ContractError> {
+    let sent_assets = asset_from_funds(info.funds)?;
+    _make_collection_offer(
+        deps,
+        info.sender,
+        sent_assets,
+        collections,
+        token_count,
+        terms,
+        comment,
+        traits,
+    )
+}
+
+/// Saves an offer that can be accepted with `token_count` tokens from any of the `collections`
+#[allow(clippy::too_many_arguments)]
+pub fn _make_collection_offer(
+    deps: DepsMut,
+    lender: Addr,
+    sent_assets: AssetInfo,
+    collections: Vec<String>,
+    token_count: Option<u32>,
+    terms: LoanTerms,
+    comment: Option<String>,
+    traits: Option<Vec<TraitConstraint>>,
 ) -> Result<Response, ContractError> {
     // We make sure the contract is not locked
     let config = CONFIG.load(deps.storage)?;
@@ -34,7 +65,20 @@ pub fn execute_make_collection_offer(
         return Err(ContractError::ContractIsLocked {});
     }
 
-    let collection = deps.api.addr_validate(&collection)?;
+    let mut collections = collections
+        .iter()
+        .map(|collection| deps.api.addr_validate(collection))
+        .collect::<StdResult<Vec<_>>>()?;
+    collections.sort();
+    collections.dedup();
+    if collections.is_empty() {
+        return Err(ContractError::NoCollections {});
+    }
+    let token_count = token_count.unwrap_or(1);
+    if token_count == 0 {
+        return Err(ContractError::NoAssets {});
+    }
+    terms.validate(deps.api)?;
 
     // checks comment size
     if !is_valid_comment(&comment.clone().unwrap_or_default()) {
@@ -43,9 +87,7 @@ pub fn execute_make_collection_offer(
         )));
     }
     // Make sure the transaction contains funds that match the principle indicated in the terms
-    if info.funds.len() != 1 {
-        return Err(ContractError::MultipleCoins {});
-    } else if terms.principle != info.funds[0].clone() {
+    if terms.principle != sent_assets {
         return Err(ContractError::FundsDontMatchTerms {});
     }
 
@@ -57,11 +99,13 @@ pub fn execute_make_collection_offer(
         deps.storage,
         &global_collection_offer_index.to_string(),
         &CollectionOfferInfo {
-            lender: info.sender.clone(),
-            collection: collection.clone(),
+            lender: lender.clone(),
+            collections: collections.clone(),
+            token_count,
             collection_offer_id: global_collection_offer_index,
             terms,
             comment,
+            traits: traits.unwrap_or_default(),
         },
     )?;
 
@@ -69,8 +113,9 @@ pub fn execute_make_collection_offer(
 
     Ok(Response::new()
         .add_attribute("action", "make_collection_offer")
-        .add_attribute("lender", info.sender)
-        .add_attribute("collection", collection)
+        .add_attribute("lender", lender)
+        .add_attribute("collections", join_collections(&collections))
+        .add_attribute("token_count", token_count.to_string())
         .add_attribute(
             "collection_offer_id",
             global_collection_offer_index.to_string(),
@@ -93,10 +138,11 @@ pub fn execute_withdraw_collection_offer(
     }
 
     // We send them the corresponding assets back
-    let msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: vec![collection_info.terms.principle],
-    });
+    let msg = fungible_transfer_msg(
+        &collection_info.terms.principle,
+        collection_info.terms.principle_amount()?,
+        &info.sender,
+    )?;
 
     // We remove the collection offer
     collection_offers().remove(deps.storage, &collection_offer_id.to_string())?;
@@ -105,7 +151,10 @@ pub fn execute_withdraw_collection_offer(
         .add_message(msg)
         .add_attribute("action", "remove_collection_offer")
         .add_attribute("lender", info.sender)
-        .add_attribute("collection", collection_info.collection))
+        .add_attribute(
+            "collections",
+            join_collections(&collection_info.collections),
+        ))
 }
 
 pub fn execute_accept_collection_offer(
@@ -113,7 +162,7 @@ pub fn execute_accept_collection_offer(
     env: Env,
     info: MessageInfo,
     collection_offer_id: u64,
-    token: AssetInfo,
+    tokens: Vec<AssetInfo>,
 ) -> Result<Response, ContractError> {
     // We verify the sender paid the listing fee
     let transfer_fee_msg = assert_listing_fee(deps.as_ref(), info.funds)?;
@@ -125,12 +174,36 @@ pub fn execute_accept_collection_offer(
     let collection_info =
         collection_offers().load(deps.storage, &collection_offer_id.to_string())?;
 
-    // We create a collateral listing with the given token
+    // We verify the tokens match what the lender asked for
+    if tokens.len() != collection_info.token_count as usize {
+        return Err(ContractError::WrongTokenCount {
+            expected: collection_info.token_count,
+            actual: tokens.len() as u32,
+        });
+    }
+    for (i, token) in tokens.iter().enumerate() {
+        let collection = nft_collection(token)?;
+        if !collection_info
+            .collections
+            .iter()
+            .any(|c| c.as_str() == collection)
+        {
+            return Err(ContractError::CollectionNotInOffer {
+                collection: collection.to_string(),
+            });
+        }
+        if tokens[..i].iter().any(|other| other.overlaps(token)) {
+            return Err(ContractError::DuplicateToken {});
+        }
+        assert_token_traits(deps.as_ref(), token, &collection_info.traits)?;
+    }
+
+    // We create a collateral listing with the given tokens
     let (list_attributes, collateral_id) = _internal_list_collaterals(
         deps.branch(),
         env.clone(),
         borrower.clone(),
-        vec![token],
+        tokens,
         None,
         None,
         None,
@@ -142,7 +215,7 @@ pub fn execute_accept_collection_offer(
         deps.storage,
         env.clone(),
         collection_info.lender.clone(),
-        vec![collection_info.terms.principle.clone()],
+        collection_info.terms.principle.clone(),
         borrower.clone(),
         collateral_id,
         collection_info.terms,
@@ -159,7 +232,10 @@ pub fn execute_accept_collection_offer(
         .add_message(transfer_fee_msg)
         .add_attribute("action", "accept_collection_offer")
         .add_attribute("lender", collection_info.lender)
-        .add_attribute("collection", collection_info.collection)
+        .add_attribute(
+            "collections",
+            join_collections(&collection_info.collections),
+        )
         .add_attributes(list_attributes)
         .add_attributes(accept_res.attributes)
         .add_events(accept_res.events)
@@ -178,26 +254,25 @@ pub fn query_collection_offers(
 
     let offers = collection_offers()
         .idx
-        .collection
-        .prefix(collection_addr)
-        .range(deps.storage, None, start, Order::Descending)
-        .map(|r| {
-            r.map(|(key, collection_offer_info)| CollectionOfferResponse {
-                global_offer_id: key,
-                collection_offer_info,
+        .collections
+        .idx
+        .prefix(collection_addr.clone())
+        .keys(deps.storage, None, start, Order::Descending)
+        .take(limit)
+        .map(|key| {
+            let global_offer_id = key?;
+            Ok(CollectionOfferResponse {
+                collection_offer_info: collection_offers().load(deps.storage, &global_offer_id)?,
+                global_offer_id,
             })
         })
-        .take(limit)
-        .collect::<Result<Vec<CollectionOfferResponse>, _>>()?;
+        .collect::<StdResult<Vec<CollectionOfferResponse>>>()?;
 
     Ok(MultipleCollectionOffersResponse {
         next_offer: if offers.len() == limit {
-            offers.last().map(|last| {
-                (
-                    last.collection_offer_info.collection.to_string(),
-                    last.global_offer_id.clone(),
-                )
-            })
+            offers
+                .last()
+                .map(|last| (collection_addr.to_string(), last.global_offer_id.clone()))
         } else {
             None
         },
@@ -205,14 +280,65 @@ pub fn query_collection_offers(
     })
 }
 
+fn join_collections(collections: &[Addr]) -> String {
+    collections
+        .iter()
+        .map(Addr::as_str)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Indexes a collection offer under every collection it accepts
+/// (a `MultiIndex` can only index an offer under a single key)
+pub struct CollectionsIndex<'a> {
+    pub idx: Map<'a, (Addr, String), Empty>,
+}
+
+impl<'a> CollectionsIndex<'a> {
+    pub const fn new(idx_namespace: &'a str) -> Self {
+        CollectionsIndex {
+            idx: Map::new(idx_namespace),
+        }
+    }
+}
+
+impl<'a> Index<CollectionOfferInfo> for CollectionsIndex<'a> {
+    fn save(
+        &self,
+        store: &mut dyn Storage,
+        pk: &[u8],
+        data: &CollectionOfferInfo,
+    ) -> StdResult<()> {
+        let pk = String::from_utf8(pk.to_vec())?;
+        data.collections.iter().try_for_each(|collection| {
+            self.idx
+                .save(store, (collection.clone(), pk.clone()), &Empty {})
+        })
+    }
+
+    fn remove(
+        &self,
+        store: &mut dyn Storage,
+        pk: &[u8],
+        old_data: &CollectionOfferInfo,
+    ) -> StdResult<()> {
+        let pk = String::from_utf8(pk.to_vec())?;
+        old_data
+            .collections
+            .iter()
+            .for_each(|collection| self.idx.remove(store, (collection.clone(), pk.clone())));
+        Ok(())
+    }
+}
+
 pub struct CollectionOfferIndexes<'a> {
     pub lender: MultiIndex<'a, Addr, CollectionOfferInfo, String>,
-    pub collection: MultiIndex<'a, Addr, CollectionOfferInfo, String>,
+    pub collections: CollectionsIndex<'a>,
 }
 
 impl<'a> IndexList<CollectionOfferInfo> for CollectionOfferIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<CollectionOfferInfo>> + '_> {
-        let v: Vec<&dyn Index<CollectionOfferInfo>> = vec![&self.lender, &self.collection];
+        let v: Vec<&dyn Index<CollectionOfferInfo>> = vec![&self.lender, &self.collections];
         Box::new(v.into_iter())
     }
 }
@@ -225,11 +351,7 @@ pub fn collection_offers<'a>(
             "collection_offers",
             "collection_offers__lender",
         ),
-        collection: MultiIndex::new(
-            |_, d: &CollectionOfferInfo| d.collection.clone(),
-            "collection_offers",
-            "collection_offers__collection",
-        ),
+        collections: CollectionsIndex::new("collection_offers__collections"),
     };
     IndexedMap::new("collection_offers", indexes)
 }
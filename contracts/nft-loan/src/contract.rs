@@ -14,16 +14,17 @@ use utils::{
 use crate::{
     collection_offer::execute_accept_collection_offer,
     query::{
-        query_all_collaterals, query_borrower_info, query_collateral_info, query_collaterals,
-        query_config, query_lender_offers, query_offer_info, query_offers,
+        query_all_collaterals, query_amount_owed, query_borrower_info, query_collateral_info,
+        query_collaterals, query_config, query_lender_offers, query_offer_info, query_offers,
     },
-    state::OLD_CONFIG,
+    state::{migrate_loan_terms, OLD_CONFIG},
 };
 use crate::{
     collection_offer::execute_make_collection_offer,
     execute::{
-        accept_loan, accept_offer, cancel_offer, execute_toggle_lock, list_collaterals, make_offer,
-        modify_collaterals, refuse_offer, repay_borrowed_funds, withdraw_collateral,
+        accept_extension, accept_loan, accept_offer, cancel_extension, cancel_offer,
+        execute_receive, execute_toggle_lock, list_collaterals, make_offer, modify_collaterals,
+        partial_repay, propose_extension, refuse_offer, repay_borrowed_funds, withdraw_collateral,
         withdraw_defaulted_loan, withdraw_refused_offer,
     },
 };
@@ -90,21 +91,25 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), ::cosmwasm_std::entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
-    let old_config = OLD_CONFIG.load(deps.storage)?;
+    // The config is only migrated if it's still in the old format
+    if let Ok(old_config) = OLD_CONFIG.load(deps.storage) {
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                name: old_config.name,
+                owner: old_config.owner,
+                treasury_addr: old_config.treasury_addr,
+                fee_rate: old_config.fee_rate,
+                listing_fee_coins: old_config.listing_fee_coins,
+                global_offer_index: old_config.global_offer_index,
+                global_collection_offer_index: 0,
+                locks: old_config.locks,
+            },
+        )?;
+    }
 
-    CONFIG.save(
-        deps.storage,
-        &Config {
-            name: old_config.name,
-            owner: old_config.owner,
-            treasury_addr: old_config.treasury_addr,
-            fee_rate: old_config.fee_rate,
-            listing_fee_coins: old_config.listing_fee_coins,
-            global_offer_index: old_config.global_offer_index,
-            global_collection_offer_index: 0,
-            locks: old_config.locks,
-        },
-    )?;
+    // Loan terms now accept CW20 principles
+    migrate_loan_terms(deps.storage)?;
 
     set_contract_version(
         deps.storage,
@@ -181,9 +186,17 @@ pub fn execute(
         ExecuteMsg::RepayBorrowedFunds { loan_id } => {
             repay_borrowed_funds(deps, env, info, loan_id)
         }
+        ExecuteMsg::PartialRepay { loan_id } => partial_repay(deps, env, info, loan_id),
         ExecuteMsg::WithdrawDefaultedLoan { borrower, loan_id } => {
             withdraw_defaulted_loan(deps, env, info, borrower, loan_id)
         }
+        ExecuteMsg::ProposeExtension { loan_id, duration } => {
+            propose_extension(deps, env, info, loan_id, duration)
+        }
+        ExecuteMsg::AcceptExtension { borrower, loan_id } => {
+            accept_extension(deps, env, info, borrower, loan_id)
+        }
+        ExecuteMsg::CancelExtension { loan_id } => cancel_extension(deps, info, loan_id),
         ExecuteMsg::SetOwner { owner } => set_owner(deps, env, info, owner),
         ExecuteMsg::SetFeeDestination { treasury_addr } => {
             set_fee_distributor(deps, env, info, treasury_addr)
@@ -194,17 +207,29 @@ pub fn execute(
         ExecuteMsg::SetFeeRate { fee_rate } => set_fee_rate(deps, env, info, fee_rate),
         ExecuteMsg::ToggleLock { lock } => execute_toggle_lock(deps, info, env, lock),
         ExecuteMsg::MakeCollectionOffer {
-            collection,
+            collections,
+            token_count,
             terms,
             comment,
-        } => execute_make_collection_offer(deps, env, info, collection, terms, comment),
+            traits,
+        } => execute_make_collection_offer(
+            deps,
+            env,
+            info,
+            collections,
+            token_count,
+            terms,
+            comment,
+            traits,
+        ),
         ExecuteMsg::WithdrawCollectionOffer {
             collection_offer_id,
         } => execute_withdraw_collection_offer(deps, env, info, collection_offer_id),
         ExecuteMsg::AcceptCollectionOffer {
-            token,
+            tokens,
             collection_offer_id,
-        } => execute_accept_collection_offer(deps, env, info, collection_offer_id, token),
+        } => execute_accept_collection_offer(deps, env, info, collection_offer_id, tokens),
+        ExecuteMsg::Receive(wrapper) => execute_receive(deps, env, info, wrapper),
     }
 }
 
@@ -218,6 +243,14 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::CollateralInfo { borrower, loan_id } => {
             to_json_binary(&query_collateral_info(deps, env, borrower, loan_id)?)
         }
+        QueryMsg::AmountOwed {
+            borrower,
+            loan_id,
+            height,
+            time,
+        } => to_json_binary(&query_amount_owed(
+            deps, env, borrower, loan_id, height, time,
+        )?),
         QueryMsg::Collaterals {
             borrower,
             start_after,
@@ -35,6 +35,9 @@ pub enum ContractError {
     #[error("Fund sent do not match the loan terms")]
     FundsDontMatchTerms {},
 
+    #[error("The loan principle can only be a native coin or a CW20 token")]
+    InvalidPrinciple {},
+
     #[error("Fund sent do not match the loan terms, {0}, {1}")]
     FundsDontMatchTermsAndPrinciple(Uint128, Uint128),
 
@@ -59,6 +62,30 @@ pub enum ContractError {
     #[error("Sorry, you can't make an offer on this loan")]
     NotCounterable {},
 
+    #[error("The new principle ({principle}) doesn't cover the amount owed on the loan ({owed})")]
+    RefinanceNotCovered { principle: Uint128, owed: Uint128 },
+
+    #[error("Please include at least one collection in a collection offer")]
+    NoCollections {},
+
+    #[error("This collection offer requires {expected} tokens, {actual} were provided")]
+    WrongTokenCount { expected: u32, actual: u32 },
+
+    #[error("The collection {collection} is not part of this collection offer")]
+    CollectionNotInOffer { collection: String },
+
+    #[error("The same token can't be provided twice")]
+    DuplicateToken {},
+
+    #[error("The token doesn't have the traits required by this collection offer")]
+    TraitsNotMatched {},
+
+    #[error("An extension was already proposed for this loan")]
+    ExtensionAlreadyProposed {},
+
+    #[error("No extension was proposed for this loan")]
+    NoExtensionProposed {},
+
     #[error("Sorry, you can't refuse this offer, it's not published")]
     NotRefusable {},
 
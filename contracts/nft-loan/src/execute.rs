@@ -7,26 +7,33 @@ use {
 
 use {
     crate::{
+        collection_offer::_make_collection_offer,
         error::{self, ContractError},
-        helpers::assert_listing_fee,
+        helpers::{
+            assert_listing_fee, asset_from_funds, fungible_amount, fungible_transfer_msg,
+            is_same_fungible,
+        },
         lender_offer::lender_offers,
-        query::{is_approved_cw721, is_nft_owner},
+        msg::ReceiveMsg,
+        query::{is_approved_cw1155, is_approved_cw721, is_cw1155_owner, is_nft_owner},
         state::{
             can_repay_loan, get_active_loan, get_offer, is_active_lender,
             is_collateral_withdrawable, is_lender, is_loan_acceptable, is_loan_counterable,
-            is_loan_defaulted, is_loan_modifiable, is_offer_borrower, is_offer_refusable,
-            save_offer, BorrowerInfo, CollateralInfo, LoanState, LoanTerms, OfferInfo, OfferState,
-            BORROWER_INFO, COLLATERAL_INFO, CONFIG,
+            is_loan_defaulted, is_loan_modifiable, is_loan_refinanceable, is_offer_borrower,
+            is_offer_refusable, save_offer, BorrowerInfo, CollateralInfo, LoanExtension, LoanState,
+            LoanTerms, OfferInfo, OfferState, BORROWER_INFO, COLLATERAL_INFO, CONFIG,
         },
     },
     cosmwasm_std::{
-        coins, ensure_eq, Addr, Attribute, BankMsg, Coin, Decimal, DepsMut, Empty, Env,
-        MessageInfo, StdError, StdResult, Storage,
+        ensure_eq, from_json, Addr, Attribute, Decimal, DepsMut, Empty, Env, MessageInfo, StdError,
+        StdResult, Storage, Uint128,
     },
+    cw1155::Cw1155ExecuteMsg,
+    cw20::Cw20ReceiveMsg,
     cw721::Cw721ExecuteMsg,
     cw721_base::Extension,
     utils::{
-        state::{is_valid_comment, AssetInfo, Cw721Coin, Sg721Token},
+        state::{is_valid_comment, AssetInfo, Cw1155Coin, Cw20Coin, Cw721Coin, Sg721Token},
         types::{CosmosMsg, Response},
     },
 };
@@ -70,7 +77,7 @@ pub fn list_collaterals(
 /// The borrower (the person that deposits collaterals) can specify terms at which they wish to borrow funds against their collaterals.
 /// If terms are specified, fund lenders can accept the loan directly.
 /// If not, lenders can propose terms than may be accepted by the borrower in return to start the loan
-/// This deposit function allows CW721 and SG721 tokens to be listed
+/// This deposit function allows CW721, SG721 and CW1155 tokens to be listed
 pub fn _internal_list_collaterals(
     deps: DepsMut,
     env: Env,
@@ -84,6 +91,9 @@ pub fn _internal_list_collaterals(
     if tokens.is_empty() {
         return Err(ContractError::NoAssets {});
     }
+    if let Some(terms) = &terms {
+        terms.validate(deps.api)?;
+    }
 
     tokens.iter().try_for_each(|token| match token {
         AssetInfo::Cw721Coin(Cw721Coin { address, token_id }) => {
@@ -121,6 +131,27 @@ pub fn _internal_list_collaterals(
                 token_id.clone(),
             )
         }
+        AssetInfo::Cw1155Coin(Cw1155Coin {
+            address,
+            token_id,
+            value,
+        }) => {
+            // asserts borrower holds enough tokens
+            is_cw1155_owner(
+                deps.as_ref(),
+                borrower.clone(),
+                address.to_string(),
+                token_id.to_string(),
+                *value,
+            )?;
+            // asserts tokens have been approved for use by loan contract
+            is_approved_cw1155(
+                deps.as_ref(),
+                env.clone(),
+                borrower.clone(),
+                address.clone(),
+            )
+        }
         _ => Err(ContractError::SenderNotOwner {}),
     })?;
 
@@ -186,6 +217,9 @@ pub fn modify_collaterals(
     loan_preview: Option<AssetInfo>,
 ) -> Result<Response, ContractError> {
     let borrower = info.sender;
+    if let Some(terms) = &terms {
+        terms.validate(deps.api)?;
+    }
 
     COLLATERAL_INFO.update(
         deps.storage,
@@ -263,6 +297,27 @@ pub fn accept_loan(
     borrower: String,
     loan_id: u64,
     comment: Option<String>,
+) -> Result<Response, ContractError> {
+    let sent_assets = asset_from_funds(info.funds)?;
+    _accept_loan(
+        deps,
+        env,
+        info.sender,
+        sent_assets,
+        borrower,
+        loan_id,
+        comment,
+    )
+}
+
+pub fn _accept_loan(
+    deps: DepsMut,
+    env: Env,
+    lender: Addr,
+    sent_assets: AssetInfo,
+    borrower: String,
+    loan_id: u64,
+    comment: Option<String>,
 ) -> Result<Response, ContractError> {
     // We query the loan info
     let borrower_addr = deps.api.addr_validate(&borrower)?;
@@ -275,13 +330,16 @@ pub fn accept_loan(
         )));
     }
 
+    // A started loan can only be refinanced through a separate offer
+    is_loan_counterable(&collateral)?;
+
     // We start by making an offer with exactly the same terms as the depositor specified
     let terms: LoanTerms = collateral.terms.ok_or(ContractError::NoTermsSpecified {})?;
     let (global_offer_id, _offer_id) = _make_offer_raw(
         deps.storage,
         env.clone(),
-        info.sender,
-        info.funds,
+        lender,
+        sent_assets,
         borrower_addr,
         loan_id,
         terms,
@@ -295,6 +353,7 @@ pub fn accept_loan(
 }
 
 // It verifies an offer can be made for the current loan
+// Offers made on a started loan are saved as refinance offers
 // It verifies the sent funds match the principle indicated in the terms
 // And then saves the new offer in the internal storage
 #[allow(clippy::too_many_arguments)]
@@ -302,7 +361,7 @@ pub fn _make_offer_raw(
     storage: &mut dyn Storage,
     env: Env,
     lender: Addr,
-    sent_funds: Vec<Coin>,
+    sent_assets: AssetInfo,
     borrower: Addr,
     loan_id: u64,
     terms: LoanTerms,
@@ -317,12 +376,19 @@ pub fn _make_offer_raw(
 
     let mut collateral: CollateralInfo =
         COLLATERAL_INFO.load(storage, (borrower.clone(), loan_id))?;
-    is_loan_counterable(&collateral)?;
+    let refinance = match collateral.state {
+        LoanState::Started => {
+            can_repay_loan(storage, env.clone(), &collateral)?;
+            true
+        }
+        _ => {
+            is_loan_counterable(&collateral)?;
+            false
+        }
+    };
 
     // Make sure the transaction contains funds that match the principle indicated in the terms
-    if sent_funds.len() != 1 {
-        return Err(ContractError::MultipleCoins {});
-    } else if terms.principle != sent_funds[0].clone() {
+    if terms.principle != sent_assets {
         return Err(ContractError::FundsDontMatchTerms {});
     }
 
@@ -347,6 +413,7 @@ pub fn _make_offer_raw(
             list_date: env.block.time,
             deposited_funds: Some(terms.principle),
             comment,
+            refinance,
         },
     )?;
 
@@ -373,6 +440,7 @@ pub fn _accept_offer_raw(
         // We can start the loan now !
         collateral.state = LoanState::Started;
         collateral.start_block = Some(env.block.height);
+        collateral.start_time = Some(env.block.time);
         collateral.active_offer = Some(global_offer_id.clone());
         offer.state = OfferState::Accepted;
 
@@ -428,6 +496,31 @@ pub fn _accept_offer_raw(
                     None,
                 )?)
             }
+            AssetInfo::Cw1155Coin(Cw1155Coin {
+                address,
+                token_id,
+                value,
+            }) => {
+                is_cw1155_owner(
+                    deps.as_ref(),
+                    borrower.clone(),
+                    address.to_string(),
+                    token_id.to_string(),
+                    *value,
+                )?;
+
+                Ok(into_cosmos_msg(
+                    Cw1155ExecuteMsg::SendFrom {
+                        from: borrower.to_string(),
+                        to: env.contract.address.to_string(),
+                        token_id: token_id.to_string(),
+                        value: *value,
+                        msg: None,
+                    },
+                    address,
+                    None,
+                )?)
+            }
             _ => Err(ContractError::WrongAssetDeposited {}),
         })
         .collect::<Result<Vec<CosmosMsg>, ContractError>>()?;
@@ -436,35 +529,118 @@ pub fn _accept_offer_raw(
         .add_message(fund_messages)
         .add_messages(asset_messages)
         .add_attribute("action", "start_loan")
-        .add_attribute("denom_borrowed", offer.terms.principle.denom)
-        .add_attribute("amount_borrowed", offer.terms.principle.amount.to_string())
+        .add_attribute("denom_borrowed", offer.terms.principle.debug_str())
+        .add_attribute(
+            "amount_borrowed",
+            offer.terms.principle_amount()?.to_string(),
+        )
         .add_attribute("borrower", borrower)
         .add_attribute("lender", offer.lender)
         .add_attribute("loan_id", loan_id.to_string())
         .add_attribute("global_offer_id", global_offer_id))
 }
 
+// internal function that switches a started loan to a refinance offer
+// The new principle pays back the current lender (principle + accrued interest - fee)
+// and the rest is sent to the borrower. The collateral never leaves the contract
+pub fn _refinance_loan(
+    deps: DepsMut,
+    env: Env,
+    global_offer_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut offer = get_offer(deps.storage, &global_offer_id)?;
+
+    let borrower = offer.borrower.clone();
+    let loan_id = offer.loan_id;
+    let mut collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
+    can_repay_loan(deps.storage, env.clone(), &collateral)?;
+    if offer.state != OfferState::Published {
+        return Err(ContractError::WrongOfferState { state: offer.state });
+    }
+    let previous_offer = get_active_loan(deps.storage, &collateral)?;
+
+    // The new principle has to cover everything that is owed to the current lender
+    let (principle_owed, interest_owed) =
+        collateral.amount_owed(&previous_offer.terms, &env.block)?;
+    let owed = principle_owed + interest_owed;
+    let new_principle = offer.terms.principle_amount()?;
+    if !is_same_fungible(&previous_offer.terms.principle, &offer.terms.principle) {
+        return Err(ContractError::FundsDontMatchTerms {});
+    } else if new_principle < owed {
+        return Err(ContractError::RefinanceNotCovered {
+            principle: new_principle,
+            owed,
+        });
+    }
+
+    // The new loan starts now
+    let previous_offer_id = collateral.active_offer.replace(global_offer_id.clone());
+    collateral.start_block = Some(env.block.height);
+    collateral.start_time = Some(env.block.time);
+    collateral.principle_repaid = Uint128::zero();
+    collateral.interest_repaid = Uint128::zero();
+    collateral.interest_checkpoint = Uint128::zero();
+    collateral.checkpoint_elapsed = 0;
+    offer.state = OfferState::Accepted;
+    COLLATERAL_INFO.save(deps.storage, (borrower.clone(), loan_id), &collateral)?;
+    save_offer(deps.storage, &global_offer_id, offer.clone())?;
+
+    // The fee is only taken on the interest, like when repaying a loan
+    let lender_payback = principle_owed + interest_owed * (Decimal::one() - config.fee_rate);
+    let treasury_payback = owed - lender_payback;
+    let borrower_payback = new_principle - owed;
+
+    let mut res = Response::new();
+    for (amount, recipient) in [
+        (lender_payback, &previous_offer.lender),
+        (treasury_payback, &config.treasury_addr),
+        (borrower_payback, &borrower),
+    ] {
+        if !amount.is_zero() {
+            res = res.add_message(fungible_transfer_msg(
+                &offer.terms.principle,
+                amount,
+                recipient,
+            )?);
+        }
+    }
+
+    Ok(res
+        .add_attribute("action", "refinance_loan")
+        .add_attribute("borrower", borrower)
+        .add_attribute("previous_lender", previous_offer.lender)
+        .add_attribute("lender", offer.lender)
+        .add_attribute("loan_id", loan_id.to_string())
+        .add_attribute("previous_offer_id", previous_offer_id.unwrap_or_default())
+        .add_attribute("global_offer_id", global_offer_id)
+        .add_attribute("principle_repaid", principle_owed)
+        .add_attribute("interest_repaid", interest_owed))
+}
+
 /// This creates withdraw messages to withdraw the funds from an offer (to the lender of the borrower depending on the situation
 /// This function does not do any checks on the validity of the procedure
 /// Be careful when using this internal function
 pub fn _withdraw_offer_unsafe(
     recipient: Addr,
     offer_info: &OfferInfo,
-) -> Result<BankMsg, ContractError> {
+) -> Result<CosmosMsg, ContractError> {
     // We get the funds to withdraw
     let funds_to_withdraw = offer_info
         .deposited_funds
-        .clone()
+        .as_ref()
         .ok_or(ContractError::NoFundsToWithdraw {})?;
 
-    Ok(BankMsg::Send {
-        to_address: recipient.to_string(),
-        amount: vec![funds_to_withdraw],
-    })
+    fungible_transfer_msg(
+        funds_to_withdraw,
+        fungible_amount(funds_to_withdraw)?,
+        &recipient,
+    )
 }
 
 /// Accept an offer someone made for your collateral
 /// As soon as the borrower executes this messages, the loan starts and the they will need to repay the loan before the term
+/// If the loan is already started, the offer refinances it and the current lender is paid back with the new principle
 pub fn accept_offer(
     deps: DepsMut,
     env: Env,
@@ -479,10 +655,14 @@ pub fn accept_offer(
     }
 
     // We make sure the caller is the borrower
-    is_offer_borrower(deps.storage, info.sender, &global_offer_id)?;
+    let offer = is_offer_borrower(deps.storage, info.sender, &global_offer_id)?;
 
     // We accept the offer
-    let res = _accept_offer_raw(deps, env, global_offer_id)?;
+    let res = if offer.refinance {
+        _refinance_loan(deps, env, global_offer_id)?
+    } else {
+        _accept_offer_raw(deps, env, global_offer_id)?
+    };
 
     Ok(res.add_attribute("action_type", "accept_offer"))
 }
@@ -498,9 +678,36 @@ pub fn make_offer(
     terms: LoanTerms,
     comment: Option<String>,
     on_behalf_of: Option<String>,
+) -> Result<Response, ContractError> {
+    let sent_assets = asset_from_funds(info.funds)?;
+    _make_offer(
+        deps,
+        env,
+        info.sender,
+        sent_assets,
+        borrower,
+        loan_id,
+        terms,
+        comment,
+        on_behalf_of,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn _make_offer(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    sent_assets: AssetInfo,
+    borrower: String,
+    loan_id: u64,
+    terms: LoanTerms,
+    comment: Option<String>,
+    on_behalf_of: Option<String>,
 ) -> Result<Response, ContractError> {
     // We query the loan info
     let borrower = deps.api.addr_validate(&borrower)?;
+    terms.validate(deps.api)?;
 
     // checks comment size
     if !is_valid_comment(&comment.clone().unwrap_or_default()) {
@@ -512,13 +719,13 @@ pub fn make_offer(
     let lender = on_behalf_of
         .map(|o| deps.api.addr_validate(&o))
         .transpose()?
-        .unwrap_or(info.sender);
+        .unwrap_or(sender);
 
     let (global_offer_id, _offer_id) = _make_offer_raw(
         deps.storage,
         env,
         lender.clone(),
-        info.funds,
+        sent_assets,
         borrower.clone(),
         loan_id,
         terms,
@@ -557,7 +764,12 @@ pub fn cancel_offer(
     let loan_id = offer_info.loan_id;
     let collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
     // We can cancel an offer only if the Borrower is still searching for a loan (the loan is modifyable)
-    is_loan_modifiable(&collateral)?;
+    // Refinance offers can be cancelled while the loan is running
+    if offer_info.refinance {
+        is_loan_refinanceable(&collateral)?;
+    } else {
+        is_loan_modifiable(&collateral)?;
+    }
 
     // The funds deposited for lending are withdrawn
     let withdraw_response = _withdraw_offer_unsafe(lender.clone(), &offer_info)?;
@@ -657,44 +869,55 @@ pub fn repay_borrowed_funds(
     env: Env,
     info: MessageInfo,
     loan_id: u64,
+) -> Result<Response, ContractError> {
+    let sent_assets = asset_from_funds(info.funds)?;
+    _repay_borrowed_funds(deps, env, info.sender, sent_assets, loan_id)
+}
+
+pub fn _repay_borrowed_funds(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    sent_assets: AssetInfo,
+    loan_id: u64,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     // We query the loan info
-    let borrower = info.sender;
     let mut collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
     can_repay_loan(deps.storage, env.clone(), &collateral)?;
     let offer_info = get_active_loan(deps.storage, &collateral)?;
 
-    // We verify the sent funds correspond to the principle + interests
-    let interests = offer_info.terms.interest;
-    if info.funds.len() != 1 {
-        return Err(ContractError::MultipleCoins {});
-    } else if offer_info.terms.principle.denom != info.funds[0].denom.clone() {
+    // We verify the sent funds correspond to the outstanding principle + interests
+    let (principle, interests) = collateral.amount_owed(&offer_info.terms, &env.block)?;
+    let sent_amount = fungible_amount(&sent_assets)?;
+    if !is_same_fungible(&offer_info.terms.principle, &sent_assets) {
         return Err(ContractError::FundsDontMatchTerms {});
-    } else if offer_info.terms.principle.amount + interests > info.funds[0].amount {
+    } else if principle + interests > sent_amount {
         return Err(ContractError::FundsDontMatchTermsAndPrinciple(
-            offer_info.terms.principle.amount + interests,
-            info.funds[0].amount,
+            principle + interests,
+            sent_amount,
         ));
     }
 
     // We save the collateral state
     collateral.state = LoanState::Ended;
+    collateral.principle_repaid += principle;
+    collateral.interest_repaid += interests;
     COLLATERAL_INFO.save(deps.storage, (borrower.clone(), loan_id), &collateral)?;
 
     // We prepare the funds to send back to the lender
     // % of interest expected back
-    let lender_payback =
-        offer_info.terms.principle.amount + interests * (Decimal::one() - config.fee_rate);
-    let treasury_payback = info.funds[0].amount - lender_payback;
+    let lender_payback = principle + interests * (Decimal::one() - config.fee_rate);
+    let treasury_payback = sent_amount - lender_payback;
 
     let mut res = Response::new();
     // lender is paid back
     if lender_payback.u128() > 0u128 {
-        res = res.add_message(BankMsg::Send {
-            to_address: offer_info.lender.to_string(),
-            amount: coins(lender_payback.u128(), info.funds[0].denom.clone()),
-        })
+        res = res.add_message(fungible_transfer_msg(
+            &sent_assets,
+            lender_payback,
+            &offer_info.lender,
+        )?)
     }
 
     // add the msg to withdraw_loan collateral back to the borrower*
@@ -706,10 +929,11 @@ pub fn repay_borrowed_funds(
 
     // And we pay the fee to the treasury
     if treasury_payback.u128() > 0u128 {
-        res = res.add_message(BankMsg::Send {
-            to_address: config.treasury_addr.to_string(),
-            amount: coins(treasury_payback.u128(), info.funds[0].denom.clone()),
-        });
+        res = res.add_message(fungible_transfer_msg(
+            &sent_assets,
+            treasury_payback,
+            &config.treasury_addr,
+        )?);
     }
     // attributes
     Ok(res
@@ -720,6 +944,143 @@ pub fn repay_borrowed_funds(
         .add_attribute("interest", interests))
 }
 
+/// This function receives part of the principle + interest funds owed on a loan
+/// The loan stays active until everything is paid back
+pub fn partial_repay(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    loan_id: u64,
+) -> Result<Response, ContractError> {
+    let sent_assets = asset_from_funds(info.funds)?;
+    _partial_repay(deps, env, info.sender, sent_assets, loan_id)
+}
+
+/// Pays back part of a loan. The accrued interest is paid first, then the principle.
+/// If the sent funds cover everything that is owed, the loan is repaid and the collateral is released
+pub fn _partial_repay(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    sent_assets: AssetInfo,
+    loan_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
+    can_repay_loan(deps.storage, env.clone(), &collateral)?;
+    let offer_info = get_active_loan(deps.storage, &collateral)?;
+
+    if !is_same_fungible(&offer_info.terms.principle, &sent_assets) {
+        return Err(ContractError::FundsDontMatchTerms {});
+    }
+    let (principle_owed, interest_owed) = collateral.amount_owed(&offer_info.terms, &env.block)?;
+    let sent_amount = fungible_amount(&sent_assets)?;
+    if sent_amount >= principle_owed + interest_owed {
+        return _repay_borrowed_funds(deps, env, borrower, sent_assets, loan_id);
+    }
+
+    // The interest is paid before the principle
+    let interest_paid = sent_amount.min(interest_owed);
+    let principle_paid = sent_amount - interest_paid;
+    // From now on, interest accrues on the remaining principle only
+    collateral.checkpoint_interest(&offer_info.terms, &env.block)?;
+    collateral.interest_repaid += interest_paid;
+    collateral.principle_repaid += principle_paid;
+    COLLATERAL_INFO.save(deps.storage, (borrower.clone(), loan_id), &collateral)?;
+
+    // The treasury only takes a share of the interest
+    let lender_payback = principle_paid + interest_paid * (Decimal::one() - config.fee_rate);
+    let treasury_payback = sent_amount - lender_payback;
+
+    let mut res = Response::new();
+    if !lender_payback.is_zero() {
+        res = res.add_message(fungible_transfer_msg(
+            &sent_assets,
+            lender_payback,
+            &offer_info.lender,
+        )?);
+    }
+    if !treasury_payback.is_zero() {
+        res = res.add_message(fungible_transfer_msg(
+            &sent_assets,
+            treasury_payback,
+            &config.treasury_addr,
+        )?);
+    }
+
+    Ok(res
+        .add_attribute("action", "partial_repay")
+        .add_attribute("borrower", borrower)
+        .add_attribute("lender", offer_info.lender)
+        .add_attribute("loan_id", loan_id.to_string())
+        .add_attribute("principle_repaid", principle_paid)
+        .add_attribute("interest_repaid", interest_paid)
+        .add_attribute("principle_owed", principle_owed - principle_paid))
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let sent_assets = AssetInfo::Cw20Coin(Cw20Coin {
+        address: info.sender.to_string(),
+        amount: wrapper.amount,
+    });
+
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::MakeOffer {
+            borrower,
+            loan_id,
+            terms,
+            comment,
+            on_behalf_of,
+        } => _make_offer(
+            deps,
+            env,
+            sender,
+            sent_assets,
+            borrower,
+            loan_id,
+            terms,
+            comment,
+            on_behalf_of,
+        ),
+        ReceiveMsg::AcceptLoan {
+            borrower,
+            loan_id,
+            comment,
+        } => _accept_loan(deps, env, sender, sent_assets, borrower, loan_id, comment),
+        ReceiveMsg::MakeCollectionOffer {
+            collections,
+            token_count,
+            terms,
+            comment,
+            traits,
+        } => _make_collection_offer(
+            deps,
+            sender,
+            sent_assets,
+            collections,
+            token_count,
+            terms,
+            comment,
+            traits,
+        ),
+        ReceiveMsg::RepayBorrowedFunds { loan_id } => {
+            _repay_borrowed_funds(deps, env, sender, sent_assets, loan_id)
+        }
+        ReceiveMsg::PartialRepay { loan_id } => {
+            _partial_repay(deps, env, sender, sent_assets, loan_id)
+        }
+        ReceiveMsg::ProposeExtension { loan_id, duration } => {
+            _propose_extension(deps, env, sender, sent_assets, loan_id, duration)
+        }
+    }
+}
+
 /// Withdraw the collateral from a defaulted loan
 /// If the loan duration has exceeded, the collateral can be withdrawn by the lender
 /// This closes the loan and puts it in a defaulted state
@@ -756,6 +1117,148 @@ pub fn withdraw_defaulted_loan(
         .add_attribute("loan_id", loan_id.to_string()))
 }
 
+/// Propose to extend a running loan
+/// The funds sent are the extra interest the lender receives if they accept the extension
+/// Only one extension can be pending at a time
+pub fn propose_extension(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    loan_id: u64,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let sent_assets = asset_from_funds(info.funds)?;
+    _propose_extension(deps, env, info.sender, sent_assets, loan_id, duration)
+}
+
+pub fn _propose_extension(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    sent_assets: AssetInfo,
+    loan_id: u64,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let mut collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
+    can_repay_loan(deps.storage, env.clone(), &collateral)?;
+    let offer_info = get_active_loan(deps.storage, &collateral)?;
+
+    if collateral.pending_extension.is_some() {
+        return Err(ContractError::ExtensionAlreadyProposed {});
+    }
+    if duration == 0 {
+        return Err(ContractError::InvalidAmount {});
+    }
+    // The extra interest is paid in the same currency as the loan
+    if !is_same_fungible(&offer_info.terms.principle, &sent_assets) {
+        return Err(ContractError::FundsDontMatchTerms {});
+    }
+
+    collateral.pending_extension = Some(LoanExtension {
+        global_offer_id: collateral
+            .active_offer
+            .clone()
+            .ok_or(ContractError::OfferNotFound {})?,
+        duration,
+        interest: sent_assets.clone(),
+        proposed_block: env.block.height,
+        accepted_block: None,
+    });
+    COLLATERAL_INFO.save(deps.storage, (borrower.clone(), loan_id), &collateral)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_extension")
+        .add_attribute("borrower", borrower)
+        .add_attribute("lender", offer_info.lender)
+        .add_attribute("loan_id", loan_id.to_string())
+        .add_attribute("duration", duration.to_string())
+        .add_attribute("interest", fungible_amount(&sent_assets)?))
+}
+
+/// Accept the extension proposed by the borrower
+/// The loan deadline is pushed back and the extra interest is paid to the lender (minus the fee)
+pub fn accept_extension(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: String,
+    loan_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let borrower = deps.api.addr_validate(&borrower)?;
+    let mut collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
+    can_repay_loan(deps.storage, env.clone(), &collateral)?;
+    let offer_info = is_active_lender(deps.storage, info.sender, &collateral)?;
+
+    // The extension has to be proposed for the current lender
+    let mut extension = match collateral.pending_extension.take() {
+        Some(extension) if Some(&extension.global_offer_id) == collateral.active_offer.as_ref() => {
+            extension
+        }
+        _ => return Err(ContractError::NoExtensionProposed {}),
+    };
+    extension.accepted_block = Some(env.block.height);
+    collateral.extensions.push(extension.clone());
+    COLLATERAL_INFO.save(deps.storage, (borrower.clone(), loan_id), &collateral)?;
+
+    // The fee is taken on the extra interest
+    let interest = fungible_amount(&extension.interest)?;
+    let lender_payback = interest * (Decimal::one() - config.fee_rate);
+    let treasury_payback = interest - lender_payback;
+
+    let mut res = Response::new();
+    if !lender_payback.is_zero() {
+        res = res.add_message(fungible_transfer_msg(
+            &extension.interest,
+            lender_payback,
+            &offer_info.lender,
+        )?);
+    }
+    if !treasury_payback.is_zero() {
+        res = res.add_message(fungible_transfer_msg(
+            &extension.interest,
+            treasury_payback,
+            &config.treasury_addr,
+        )?);
+    }
+
+    Ok(res
+        .add_attribute("action", "accept_extension")
+        .add_attribute("borrower", borrower)
+        .add_attribute("lender", offer_info.lender)
+        .add_attribute("loan_id", loan_id.to_string())
+        .add_attribute("duration", extension.duration.to_string())
+        .add_attribute("interest", interest))
+}
+
+/// Withdraw a pending extension proposal
+/// The extra interest is sent back to the borrower
+pub fn cancel_extension(
+    deps: DepsMut,
+    info: MessageInfo,
+    loan_id: u64,
+) -> Result<Response, ContractError> {
+    let borrower = info.sender;
+    let mut collateral = COLLATERAL_INFO.load(deps.storage, (borrower.clone(), loan_id))?;
+    let extension = collateral
+        .pending_extension
+        .take()
+        .ok_or(ContractError::NoExtensionProposed {})?;
+    COLLATERAL_INFO.save(deps.storage, (borrower.clone(), loan_id), &collateral)?;
+
+    let refund_message = fungible_transfer_msg(
+        &extension.interest,
+        fungible_amount(&extension.interest)?,
+        &borrower,
+    )?;
+
+    Ok(Response::new()
+        .add_message(refund_message)
+        .add_attribute("action", "cancel_extension")
+        .add_attribute("borrower", borrower)
+        .add_attribute("loan_id", loan_id.to_string()))
+}
+
 pub fn _withdraw_loan(
     collateral: CollateralInfo,
     sender: Addr,
@@ -768,7 +1271,7 @@ pub fn _withdraw_loan(
         .collect()
 }
 
-pub fn _withdraw_asset(asset: &AssetInfo, _sender: Addr, recipient: Addr) -> StdResult<CosmosMsg> {
+pub fn _withdraw_asset(asset: &AssetInfo, sender: Addr, recipient: Addr) -> StdResult<CosmosMsg> {
     match asset {
         AssetInfo::Cw721Coin(cw721) => into_cosmos_msg(
             Cw721ExecuteMsg::TransferNft {
@@ -787,6 +1290,17 @@ pub fn _withdraw_asset(asset: &AssetInfo, _sender: Addr, recipient: Addr) -> Std
             sg721.address.clone(),
             None,
         ),
+        AssetInfo::Cw1155Coin(cw1155) => into_cosmos_msg(
+            Cw1155ExecuteMsg::SendFrom {
+                from: sender.to_string(),
+                to: recipient.to_string(),
+                token_id: cw1155.token_id.clone(),
+                value: cw1155.value,
+                msg: None,
+            },
+            cw1155.address.clone(),
+            None,
+        ),
         _ => Err(StdError::generic_err("msg")),
     }
 }
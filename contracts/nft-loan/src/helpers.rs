@@ -1,8 +1,18 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_json_binary, Addr, BankMsg, Coin, Deps, StdResult, WasmMsg};
-use utils::types::CosmosMsg;
+use cosmwasm_std::{coins, to_json_binary, Addr, BankMsg, Coin, Deps, StdResult, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use cw721::{Cw721QueryMsg, NftInfoResponse};
+use serde::Deserialize;
+use utils::{
+    state::{into_cosmos_msg, AssetInfo},
+    types::CosmosMsg,
+};
 
-use crate::{error::ContractError, msg::ExecuteMsg, state::CONFIG};
+use crate::{
+    error::ContractError,
+    msg::ExecuteMsg,
+    state::{TraitConstraint, CONFIG},
+};
 
 /// NftLoanContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -48,3 +58,125 @@ pub fn assert_listing_fee(deps: Deps, funds: Vec<Coin>) -> Result<CosmosMsg, Con
 
     Ok(transfer_fee_msg)
 }
+
+/// Loan funds are sent as a single native coin
+pub fn asset_from_funds(funds: Vec<Coin>) -> Result<AssetInfo, ContractError> {
+    if funds.len() != 1 {
+        return Err(ContractError::MultipleCoins {});
+    }
+    Ok(AssetInfo::Coin(funds[0].clone()))
+}
+
+/// Amount of a fungible asset (native coin or CW20 token)
+pub fn fungible_amount(asset: &AssetInfo) -> Result<Uint128, ContractError> {
+    match asset {
+        AssetInfo::Coin(coin) => Ok(coin.amount),
+        AssetInfo::Cw20Coin(token) => Ok(token.amount),
+        _ => Err(ContractError::InvalidPrinciple {}),
+    }
+}
+
+/// Address of the collection a collateral token belongs to
+pub fn nft_collection(asset: &AssetInfo) -> Result<&str, ContractError> {
+    match asset {
+        AssetInfo::Cw721Coin(token) => Ok(&token.address),
+        #[cfg(feature = "sg")]
+        AssetInfo::Sg721Token(token) => Ok(&token.address),
+        AssetInfo::Cw1155Coin(token) => Ok(&token.address),
+        _ => Err(ContractError::WrongAssetDeposited {}),
+    }
+}
+
+/// On-chain metadata (as stored by Stargaze metadata collections), only the attributes are read
+#[derive(Deserialize, Default)]
+struct NftMetadata {
+    #[serde(default)]
+    attributes: Option<Vec<NftTrait>>,
+}
+
+#[derive(Deserialize)]
+struct NftTrait {
+    trait_type: String,
+    /// Trait values can be strings, numbers or booleans
+    value: serde_json::Value,
+}
+
+impl NftTrait {
+    fn matches(&self, constraint: &TraitConstraint) -> bool {
+        self.trait_type == constraint.trait_type
+            && match &self.value {
+                serde_json::Value::String(value) => value == &constraint.value,
+                value => value.to_string() == constraint.value,
+            }
+    }
+}
+
+/// Checks the token carries all the `traits` in its on-chain metadata
+/// Tokens without on-chain metadata (or CW1155 tokens) never match a non-empty list of traits
+pub fn assert_token_traits(
+    deps: Deps,
+    asset: &AssetInfo,
+    traits: &[TraitConstraint],
+) -> Result<(), ContractError> {
+    if traits.is_empty() {
+        return Ok(());
+    }
+    let (address, token_id) = match asset {
+        AssetInfo::Cw721Coin(token) => (&token.address, &token.token_id),
+        #[cfg(feature = "sg")]
+        AssetInfo::Sg721Token(token) => (&token.address, &token.token_id),
+        _ => return Err(ContractError::TraitsNotMatched {}),
+    };
+    let nft_info: NftInfoResponse<Option<NftMetadata>> = deps.querier.query_wasm_smart(
+        address,
+        &Cw721QueryMsg::NftInfo {
+            token_id: token_id.clone(),
+        },
+    )?;
+    let attributes = nft_info
+        .extension
+        .unwrap_or_default()
+        .attributes
+        .unwrap_or_default();
+
+    let matches = traits
+        .iter()
+        .all(|constraint| attributes.iter().any(|t| t.matches(constraint)));
+    if !matches {
+        return Err(ContractError::TraitsNotMatched {});
+    }
+    Ok(())
+}
+
+/// Checks two fungible assets have the same denom (or the same token address), regardless of the amount
+pub fn is_same_fungible(a: &AssetInfo, b: &AssetInfo) -> bool {
+    match (a, b) {
+        (AssetInfo::Coin(a), AssetInfo::Coin(b)) => a.denom == b.denom,
+        (AssetInfo::Cw20Coin(a), AssetInfo::Cw20Coin(b)) => a.address == b.address,
+        _ => false,
+    }
+}
+
+/// Creates the message sending `amount` of the given fungible asset to the recipient
+pub fn fungible_transfer_msg(
+    asset: &AssetInfo,
+    amount: Uint128,
+    recipient: &Addr,
+) -> Result<CosmosMsg, ContractError> {
+    match asset {
+        AssetInfo::Coin(coin) => Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), coin.denom.clone()),
+        }
+        .into()),
+        AssetInfo::Cw20Coin(token) => Ok(into_cosmos_msg(
+            Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            },
+            token.address.clone(),
+            None,
+        )?),
+        _ => Err(ContractError::InvalidPrinciple {}),
+    }
+}
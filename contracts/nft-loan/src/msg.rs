@@ -1,10 +1,12 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Decimal, StdError, StdResult};
+use cosmwasm_std::{Coin, Decimal, StdError, StdResult, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
 
 use utils::state::{is_valid_name, AssetInfo};
 
 use crate::state::{
     BorrowerInfo, CollateralInfo, CollectionOfferInfo, Config, LoanState, LoanTerms, OfferInfo,
+    TraitConstraint,
 };
 
 #[cw_serde]
@@ -54,6 +56,7 @@ pub enum ExecuteMsg {
         loan_id: u64,
     },
     /// Make an offer to deposited collaterals
+    /// Offers made on a started loan can be accepted by the borrower to refinance it
     MakeOffer {
         borrower: String,
         loan_id: u64,
@@ -81,26 +84,51 @@ pub enum ExecuteMsg {
     RepayBorrowedFunds {
         loan_id: u64,
     },
+    /// Pays back part of the loan, interest first. Sending everything that is owed ends the loan
+    PartialRepay {
+        loan_id: u64,
+    },
     WithdrawDefaultedLoan {
         borrower: String,
         loan_id: u64,
     },
+    /// Proposes to push the loan deadline back, the funds sent are paid to the lender as extra interest
+    /// The duration is expressed in the unit of the loan duration (blocks or seconds)
+    ProposeExtension {
+        loan_id: u64,
+        duration: u64,
+    },
+    /// Used by the active lender to approve the proposed extension
+    AcceptExtension {
+        borrower: String,
+        loan_id: u64,
+    },
+    /// Withdraws the proposed extension and the associated funds
+    CancelExtension {
+        loan_id: u64,
+    },
     ToggleLock {
         lock: bool,
     },
     /// Collection offers
+    /// The offer can be accepted with `token_count` (defaults to 1) tokens from any of the `collections`
+    /// When `traits` are set, every token must carry all of them in its on-chain metadata
     MakeCollectionOffer {
-        collection: String,
+        collections: Vec<String>,
+        token_count: Option<u32>,
         terms: LoanTerms,
         comment: Option<String>,
+        traits: Option<Vec<TraitConstraint>>,
     },
     WithdrawCollectionOffer {
         collection_offer_id: u64,
     },
     AcceptCollectionOffer {
         collection_offer_id: u64,
-        token: AssetInfo,
+        tokens: Vec<AssetInfo>,
     },
+    /// Used to lend or repay CW20 tokens, the loan principle must be a `Cw20Coin`
+    Receive(Cw20ReceiveMsg),
     /// Internal state
     SetOwner {
         owner: String,
@@ -116,6 +144,40 @@ pub enum ExecuteMsg {
     },
 }
 
+/// Messages that can be sent along CW20 tokens
+#[cw_serde]
+pub enum ReceiveMsg {
+    MakeOffer {
+        borrower: String,
+        loan_id: u64,
+        terms: LoanTerms,
+        comment: Option<String>,
+        on_behalf_of: Option<String>,
+    },
+    AcceptLoan {
+        borrower: String,
+        loan_id: u64,
+        comment: Option<String>,
+    },
+    MakeCollectionOffer {
+        collections: Vec<String>,
+        token_count: Option<u32>,
+        terms: LoanTerms,
+        comment: Option<String>,
+        traits: Option<Vec<TraitConstraint>>,
+    },
+    RepayBorrowedFunds {
+        loan_id: u64,
+    },
+    PartialRepay {
+        loan_id: u64,
+    },
+    ProposeExtension {
+        loan_id: u64,
+        duration: u64,
+    },
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
@@ -132,6 +194,15 @@ pub enum QueryMsg {
         // filters: Option<QueryFilters>,
     },
 
+    /// Amount owed by the borrower of a started loan at the given height and time (defaults to the current block)
+    #[returns(AmountOwedResponse)]
+    AmountOwed {
+        borrower: String,
+        loan_id: u64,
+        height: Option<u64>,
+        time: Option<Timestamp>,
+    },
+
     #[returns(MultipleCollateralsResponse)]
     Collaterals {
         borrower: String,
@@ -180,6 +251,14 @@ pub struct CollateralResponse {
     pub collateral: CollateralInfo,
 }
 
+// amount left to repay on a loan
+#[cw_serde]
+pub struct AmountOwedResponse {
+    pub principle: Uint128,
+    pub interest: Uint128,
+    pub total: Uint128,
+}
+
 // array of loan information
 #[cw_serde]
 pub struct MultipleCollateralsResponse {
@@ -1,6 +1,8 @@
 use cosmwasm_std::{
-    to_json_binary, Addr, Deps, Env, Order, QueryRequest, StdError, StdResult, WasmQuery,
+    to_json_binary, Addr, BlockInfo, Deps, Env, Order, QueryRequest, StdError, StdResult,
+    Timestamp, Uint128, WasmQuery,
 };
+use cw1155::{BalanceResponse, Cw1155QueryMsg, IsApprovedForAllResponse};
 use cw721::{ApprovalResponse, Cw721QueryMsg, Expiration, OwnerOfResponse};
 use cw_storage_plus::Bound;
 
@@ -11,6 +13,7 @@ use crate::{
     error::ContractError,
     lender_offer::lender_offers,
     msg::{
+        AmountOwedResponse,
         CollateralResponse,
         MultipleCollateralsAllResponse,
         MultipleCollateralsResponse,
@@ -19,8 +22,8 @@ use crate::{
         // QueryFilters,
     },
     state::{
-        get_actual_state, get_offer, BorrowerInfo, CollateralInfo, Config, BORROWER_INFO,
-        COLLATERAL_INFO, CONFIG,
+        get_active_loan, get_actual_state, get_offer, BorrowerInfo, CollateralInfo, Config,
+        LoanState, BORROWER_INFO, COLLATERAL_INFO, CONFIG,
     },
 };
 
@@ -126,6 +129,51 @@ pub fn is_approved_sg721(
     Ok(())
 }
 
+// confirm ownership, the borrower needs to hold at least `value` tokens
+pub fn is_cw1155_owner(
+    deps: Deps,
+    sender: Addr,
+    address: String,
+    token_id: String,
+    value: Uint128,
+) -> Result<(), ContractError> {
+    let balance_response: BalanceResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: address,
+            msg: to_json_binary(&Cw1155QueryMsg::Balance {
+                owner: sender.to_string(),
+                token_id,
+            })?,
+        }))?;
+
+    if balance_response.balance < value {
+        return Err(ContractError::SenderNotOwner {});
+    }
+    Ok(())
+}
+
+// confirm token approval, cw1155 tokens can only be approved for all
+pub fn is_approved_cw1155(
+    deps: Deps,
+    env: Env,
+    sender: Addr,
+    address: String,
+) -> Result<(), ContractError> {
+    let approval_response: IsApprovedForAllResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: address,
+            msg: to_json_binary(&Cw1155QueryMsg::IsApprovedForAll {
+                owner: sender.to_string(),
+                operator: env.contract.address.to_string(),
+            })?,
+        }))?;
+
+    if !approval_response.approved {
+        return Err(ContractError::TokenApprovalIssue {});
+    }
+    Ok(())
+}
+
 pub fn query_borrower_info(deps: Deps, borrower: String) -> StdResult<BorrowerInfo> {
     let borrower = deps.api.addr_validate(&borrower)?;
     BORROWER_INFO
@@ -144,6 +192,43 @@ pub fn query_collateral_info(
     COLLATERAL_INFO.load(deps.storage, (borrower, loan_id))
 }
 
+pub fn query_amount_owed(
+    deps: Deps,
+    env: Env,
+    borrower: String,
+    loan_id: u64,
+    height: Option<u64>,
+    time: Option<Timestamp>,
+) -> StdResult<AmountOwedResponse> {
+    let borrower = deps.api.addr_validate(&borrower)?;
+    let collateral = COLLATERAL_INFO.load(deps.storage, (borrower, loan_id))?;
+    if collateral.state != LoanState::Started {
+        return Err(StdError::generic_err(
+            ContractError::WrongLoanState {
+                state: collateral.state,
+            }
+            .to_string(),
+        ));
+    }
+    let offer_info = get_active_loan(deps.storage, &collateral)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let block = BlockInfo {
+        height: height.unwrap_or(env.block.height),
+        time: time.unwrap_or(env.block.time),
+        chain_id: env.block.chain_id,
+    };
+    let (principle, interest) = collateral
+        .amount_owed(&offer_info.terms, &block)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(AmountOwedResponse {
+        principle,
+        interest,
+        total: principle + interest,
+    })
+}
+
 pub fn query_collaterals(
     deps: Deps,
     borrower: String,
@@ -1,9 +1,15 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Decimal, Env, StdError, StdResult, Storage, Timestamp, Uint128};
+use cosmwasm_std::{
+    Addr, Api, BlockInfo, Coin, Decimal, Env, Order, StdError, StdResult, Storage, Timestamp,
+    Uint128, Uint256,
+};
 use cw_storage_plus::{Item, Map};
 use utils::state::{AssetInfo, Locks};
 
-use crate::{error::ContractError, lender_offer::lender_offers};
+use crate::{
+    collection_offer::collection_offers, error::ContractError, helpers::fungible_amount,
+    lender_offer::lender_offers,
+};
 
 pub const OLD_CONFIG: Item<OldConfig> = Item::new("config");
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -66,6 +72,32 @@ pub struct CollateralInfo {
     pub start_block: Option<u64>,        // block which offer has been accepter
     pub comment: Option<String>,         // comment made
     pub loan_preview: Option<AssetInfo>, // The preview can only be a SG721 or a CW721 token.
+    #[serde(default)]
+    pub start_time: Option<Timestamp>, // time at which the offer has been accepted
+    #[serde(default)]
+    pub principle_repaid: Uint128, // principle already paid back with partial repayments
+    #[serde(default)]
+    pub interest_repaid: Uint128, // interest already paid back with partial repayments
+    #[serde(default)]
+    pub interest_checkpoint: Uint128, // pro-rata interest accrued until the last partial repayment
+    #[serde(default)]
+    pub checkpoint_elapsed: u64, // time elapsed in the loan at the last partial repayment
+    #[serde(default)]
+    pub pending_extension: Option<LoanExtension>, // extension proposed by the borrower
+    #[serde(default)]
+    pub extensions: Vec<LoanExtension>, // extensions accepted by the lenders
+}
+
+#[cw_serde]
+pub struct LoanExtension {
+    /// Offer (and lender) the extension applies to
+    pub global_offer_id: String,
+    /// Expressed in the unit of the loan duration (blocks or seconds)
+    pub duration: u64,
+    /// Additional interest paid to the lender, in the principle currency
+    pub interest: AssetInfo,
+    pub proposed_block: u64,
+    pub accepted_block: Option<u64>,
 }
 
 impl Default for CollateralInfo {
@@ -80,7 +112,86 @@ impl Default for CollateralInfo {
             active_offer: None,
             start_block: None,
             loan_preview: None,
+            start_time: None,
+            principle_repaid: Uint128::zero(),
+            interest_repaid: Uint128::zero(),
+            interest_checkpoint: Uint128::zero(),
+            checkpoint_elapsed: 0,
+            pending_extension: None,
+            extensions: vec![],
+        }
+    }
+}
+
+impl CollateralInfo {
+    /// Time elapsed since the start of the loan, in the unit of the loan duration
+    pub fn elapsed(&self, duration: &LoanDuration, block: &BlockInfo) -> u64 {
+        match duration {
+            LoanDuration::Blocks(_) => block
+                .height
+                .saturating_sub(self.start_block.unwrap_or_default()),
+            LoanDuration::Seconds(_) => block.time.seconds().saturating_sub(
+                self.start_time
+                    .map(|time| time.seconds())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// Outstanding principle and interest owed to the lender at the given block
+    pub fn amount_owed(
+        &self,
+        terms: &LoanTerms,
+        block: &BlockInfo,
+    ) -> Result<(Uint128, Uint128), ContractError> {
+        let principle = terms
+            .principle_amount()?
+            .saturating_sub(self.principle_repaid);
+        let interest = self
+            .accrued_interest(terms, block)?
+            .saturating_sub(self.interest_repaid);
+        Ok((principle, interest))
+    }
+
+    /// Interest accrued since the start of the loan.
+    /// Pro-rata interest only accrues on the principle that wasn't paid back yet
+    pub fn accrued_interest(
+        &self,
+        terms: &LoanTerms,
+        block: &BlockInfo,
+    ) -> Result<Uint128, ContractError> {
+        if !terms.pro_rata_interest || terms.duration.value() == 0 {
+            return Ok(terms.interest);
         }
+        let outstanding = terms
+            .principle_amount()?
+            .saturating_sub(self.principle_repaid);
+        Ok(self.interest_checkpoint
+            + terms.accrued_interest(
+                outstanding,
+                self.checkpoint_elapsed,
+                self.elapsed(&terms.duration, block),
+            )?)
+    }
+
+    /// Saves the interest accrued so far, before the outstanding principle changes
+    pub fn checkpoint_interest(
+        &mut self,
+        terms: &LoanTerms,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        self.interest_checkpoint = self.accrued_interest(terms, block)?;
+        self.checkpoint_elapsed = self.elapsed(&terms.duration, block);
+        Ok(())
+    }
+
+    /// Duration the active loan was extended by, in the unit of the loan duration
+    pub fn extension_duration(&self) -> u64 {
+        self.extensions
+            .iter()
+            .filter(|extension| Some(&extension.global_offer_id) == self.active_offer.as_ref())
+            .map(|extension| extension.duration)
+            .sum()
     }
 }
 
@@ -145,24 +256,102 @@ pub struct OfferInfo {
     pub terms: LoanTerms,
     pub state: OfferState,
     pub list_date: Timestamp,
-    pub deposited_funds: Option<Coin>,
+    pub deposited_funds: Option<AssetInfo>,
     pub comment: Option<String>,
+    /// Offers made on a started loan are used to refinance it.
+    /// The new principle pays back the current lender when the borrower accepts the offer
+    #[serde(default)]
+    pub refinance: bool,
 }
 
 #[cw_serde]
 pub struct CollectionOfferInfo {
     pub lender: Addr,
-    pub collection: Addr,
+    /// The offer can be accepted with tokens from any of those collections
+    pub collections: Vec<Addr>,
+    /// Number of tokens the borrower has to deposit to accept the offer
+    pub token_count: u32,
     pub collection_offer_id: u64,
     pub terms: LoanTerms,
     pub comment: Option<String>,
+    /// Traits every token has to carry in its on-chain metadata to be accepted
+    #[serde(default)]
+    pub traits: Vec<TraitConstraint>,
+}
+
+/// Requires the token metadata to contain an attribute with this `trait_type` and `value`.
+/// Number and boolean trait values are compared with their JSON representation (e.g. `"5"` or `"true"`)
+#[cw_serde]
+pub struct TraitConstraint {
+    pub trait_type: String,
+    pub value: String,
 }
 
 #[cw_serde]
 pub struct LoanTerms {
-    pub principle: Coin,
+    /// The lent funds, either a native coin or a CW20 token
+    pub principle: AssetInfo,
     pub interest: Uint128,
-    pub duration_in_blocks: u64,
+    pub duration: LoanDuration,
+    /// If true, the interest accrues linearly until the end of the loan.
+    /// Otherwise, the whole interest is due whenever the loan is repaid
+    #[serde(default)]
+    pub pro_rata_interest: bool,
+}
+
+impl LoanTerms {
+    pub fn validate(&self, api: &dyn Api) -> Result<(), ContractError> {
+        match &self.principle {
+            AssetInfo::Coin(_) => Ok(()),
+            AssetInfo::Cw20Coin(token) => {
+                api.addr_validate(&token.address)?;
+                Ok(())
+            }
+            _ => Err(ContractError::InvalidPrinciple {}),
+        }
+    }
+
+    /// Amount of funds lent with those terms
+    pub fn principle_amount(&self) -> Result<Uint128, ContractError> {
+        fungible_amount(&self.principle)
+    }
+
+    /// Pro-rata interest accrued on `outstanding` principle between `from` and `to` blocks or seconds.
+    /// No interest accrues after the end of the loan duration
+    pub fn accrued_interest(
+        &self,
+        outstanding: Uint128,
+        from: u64,
+        to: u64,
+    ) -> Result<Uint128, ContractError> {
+        let duration = self.duration.value();
+        let principle = self.principle_amount()?;
+        if duration == 0 || principle.is_zero() {
+            return Ok(Uint128::zero());
+        }
+        let period = to.min(duration).saturating_sub(from.min(duration));
+        let interest = self.interest.full_mul(outstanding) * Uint256::from(period)
+            / (Uint256::from(principle) * Uint256::from(duration));
+        Ok(Uint128::try_from(interest).map_err(StdError::from)?)
+    }
+}
+
+/// Loans can last for a number of blocks or for a fixed amount of time
+#[cw_serde]
+#[derive(Copy)]
+pub enum LoanDuration {
+    Blocks(u64),
+    Seconds(u64),
+}
+
+impl LoanDuration {
+    /// Number of blocks or seconds the loan lasts
+    pub fn value(&self) -> u64 {
+        match self {
+            LoanDuration::Blocks(blocks) => *blocks,
+            LoanDuration::Seconds(seconds) => *seconds,
+        }
+    }
 }
 
 pub fn is_loan_modifiable(collateral: &CollateralInfo) -> Result<(), ContractError> {
@@ -185,11 +374,23 @@ pub fn is_loan_counterable(collateral: &CollateralInfo) -> Result<(), ContractEr
     }
 }
 
+pub fn is_loan_refinanceable(collateral: &CollateralInfo) -> Result<(), ContractError> {
+    match collateral.state {
+        LoanState::Started => Ok(()),
+        _ => Err(ContractError::NotCounterable {}),
+    }
+}
+
 pub fn is_offer_refusable(
     collateral: &CollateralInfo,
     offer_info: &OfferInfo,
 ) -> Result<(), ContractError> {
-    is_loan_counterable(collateral).map_err(|_| ContractError::NotRefusable {})?;
+    if offer_info.refinance {
+        is_loan_refinanceable(collateral)
+    } else {
+        is_loan_counterable(collateral)
+    }
+    .map_err(|_| ContractError::NotRefusable {})?;
     match offer_info.state {
         OfferState::Published => Ok(()),
         _ => Err(ContractError::NotRefusable {}),
@@ -223,7 +424,9 @@ pub fn is_loan_defaulted(
     let offer: OfferInfo = get_active_loan(storage, collateral)?;
     match &collateral.state {
         LoanState::Started => {
-            if collateral.start_block.unwrap() + offer.terms.duration_in_blocks < env.block.height {
+            if collateral.elapsed(&offer.terms.duration, &env.block)
+                > offer.terms.duration.value() + collateral.extension_duration()
+            {
                 Ok(())
             } else {
                 Err(ContractError::WrongLoanState {
@@ -316,9 +519,15 @@ pub fn get_actual_state(offer_info: &OfferInfo, storage: &dyn Storage) -> StdRes
 
     // We check the status of the offer.
     // A refused offer isn't marked as such but depends on the overlying collateral info state
+    // Refinance offers stay valid as long as the loan is running
+    let expected_state = if offer_info.refinance {
+        LoanState::Started
+    } else {
+        LoanState::Published
+    };
     Ok(match &offer_info.state {
         OfferState::Published => {
-            if collateral_info.state != LoanState::Published {
+            if collateral_info.state != expected_state {
                 OfferState::Refused
             } else {
                 OfferState::Published
@@ -327,3 +536,143 @@ pub fn get_actual_state(offer_info: &OfferInfo, storage: &dyn Storage) -> StdRes
         _ => offer_info.state.clone(),
     })
 }
+
+// Loan terms used to only accept native coins as principle.
+// Those structures are used to migrate the stored terms to the `AssetInfo` format
+pub const OLD_COLLATERAL_INFO: Map<(Addr, u64), OldCollateralInfo> = Map::new("collateral_info");
+pub const OLD_LENDER_OFFERS: Map<&str, OldOfferInfo> = Map::new("lender_offers");
+pub const OLD_COLLECTION_OFFERS: Map<&str, OldCollectionOfferInfo> = Map::new("collection_offers");
+
+#[cw_serde]
+pub struct OldLoanTerms {
+    pub principle: Coin,
+    pub interest: Uint128,
+    pub duration_in_blocks: u64,
+}
+
+impl From<OldLoanTerms> for LoanTerms {
+    fn from(terms: OldLoanTerms) -> Self {
+        LoanTerms {
+            principle: AssetInfo::Coin(terms.principle),
+            interest: terms.interest,
+            duration: LoanDuration::Blocks(terms.duration_in_blocks),
+            pro_rata_interest: false,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct OldCollateralInfo {
+    pub terms: Option<OldLoanTerms>,
+    pub associated_assets: Vec<AssetInfo>,
+    pub list_date: Timestamp,
+    pub state: LoanState,
+    pub offer_amount: u64,
+    pub active_offer: Option<String>,
+    pub start_block: Option<u64>,
+    pub comment: Option<String>,
+    pub loan_preview: Option<AssetInfo>,
+}
+
+#[cw_serde]
+pub struct OldOfferInfo {
+    pub lender: Addr,
+    pub borrower: Addr,
+    pub loan_id: u64,
+    pub offer_id: u64,
+    pub terms: OldLoanTerms,
+    pub state: OfferState,
+    pub list_date: Timestamp,
+    pub deposited_funds: Option<Coin>,
+    pub comment: Option<String>,
+}
+
+#[cw_serde]
+pub struct OldCollectionOfferInfo {
+    pub lender: Addr,
+    pub collection: Addr,
+    pub collection_offer_id: u64,
+    pub terms: OldLoanTerms,
+    pub comment: Option<String>,
+}
+
+/// Rewrites all the loan terms saved with a native principle.
+/// Entries that can't be parsed with the old format are already migrated and are skipped.
+/// The lender offer indexes only depend on unchanged fields, so the primary map is written directly.
+/// Collection offers are now indexed by all their collections, so they go through the indexed map.
+pub fn migrate_loan_terms(storage: &mut dyn Storage) -> StdResult<()> {
+    let collaterals = OLD_COLLATERAL_INFO
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    for (key, old) in collaterals {
+        COLLATERAL_INFO.save(
+            storage,
+            key,
+            &CollateralInfo {
+                terms: old.terms.map(Into::into),
+                associated_assets: old.associated_assets,
+                list_date: old.list_date,
+                state: old.state,
+                offer_amount: old.offer_amount,
+                active_offer: old.active_offer,
+                start_block: old.start_block,
+                comment: old.comment,
+                loan_preview: old.loan_preview,
+                start_time: None,
+                principle_repaid: Uint128::zero(),
+                interest_repaid: Uint128::zero(),
+                interest_checkpoint: Uint128::zero(),
+                checkpoint_elapsed: 0,
+                pending_extension: None,
+                extensions: vec![],
+            },
+        )?;
+    }
+
+    let offers = OLD_LENDER_OFFERS
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    for (key, old) in offers {
+        Map::<&str, OfferInfo>::new("lender_offers").save(
+            storage,
+            &key,
+            &OfferInfo {
+                lender: old.lender,
+                borrower: old.borrower,
+                loan_id: old.loan_id,
+                offer_id: old.offer_id,
+                terms: old.terms.into(),
+                state: old.state,
+                list_date: old.list_date,
+                deposited_funds: old.deposited_funds.map(AssetInfo::Coin),
+                comment: old.comment,
+                refinance: false,
+            },
+        )?;
+    }
+
+    let collection_offers = OLD_COLLECTION_OFFERS
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    for (key, old) in collection_offers {
+        collection_offers().replace(
+            storage,
+            &key,
+            Some(&CollectionOfferInfo {
+                lender: old.lender,
+                collections: vec![old.collection],
+                token_count: 1,
+                collection_offer_id: old.collection_offer_id,
+                terms: old.terms.into(),
+                comment: old.comment,
+                traits: vec![],
+            }),
+            None,
+        )?;
+    }
+
+    Ok(())
+}
@@ -3,18 +3,22 @@ use cosmwasm_std::{
     entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
     StdResult,
 };
-use cosmwasm_std::{BankMsg, Coin};
+use cosmwasm_std::{from_json, BankMsg, Coin};
 use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
 use utils::payment::assert_payment;
-use utils::state::AssetInfo;
+use utils::state::{AssetInfo, Cw20Coin};
 
 use crate::error::ContractError;
 
 use crate::state::{
-    is_owner, load_counter_trade, load_trade, CONTRACT_INFO, COUNTER_TRADE_INFO, TRADE_INFO,
+    is_owner, load_counter_trade, load_trade, migrate_tokens_wanted, CONTRACT_INFO,
+    COUNTER_TRADE_INFO, TRADE_INFO,
+};
+use crate::trade_steps::direct_buy::{_direct_buy, direct_buy};
+use p2p_trading_export::msg::{
+    AddAssetAction, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg,
 };
-use crate::trade_steps::direct_buy::direct_buy;
-use p2p_trading_export::msg::{AddAssetAction, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use p2p_trading_export::state::{ContractInfo, TradeState};
 
 use crate::counter_trade::{
@@ -85,7 +89,14 @@ pub fn execute(
             whitelisted_users,
             comment,
         } => create_trade(deps, env, info, whitelisted_users, comment),
-        ExecuteMsg::AddAsset { action, asset } => add_asset(deps, env, info, action, asset),
+        ExecuteMsg::AddAsset { action, asset } => {
+            // CW20 tokens need to be sent along the message, using the `Receive` entry point
+            if let AssetInfo::Cw20Coin(_) = asset {
+                return Err(ContractError::WrongTokenType {});
+            }
+            add_asset(deps, env, info, action, asset)
+        }
+        ExecuteMsg::Receive(wrapper) => receive(deps, env, info, wrapper),
         ExecuteMsg::RemoveAssets {
             trade_id,
             counter_id,
@@ -192,8 +203,9 @@ pub fn execute(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    // No state migrations performed, just returned a Response
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // The tokens wanted now accept CW20 tokens
+    migrate_tokens_wanted(deps.storage)?;
     Ok(Response::default())
 }
 
@@ -333,6 +345,35 @@ pub fn add_asset(
     }
 }
 
+/// Handler for CW20 tokens sent to the contract
+/// The sender of the tokens is considered as the sender of the message
+pub fn receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let asset = AssetInfo::Cw20Coin(Cw20Coin {
+        address: info.sender.to_string(),
+        amount: wrapper.amount,
+    });
+
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::AddAsset { action } => {
+            let info = MessageInfo {
+                sender,
+                funds: vec![],
+            };
+            add_asset(deps, env, info, action, asset)
+        }
+        ReceiveMsg::DirectBuy {
+            trade_id,
+            on_behalf_of,
+        } => _direct_buy(deps, env, sender, vec![asset], trade_id, on_behalf_of),
+    }
+}
+
 /// Remove some assets from a trade when creating it.
 pub fn withdraw_assets_while_creating(
     deps: DepsMut,
@@ -355,7 +396,7 @@ pub fn withdraw_assets_while_creating(
 /// The counter_trader will withdraw assets from the trade
 pub fn withdraw_successful_trade(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     trade_id: u64,
 ) -> Result<Response, ContractError> {
@@ -381,6 +422,7 @@ pub fn withdraw_successful_trade(
     let royalties = gather_royalties(deps.as_ref(), &trade_info)?;
     let trader_res = check_and_create_withdraw_messages(
         deps.as_ref(),
+        &env,
         &trade_info.owner,
         &counter_info,
         Some(royalties),
@@ -391,6 +433,7 @@ pub fn withdraw_successful_trade(
     let royalties = gather_royalties(deps.as_ref(), &counter_info)?;
     let counter_trader_res = check_and_create_withdraw_messages(
         deps.as_ref(),
+        &env,
         &counter_info.owner,
         &trade_info,
         Some(royalties),
@@ -717,6 +760,151 @@ pub mod tests {
         )
     }
 
+    fn receive_cw20_helper(
+        deps: DepsMut,
+        sender: &str,
+        token: &str,
+        amount: u128,
+        msg: ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let info = mock_info(token, &[]);
+        let env = mock_env();
+
+        execute(
+            deps,
+            env,
+            info,
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: sender.to_string(),
+                amount: amount.into(),
+                msg: cosmwasm_std::to_json_binary(&msg).unwrap(),
+            }),
+        )
+    }
+
+    #[test]
+    fn cw20_add_asset_and_withdraw() {
+        let mut deps = mock_dependencies();
+        init_helper(deps.as_mut());
+        create_trade_helper(deps.as_mut(), "creator");
+
+        // CW20 tokens can't be added without being sent
+        let err = add_asset_to_trade_helper(
+            deps.as_mut(),
+            "creator",
+            0,
+            AssetInfo::cw20(100u128, "token"),
+            &[],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::WrongTokenType {});
+
+        let msg = ReceiveMsg::AddAsset {
+            action: AddAssetAction::ToTrade { trade_id: 0 },
+        };
+        receive_cw20_helper(deps.as_mut(), "creator", "token", 100, msg.clone()).unwrap();
+        receive_cw20_helper(deps.as_mut(), "creator", "token", 50, msg).unwrap();
+
+        let trade_info = load_trade(&deps.storage, 0).unwrap();
+        assert_eq!(
+            trade_info.associated_assets,
+            vec![AssetInfo::cw20(150u128, "token")]
+        );
+
+        let res = withdraw_cancelled_trade_helper(deps.as_mut(), "creator", 0).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            cosmwasm_std::CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: cosmwasm_std::to_json_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                    recipient: "creator".to_string(),
+                    amount: 150u128.into(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn cw20_tokens_wanted() {
+        let mut deps = mock_dependencies();
+        init_helper(deps.as_mut());
+        create_trade_helper(deps.as_mut(), "creator");
+        add_asset_to_trade_helper(
+            deps.as_mut(),
+            "creator",
+            0,
+            AssetInfo::Cw721Coin(utils::state::Cw721Coin {
+                address: "nft".to_string(),
+                token_id: "58".to_string(),
+            }),
+            &[],
+        )
+        .unwrap();
+
+        // Only fungible tokens can be wanted
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::AddTokensWanted {
+                trade_id: Some(0),
+                tokens_wanted: vec![AssetInfo::Cw721Coin(utils::state::Cw721Coin {
+                    address: "nft".to_string(),
+                    token_id: "59".to_string(),
+                })],
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::WrongTokenType {});
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::AddTokensWanted {
+                trade_id: Some(0),
+                tokens_wanted: vec![AssetInfo::cw20(100u128, "token")],
+            },
+        )
+        .unwrap();
+        confirm_trade_helper(deps.as_mut(), "creator", 0).unwrap();
+
+        // Not enough tokens sent
+        let err = receive_cw20_helper(
+            deps.as_mut(),
+            "buyer",
+            "token",
+            60,
+            ReceiveMsg::DirectBuy {
+                trade_id: 0,
+                on_behalf_of: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotEnoughPaiement {
+                missing_funds: vec![AssetInfo::cw20(40u128, "token")]
+            }
+        );
+
+        receive_cw20_helper(
+            deps.as_mut(),
+            "buyer",
+            "token",
+            100,
+            ReceiveMsg::DirectBuy {
+                trade_id: 0,
+                on_behalf_of: None,
+            },
+        )
+        .unwrap();
+        let trade_info = load_trade(&deps.storage, 0).unwrap();
+        assert_eq!(trade_info.state, TradeState::Accepted);
+    }
+
     // pub mod trade_tests {
     //     use super::*;
     //     use crate::query::{query_counter_trades, TradeResponse};
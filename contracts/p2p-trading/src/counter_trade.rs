@@ -6,8 +6,9 @@ use utils::state::AssetInfo;
 use crate::error::ContractError;
 use crate::messages::set_comment;
 use crate::state::{
-    add_cw721_coin, add_funds, add_sg721_coin, can_suggest_counter_trade, is_counter_trader,
-    load_trade, COUNTER_TRADE_INFO, LAST_USER_COUNTER_TRADE, TRADE_INFO,
+    add_cw1155_coin, add_cw20_coin, add_cw721_coin, add_funds, add_sg721_coin,
+    can_suggest_counter_trade, is_counter_trader, load_trade, COUNTER_TRADE_INFO,
+    LAST_USER_COUNTER_TRADE, TRADE_INFO,
 };
 use crate::trade::{
     _are_assets_in_trade, _create_receive_asset_messages, _create_withdraw_messages_unsafe,
@@ -149,23 +150,35 @@ pub fn add_asset_to_counter_trade(
             (trade_id, counter_id),
             add_sg721_coin(token.address.clone(), token.token_id),
         ),
+        AssetInfo::Cw20Coin(token) => COUNTER_TRADE_INFO.update(
+            deps.storage,
+            (trade_id, counter_id),
+            add_cw20_coin(token.address.clone(), token.amount),
+        ),
+        AssetInfo::Cw1155Coin(token) => COUNTER_TRADE_INFO.update(
+            deps.storage,
+            (trade_id, counter_id),
+            add_cw1155_coin(token.address.clone(), token.token_id, token.value),
+        ),
     }?;
 
     // We load the trade_info for events
     let trade_info = load_trade(deps.storage, trade_id)?;
 
     // Now we need to transfer the token
-    Ok(_create_receive_asset_messages(env, info.clone(), asset)?
-        .add_attribute("trade_id", trade_id.to_string())
-        .add_attribute("counter_id", counter_id.to_string())
-        .add_attribute("trader", trade_info.owner)
-        .add_attribute("counter_trader", info.sender))
+    Ok(
+        _create_receive_asset_messages(deps.as_ref(), env, info.clone(), asset)?
+            .add_attribute("trade_id", trade_id.to_string())
+            .add_attribute("counter_id", counter_id.to_string())
+            .add_attribute("trader", trade_info.owner)
+            .add_attribute("counter_trader", info.sender),
+    )
 }
 
 /// Allows to withdraw assets while creating a counter_trade, Refer to the `trade.rs`file for more information (similar mechanism)
 pub fn withdraw_counter_trade_assets_while_creating(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     trade_id: u64,
     counter_id: u64,
@@ -194,6 +207,11 @@ pub fn withdraw_counter_trade_assets_while_creating(
                     counter_info.additional_info.trade_preview = None;
                 }
             }
+            AssetInfo::Cw1155Coin(_) => {
+                if assets.iter().any(|r| r.1.overlaps(&preview)) {
+                    counter_info.additional_info.trade_preview = None;
+                }
+            }
             _ => {}
         }
     }
@@ -202,6 +220,7 @@ pub fn withdraw_counter_trade_assets_while_creating(
 
     let res = _create_withdraw_messages_unsafe(
         deps.as_ref(),
+        &env,
         &info.sender,
         &assets.iter().map(|x| x.1.clone()).collect(),
         None,
@@ -308,8 +327,14 @@ pub fn withdraw_all_from_counter(
     }
 
     // We create withdraw messages to send the funds back to the counter trader
-    let res =
-        check_and_create_withdraw_messages(deps.as_ref(), &info.sender, &counter_info, None, None)?;
+    let res = check_and_create_withdraw_messages(
+        deps.as_ref(),
+        &env,
+        &info.sender,
+        &counter_info,
+        None,
+        None,
+    )?;
     counter_info.assets_withdrawn = true;
     COUNTER_TRADE_INFO.save(deps.storage, (trade_id, counter_id), &counter_info)?;
 
@@ -1,6 +1,7 @@
-use cosmwasm_std::{Coin, OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError};
 use p2p_trading_export::state::TradeState;
 use thiserror::Error;
+use utils::state::AssetInfo;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -106,8 +107,14 @@ pub enum ContractError {
     AssetNotInTrade {},
 
     #[error("You need to pay the exact amount to be able to buy this trade proposal. Missing funds: {missing_funds:?}")]
-    NotEnoughPaiement { missing_funds: Vec<Coin> },
+    NotEnoughPaiement { missing_funds: Vec<AssetInfo> },
 
     #[error("You can't buy this trade directly, the owner doesn't authorize it")]
     NotBuyableDirectly {},
+
+    #[error("The Message sender has to own the tokens they deposit")]
+    SenderNotOwner {},
+
+    #[error("There is an issue with the approval of the tokens. please approve this contract to interact with your tokens & try again!")]
+    TokenApprovalIssue {},
 }
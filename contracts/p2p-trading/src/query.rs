@@ -158,6 +158,8 @@ pub fn trade_filter(
                     AssetInfo::Coin(x) => x.denom == token.as_ref(),
                     AssetInfo::Cw721Coin(x) => x.address == token.as_ref(),
                     AssetInfo::Sg721Token(x) => x.address == token.as_ref(),
+                    AssetInfo::Cw20Coin(x) => x.address == token.as_ref(),
+                    AssetInfo::Cw1155Coin(x) => x.address == token.as_ref(),
                 }),
             None => true,
         } && match &filters.assets_withdrawn {
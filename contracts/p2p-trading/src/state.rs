@@ -1,10 +1,15 @@
 use cw_storage_plus::{Item, Map};
 
-use cosmwasm_std::{Addr, Coin, StdError, StdResult, Storage, Uint128};
-use utils::state::{AssetInfo, Cw721Coin, Sg721Token};
+use cosmwasm_std::{Addr, Coin, Coins, Order, StdError, StdResult, Storage, Timestamp, Uint128};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
+use utils::state::{AssetInfo, Cw20Coin, Cw721Coin, Sg721Token};
 
 use crate::error::ContractError;
-use p2p_trading_export::state::{ContractInfo, TradeInfo, TradeState};
+use p2p_trading_export::state::{
+    AdditionalTradeInfo, Comment, ContractInfo, CounterTradeInfo, TradeInfo, TradeState,
+};
 
 pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
 
@@ -54,6 +59,66 @@ pub fn add_funds(
     }
 }
 
+pub fn add_cw20_coin(
+    address: String,
+    amount: Uint128,
+) -> impl FnOnce(Option<TradeInfo>) -> Result<TradeInfo, ContractError> {
+    move |d: Option<TradeInfo>| -> Result<TradeInfo, ContractError> {
+        match d {
+            Some(mut trade) => {
+                let existing_token = trade.associated_assets.iter_mut().find(|c| match c {
+                    AssetInfo::Cw20Coin(x) => x.address == address,
+                    _ => false,
+                });
+
+                if let Some(AssetInfo::Cw20Coin(existing_token)) = existing_token {
+                    existing_token.amount += amount;
+                } else {
+                    trade
+                        .associated_assets
+                        .push(AssetInfo::Cw20Coin(Cw20Coin { address, amount }));
+                }
+                Ok(trade)
+            }
+            //TARPAULIN : Unreachable in current code state
+            None => Err(ContractError::NotFoundInTradeInfo {}),
+        }
+    }
+}
+
+pub fn add_cw1155_coin(
+    address: String,
+    token_id: String,
+    value: Uint128,
+) -> impl FnOnce(Option<TradeInfo>) -> Result<TradeInfo, ContractError> {
+    move |d: Option<TradeInfo>| -> Result<TradeInfo, ContractError> {
+        match d {
+            Some(mut trade) => {
+                // For Cw1155 we only match the address and the token_id
+                let existing_token = trade.associated_assets.iter_mut().find(|c| match c {
+                    AssetInfo::Cw1155Coin(x) => x.address == address && x.token_id == token_id,
+                    _ => false,
+                });
+
+                if let Some(AssetInfo::Cw1155Coin(existing_token)) = existing_token {
+                    existing_token.value += value;
+                } else {
+                    trade
+                        .associated_assets
+                        .push(AssetInfo::Cw1155Coin(Cw1155Coin {
+                            address,
+                            token_id,
+                            value,
+                        }));
+                }
+                Ok(trade)
+            }
+            //TARPAULIN : Unreachable in current code state
+            None => Err(ContractError::NotFoundInTradeInfo {}),
+        }
+    }
+}
+
 pub fn add_cw721_coin(
     address: String,
     token_id: String,
@@ -188,3 +253,158 @@ pub fn can_suggest_counter_trade(
         Err(ContractError::NotFoundInTradeInfo {})
     }
 }
+
+/// Fungible tokens wanted in exchange of a trade.
+/// Native coins and CW20 tokens are aggregated by denom and by token address
+#[derive(Default)]
+pub struct TokensWanted {
+    coins: Coins,
+    cw20: BTreeMap<String, Uint128>,
+}
+
+impl TokensWanted {
+    pub fn add(&mut self, token: AssetInfo) -> Result<(), ContractError> {
+        match token {
+            AssetInfo::Coin(coin) => self.coins.add(coin)?,
+            AssetInfo::Cw20Coin(token) => {
+                if !token.amount.is_zero() {
+                    *self.cw20.entry(token.address).or_default() += token.amount;
+                }
+            }
+            _ => return Err(ContractError::WrongTokenType {}),
+        }
+        Ok(())
+    }
+
+    pub fn sub(&mut self, token: AssetInfo) -> Result<(), ContractError> {
+        match token {
+            AssetInfo::Coin(coin) => self.coins.sub(coin)?,
+            AssetInfo::Cw20Coin(token) => {
+                let remaining = self
+                    .cw20
+                    .get(&token.address)
+                    .copied()
+                    .unwrap_or_default()
+                    .checked_sub(token.amount)?;
+                if remaining.is_zero() {
+                    self.cw20.remove(&token.address);
+                } else {
+                    self.cw20.insert(token.address, remaining);
+                }
+            }
+            _ => return Err(ContractError::WrongTokenType {}),
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coins.is_empty() && self.cw20.is_empty()
+    }
+}
+
+impl TryFrom<Vec<AssetInfo>> for TokensWanted {
+    type Error = ContractError;
+
+    fn try_from(tokens: Vec<AssetInfo>) -> Result<Self, Self::Error> {
+        let mut tokens_wanted = TokensWanted::default();
+        for token in tokens {
+            tokens_wanted.add(token)?;
+        }
+        Ok(tokens_wanted)
+    }
+}
+
+impl From<TokensWanted> for Vec<AssetInfo> {
+    fn from(tokens_wanted: TokensWanted) -> Self {
+        tokens_wanted
+            .coins
+            .into_vec()
+            .into_iter()
+            .map(AssetInfo::Coin)
+            .chain(
+                tokens_wanted
+                    .cw20
+                    .into_iter()
+                    .map(|(address, amount)| AssetInfo::Cw20Coin(Cw20Coin { address, amount })),
+            )
+            .collect()
+    }
+}
+
+// The tokens wanted used to only be native coins.
+// Those structures are used to migrate the stored trades to the `AssetInfo` format
+const OLD_TRADE_INFO: Map<u64, OldTradeInfo> = Map::new("trade_info");
+const OLD_COUNTER_TRADE_INFO: Map<(u64, u64), OldTradeInfo> = Map::new("counter_trade_info");
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct OldAdditionalTradeInfo {
+    pub time: Timestamp,
+    pub owner_comment: Option<Comment>,
+    pub trader_comment: Option<Comment>,
+    pub nfts_wanted: HashSet<Addr>,
+    pub tokens_wanted: Vec<Coin>,
+    pub trade_preview: Option<AssetInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct OldTradeInfo {
+    pub owner: Addr,
+    pub associated_assets: Vec<AssetInfo>,
+    pub state: TradeState,
+    pub last_counter_id: Option<u64>,
+    pub whitelisted_users: HashSet<Addr>,
+    pub additional_info: OldAdditionalTradeInfo,
+    pub accepted_info: Option<CounterTradeInfo>,
+    pub assets_withdrawn: bool,
+}
+
+impl From<OldTradeInfo> for TradeInfo {
+    fn from(old: OldTradeInfo) -> Self {
+        TradeInfo {
+            owner: old.owner,
+            associated_assets: old.associated_assets,
+            state: old.state,
+            last_counter_id: old.last_counter_id,
+            whitelisted_users: old.whitelisted_users,
+            additional_info: AdditionalTradeInfo {
+                time: old.additional_info.time,
+                owner_comment: old.additional_info.owner_comment,
+                trader_comment: old.additional_info.trader_comment,
+                nfts_wanted: old.additional_info.nfts_wanted,
+                tokens_wanted: old
+                    .additional_info
+                    .tokens_wanted
+                    .into_iter()
+                    .map(AssetInfo::Coin)
+                    .collect(),
+                trade_preview: old.additional_info.trade_preview,
+            },
+            accepted_info: old.accepted_info,
+            assets_withdrawn: old.assets_withdrawn,
+        }
+    }
+}
+
+/// Rewrites the tokens wanted of all trades and counter trades saved with the old format.
+/// Entries that can't be parsed with the old format are already migrated and are skipped.
+pub fn migrate_tokens_wanted(storage: &mut dyn Storage) -> StdResult<()> {
+    let trades = OLD_TRADE_INFO
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    for (trade_id, old) in trades {
+        TRADE_INFO.save(storage, trade_id, &old.into())?;
+    }
+
+    let counter_trades = OLD_COUNTER_TRADE_INFO
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    for (key, old) in counter_trades {
+        COUNTER_TRADE_INFO.save(storage, key, &old.into())?;
+    }
+
+    Ok(())
+}
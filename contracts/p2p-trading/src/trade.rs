@@ -1,14 +1,16 @@
 use cosmwasm_std::{
-    coin, coins, to_json_binary, Addr, Api, BankMsg, Binary, Coin, Coins, Decimal, Deps, DepsMut,
-    Empty, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+    coin, coins, to_json_binary, Addr, Api, BankMsg, Binary, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, Uint128,
 };
 use sg721_base::msg::CollectionInfoResponse;
-use utils::state::AssetInfo;
+use utils::state::{AssetInfo, Cw1155Coin};
 
 use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
 use std::iter::FromIterator;
 
+use cw1155::{BalanceResponse, Cw1155ExecuteMsg, Cw1155QueryMsg, IsApprovedForAllResponse};
+use cw20::Cw20ExecuteMsg;
 use cw721::Cw721ExecuteMsg;
 
 use cw721_base::Extension;
@@ -21,8 +23,9 @@ use sg721::{ExecuteMsg as Sg721ExecuteMsg, RoyaltyInfoResponse};
 use crate::error::ContractError;
 use crate::messages::set_comment;
 use crate::state::{
-    add_cw721_coin, add_funds, add_sg721_coin, is_trader, load_counter_trade, CONTRACT_INFO,
-    COUNTER_TRADE_INFO, LAST_USER_TRADE, TRADE_INFO,
+    add_cw1155_coin, add_cw20_coin, add_cw721_coin, add_funds, add_sg721_coin, is_trader,
+    load_counter_trade, TokensWanted, CONTRACT_INFO, COUNTER_TRADE_INFO, LAST_USER_TRADE,
+    TRADE_INFO,
 };
 
 /// Query the last trade created by the owner.
@@ -148,9 +151,41 @@ pub fn prepare_harmless_trade_modifications(
     Ok((trade_id, trade_info))
 }
 
+/// Verifies the sender owns enough CW1155 tokens and has approved this contract to move them
+pub fn _assert_cw1155_deposit(
+    deps: Deps,
+    env: &Env,
+    owner: &Addr,
+    token: &Cw1155Coin,
+) -> Result<(), ContractError> {
+    let balance: BalanceResponse = deps.querier.query_wasm_smart(
+        token.address.clone(),
+        &Cw1155QueryMsg::Balance {
+            owner: owner.to_string(),
+            token_id: token.token_id.clone(),
+        },
+    )?;
+    if balance.balance < token.value {
+        return Err(ContractError::SenderNotOwner {});
+    }
+
+    let approval: IsApprovedForAllResponse = deps.querier.query_wasm_smart(
+        token.address.clone(),
+        &Cw1155QueryMsg::IsApprovedForAll {
+            owner: owner.to_string(),
+            operator: env.contract.address.to_string(),
+        },
+    )?;
+    if !approval.approved {
+        return Err(ContractError::TokenApprovalIssue {});
+    }
+    Ok(())
+}
+
 pub fn _create_receive_asset_messages(
+    deps: Deps,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     asset: AssetInfo,
 ) -> Result<Response, ContractError> {
     Ok(match asset {
@@ -186,6 +221,31 @@ pub fn _create_receive_asset_messages(
                 .add_attribute("nft", token.address)
                 .add_attribute("token_id", token.token_id)
         }
+        // CW20 tokens are received by the contract before being added to the trade
+        AssetInfo::Cw20Coin(token) => Response::new()
+            .add_attribute("action", "add_asset")
+            .add_attribute("asset_type", "token")
+            .add_attribute("token", token.address)
+            .add_attribute("amount", token.amount),
+        AssetInfo::Cw1155Coin(token) => {
+            _assert_cw1155_deposit(deps, &env, &info.sender, &token)?;
+
+            let message = Cw1155ExecuteMsg::SendFrom {
+                from: info.sender.to_string(),
+                to: env.contract.address.into(),
+                token_id: token.token_id.clone(),
+                value: token.value,
+                msg: None,
+            };
+
+            Response::new()
+                .add_message(into_cosmos_msg(message, token.address.clone())?)
+                .add_attribute("action", "add_asset")
+                .add_attribute("asset_type", "Cw1155")
+                .add_attribute("token", token.address)
+                .add_attribute("token_id", token.token_id)
+                .add_attribute("amount", token.value)
+        }
     })
 }
 
@@ -215,12 +275,24 @@ pub fn add_asset_to_trade(
             trade_id,
             add_sg721_coin(token.address.clone(), token.token_id),
         ),
+        AssetInfo::Cw20Coin(token) => TRADE_INFO.update(
+            deps.storage,
+            trade_id,
+            add_cw20_coin(token.address.clone(), token.amount),
+        ),
+        AssetInfo::Cw1155Coin(token) => TRADE_INFO.update(
+            deps.storage,
+            trade_id,
+            add_cw1155_coin(token.address.clone(), token.token_id, token.value),
+        ),
     }?;
 
     // Now we need to transfer the token
-    Ok(_create_receive_asset_messages(env, info.clone(), asset)?
-        .add_attribute("trade_id", trade_id.to_string())
-        .add_attribute("trader", info.sender))
+    Ok(
+        _create_receive_asset_messages(deps.as_ref(), env, info.clone(), asset)?
+            .add_attribute("trade_id", trade_id.to_string())
+            .add_attribute("trader", info.sender),
+    )
 }
 
 /// Allows to withdraw assets while creating a trade
@@ -231,7 +303,7 @@ pub fn add_asset_to_trade(
 /// This allows users to withdraw single assets without a risk of running out of gas.
 pub fn withdraw_trade_assets_while_creating(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     trade_id: u64,
     assets: Vec<(u16, AssetInfo)>,
@@ -263,6 +335,11 @@ pub fn withdraw_trade_assets_while_creating(
                     trade_info.additional_info.trade_preview = None;
                 }
             }
+            AssetInfo::Cw1155Coin(_) => {
+                if assets.iter().any(|r| r.1.overlaps(&preview)) {
+                    trade_info.additional_info.trade_preview = None;
+                }
+            }
             _ => {}
         }
     }
@@ -272,6 +349,7 @@ pub fn withdraw_trade_assets_while_creating(
     // We send the assets back to the sender
     let res = _create_withdraw_messages_unsafe(
         deps.as_ref(),
+        &env,
         &info.sender,
         &assets.iter().map(|x| x.1.clone()).collect(),
         None,
@@ -342,6 +420,43 @@ pub fn _are_assets_in_trade(
                     return Err(ContractError::AssetNotFound { position });
                 }
             }
+            AssetInfo::Cw20Coin(token_info) => {
+                // We check the token is the one we want
+                if let AssetInfo::Cw20Coin(token) = asset {
+                    // We verify the sent information matches the saved token
+                    if token_info.address != token.address {
+                        return Err(ContractError::AssetNotFound { position });
+                    }
+                    if token_info.amount < token.amount {
+                        return Err(ContractError::TooMuchWithdrawn {
+                            address: token_info.address,
+                            wanted: token.amount.u128(),
+                            available: token_info.amount.u128(),
+                        });
+                    }
+                } else {
+                    return Err(ContractError::AssetNotFound { position });
+                }
+            }
+            AssetInfo::Cw1155Coin(token_info) => {
+                // We check the token is the one we want
+                if let AssetInfo::Cw1155Coin(token) = asset {
+                    // We verify the sent information matches the saved token
+                    if token_info.address != token.address || token_info.token_id != token.token_id
+                    {
+                        return Err(ContractError::AssetNotFound { position });
+                    }
+                    if token_info.value < token.value {
+                        return Err(ContractError::TooMuchWithdrawn {
+                            address: token_info.address,
+                            wanted: token.value.u128(),
+                            available: token_info.value.u128(),
+                        });
+                    }
+                } else {
+                    return Err(ContractError::AssetNotFound { position });
+                }
+            }
         }
     }
 
@@ -379,6 +494,26 @@ pub fn _try_withdraw_assets_unsafe(
                     trade_info.associated_assets[position] = AssetInfo::Sg721Token(nft_info);
                 }
             }
+            AssetInfo::Cw20Coin(mut token_info) => {
+                if let AssetInfo::Cw20Coin(token) = asset {
+                    // If everything is in order, we remove the tokens from the trade
+                    token_info.amount = token_info
+                        .amount
+                        .checked_sub(token.amount)
+                        .map_err(ContractError::Overflow)?;
+                    trade_info.associated_assets[position] = AssetInfo::Cw20Coin(token_info);
+                }
+            }
+            AssetInfo::Cw1155Coin(mut token_info) => {
+                if let AssetInfo::Cw1155Coin(token) = asset {
+                    // If everything is in order, we remove the tokens from the trade
+                    token_info.value = token_info
+                        .value
+                        .checked_sub(token.value)
+                        .map_err(ContractError::Overflow)?;
+                    trade_info.associated_assets[position] = AssetInfo::Cw1155Coin(token_info);
+                }
+            }
         }
     }
 
@@ -387,6 +522,8 @@ pub fn _try_withdraw_assets_unsafe(
         AssetInfo::Coin(fund) => fund.amount != Uint128::zero(),
         AssetInfo::Cw721Coin(nft) => !nft.address.is_empty(),
         AssetInfo::Sg721Token(nft) => !nft.address.is_empty(),
+        AssetInfo::Cw20Coin(token) => token.amount != Uint128::zero(),
+        AssetInfo::Cw1155Coin(token) => token.value != Uint128::zero(),
     });
 
     Ok(())
@@ -398,6 +535,7 @@ pub fn _try_withdraw_assets_unsafe(
 #[allow(clippy::ptr_arg)]
 pub fn _create_withdraw_messages_unsafe(
     deps: Deps,
+    env: &Env,
     recipient: &Addr,
     assets: &Vec<AssetInfo>,
     royalties: Option<Vec<RoyaltyInfoResponse>>,
@@ -409,60 +547,29 @@ pub fn _create_withdraw_messages_unsafe(
     for asset in assets {
         match asset {
             AssetInfo::Coin(fund) => {
-                let (royalty_msgs, funds_after_royalties) = if let Some(royalties) = &royalties {
-                    let nb_royalties = royalties.len() as u128;
-
-                    if nb_royalties == 0 {
-                        (vec![], fund.clone())
-                    } else {
-                        let mut total_amount = fund.amount;
-                        let amount_per_nft = fund.amount / Uint128::from(nb_royalties);
-
-                        let msgs = royalties
-                            .iter()
-                            .filter_map(|r| {
-                                let amount = r.share * amount_per_nft;
-                                total_amount -= amount;
-                                if amount.is_zero() {
-                                    None
-                                } else {
-                                    Some(BankMsg::Send {
-                                        to_address: r.payment_address.clone(),
-                                        amount: coins(amount.u128(), fund.denom.clone()),
-                                    })
-                                }
-                            })
-                            .collect::<Vec<_>>();
-
-                        (msgs, coin(total_amount.u128(), fund.denom.clone()))
-                    }
+                let (royalty_amounts, fee_amount, remaining_amount) =
+                    _split_fungible_amount(fund.amount, &royalties, fund_fee);
+
+                let royalty_msgs = royalty_amounts
+                    .into_iter()
+                    .map(|(payment_address, amount)| BankMsg::Send {
+                        to_address: payment_address,
+                        amount: coins(amount.u128(), fund.denom.clone()),
+                    })
+                    .collect::<Vec<_>>();
+
+                let fee_msgs = if fee_amount.is_zero() {
+                    None
                 } else {
-                    (vec![], fund.clone())
-                };
-
-                let (fee_msgs, funds_for_recipient) = if let Some(fund_fee) = fund_fee {
-                    let fee_amount = funds_after_royalties.amount * fund_fee;
-                    let fee_denom = funds_after_royalties.denom.clone();
-                    let remaining_amount = funds_after_royalties.amount - fee_amount;
-
-                    (
-                        if fee_amount.is_zero() {
-                            None
-                        } else {
-                            Some(BankMsg::Send {
-                                to_address: CONTRACT_INFO.load(deps.storage)?.treasury.to_string(),
-                                amount: coins(fee_amount.u128(), fee_denom.clone()),
-                            })
-                        },
-                        coin(remaining_amount.u128(), fee_denom),
-                    )
-                } else {
-                    (None, funds_after_royalties)
+                    Some(BankMsg::Send {
+                        to_address: CONTRACT_INFO.load(deps.storage)?.treasury.to_string(),
+                        amount: coins(fee_amount.u128(), fund.denom.clone()),
+                    })
                 };
 
                 let withdraw_message = BankMsg::Send {
                     to_address: recipient.to_string(),
-                    amount: vec![funds_for_recipient.clone()],
+                    amount: vec![coin(remaining_amount.u128(), fund.denom.clone())],
                 };
 
                 res = res
@@ -495,15 +602,119 @@ pub fn _create_withdraw_messages_unsafe(
                     .add_attribute("nft", nft.address.clone())
                     .add_attribute("token_id", nft.token_id.clone());
             }
+            AssetInfo::Cw20Coin(token) => {
+                let (royalty_amounts, fee_amount, remaining_amount) =
+                    _split_fungible_amount(token.amount, &royalties, fund_fee);
+
+                // CW20 contracts don't accept zero transfers
+                let royalty_msgs = royalty_amounts
+                    .into_iter()
+                    .map(|(payment_address, amount)| {
+                        into_cosmos_msg(
+                            Cw20ExecuteMsg::Transfer {
+                                recipient: payment_address,
+                                amount,
+                            },
+                            token.address.clone(),
+                        )
+                    })
+                    .collect::<StdResult<Vec<_>>>()?;
+
+                let fee_msgs = if fee_amount.is_zero() {
+                    None
+                } else {
+                    Some(into_cosmos_msg(
+                        Cw20ExecuteMsg::Transfer {
+                            recipient: CONTRACT_INFO.load(deps.storage)?.treasury.to_string(),
+                            amount: fee_amount,
+                        },
+                        token.address.clone(),
+                    )?)
+                };
+
+                let withdraw_messages = if remaining_amount.is_zero() {
+                    None
+                } else {
+                    Some(into_cosmos_msg(
+                        Cw20ExecuteMsg::Transfer {
+                            recipient: recipient.to_string(),
+                            amount: remaining_amount,
+                        },
+                        token.address.clone(),
+                    )?)
+                };
+
+                res = res
+                    .add_messages(royalty_msgs)
+                    .add_messages(fee_msgs)
+                    .add_messages(withdraw_messages)
+                    .add_attribute("asset_type", "token")
+                    .add_attribute("token", token.address.clone())
+                    .add_attribute("amount", token.amount);
+            }
+            AssetInfo::Cw1155Coin(token) => {
+                let message = Cw1155ExecuteMsg::SendFrom {
+                    from: env.contract.address.to_string(),
+                    to: recipient.to_string(),
+                    token_id: token.token_id.clone(),
+                    value: token.value,
+                    msg: None,
+                };
+                res = res
+                    .add_message(into_cosmos_msg(message, token.address.clone())?)
+                    .add_attribute("asset_type", "Cw1155")
+                    .add_attribute("token", token.address.clone())
+                    .add_attribute("token_id", token.token_id.clone())
+                    .add_attribute("amount", token.value);
+            }
         }
     }
 
     Ok(res)
 }
 
+/// Splits a fungible amount between the royalty receivers, the treasury (`fund_fee`) and the recipient
+/// Returns the non-zero royalty amounts, the fee amount and the amount left for the recipient
+pub fn _split_fungible_amount(
+    amount: Uint128,
+    royalties: &Option<Vec<RoyaltyInfoResponse>>,
+    fund_fee: Option<Decimal>,
+) -> (Vec<(String, Uint128)>, Uint128, Uint128) {
+    let mut amount_after_royalties = amount;
+    let royalty_amounts = match royalties {
+        Some(royalties) if !royalties.is_empty() => {
+            let amount_per_nft = amount / Uint128::from(royalties.len() as u128);
+            royalties
+                .iter()
+                .filter_map(|r| {
+                    let royalty_amount = r.share * amount_per_nft;
+                    amount_after_royalties -= royalty_amount;
+                    if royalty_amount.is_zero() {
+                        None
+                    } else {
+                        Some((r.payment_address.clone(), royalty_amount))
+                    }
+                })
+                .collect()
+        }
+        _ => vec![],
+    };
+
+    let fee_amount = fund_fee
+        .map(|fund_fee| amount_after_royalties * fund_fee)
+        .unwrap_or_default();
+
+    (
+        royalty_amounts,
+        fee_amount,
+        amount_after_royalties - fee_amount,
+    )
+}
+
 /// Check the assets are not already withdrawn and then creates the withdraw messages
 pub fn check_and_create_withdraw_messages(
     deps: Deps,
+    env: &Env,
     recipient: &Addr,
     trade_info: &TradeInfo,
     royalties: Option<Vec<RoyaltyInfoResponse>>,
@@ -515,6 +726,7 @@ pub fn check_and_create_withdraw_messages(
 
     _create_withdraw_messages_unsafe(
         deps,
+        env,
         recipient,
         &trade_info.associated_assets,
         royalties,
@@ -534,6 +746,8 @@ pub fn gather_royalties(
         .filter_map(|asset| match asset {
             AssetInfo::Cw721Coin(_) => None,
             AssetInfo::Coin(_) => None,
+            AssetInfo::Cw20Coin(_) => None,
+            AssetInfo::Cw1155Coin(_) => None,
             AssetInfo::Sg721Token(token) => {
                 let collection_info: Result<CollectionInfoResponse, _> =
                     deps.querier.query_wasm_smart(
@@ -560,6 +774,14 @@ pub fn validate_addresses(api: &dyn Api, whitelisted_users: &[String]) -> StdRes
         .collect()
 }
 
+/// Helper to validate the CW20 addresses of the tokens wanted
+pub fn validate_tokens_wanted(api: &dyn Api, tokens_wanted: &[AssetInfo]) -> StdResult<()> {
+    tokens_wanted.iter().try_for_each(|token| match token {
+        AssetInfo::Cw20Coin(token) => api.addr_validate(&token.address).map(|_| ()),
+        _ => Ok(()),
+    })
+}
+
 /// Add new whitelisted users to a trade
 pub fn add_whitelisted_users(
     storage: &mut dyn Storage,
@@ -729,13 +951,15 @@ pub fn add_tokens_wanted(
     _env: Env,
     info: MessageInfo,
     trade_id: Option<u64>,
-    tokens_wanted: Vec<Coin>,
+    tokens_wanted: Vec<AssetInfo>,
 ) -> Result<Response, ContractError> {
     // We verify the trade can be modified
     let (trade_id, mut trade_info) =
         prepare_harmless_trade_modifications(deps.as_ref(), info.sender.clone(), trade_id)?;
+    validate_tokens_wanted(deps.api, &tokens_wanted)?;
 
-    let mut old_tokens_wanted: Coins = trade_info.additional_info.tokens_wanted.try_into()?;
+    let mut old_tokens_wanted: TokensWanted =
+        trade_info.additional_info.tokens_wanted.try_into()?;
 
     for token in tokens_wanted {
         old_tokens_wanted.add(token)?;
@@ -759,13 +983,14 @@ pub fn remove_tokens_wanted(
     _env: Env,
     info: MessageInfo,
     trade_id: u64,
-    tokens_wanted: Vec<Coin>,
+    tokens_wanted: Vec<AssetInfo>,
 ) -> Result<Response, ContractError> {
     // We verify the trade can be modified
     let mut trade_info = is_trader(deps.storage, &info.sender, trade_id)?;
     // We modify the whitelist
 
-    let mut old_tokens_wanted: Coins = trade_info.additional_info.tokens_wanted.try_into()?;
+    let mut old_tokens_wanted: TokensWanted =
+        trade_info.additional_info.tokens_wanted.try_into()?;
 
     for token in tokens_wanted {
         old_tokens_wanted.sub(token)?;
@@ -789,15 +1014,16 @@ pub fn set_tokens_wanted(
     _env: Env,
     info: MessageInfo,
     trade_id: Option<u64>,
-    tokens_wanted: Vec<Coin>,
+    tokens_wanted: Vec<AssetInfo>,
 ) -> Result<Response, ContractError> {
     // We verify the trade can be modified
     let (trade_id, mut trade_info) =
         prepare_harmless_trade_modifications(deps.as_ref(), info.sender.clone(), trade_id)?;
+    validate_tokens_wanted(deps.api, &tokens_wanted)?;
 
-    // We modify the coins wanted
-    let validated_coins: Coins = tokens_wanted.try_into()?;
-    trade_info.additional_info.tokens_wanted = validated_coins.into();
+    // We modify the tokens wanted
+    let validated_tokens: TokensWanted = tokens_wanted.try_into()?;
+    trade_info.additional_info.tokens_wanted = validated_tokens.into();
 
     TRADE_INFO.save(deps.storage, trade_id, &trade_info)?;
 
@@ -978,7 +1204,7 @@ pub fn cancel_trade(
 /// If the trade is only in the created state, it is automatically cancelled before withdrawing assets
 pub fn withdraw_all_from_trade(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     trade_id: u64,
 ) -> Result<Response, ContractError> {
@@ -994,8 +1220,14 @@ pub fn withdraw_all_from_trade(
         return Err(ContractError::TradeNotCancelled {});
     }
 
-    let res =
-        check_and_create_withdraw_messages(deps.as_ref(), &info.sender, &trade_info, None, None)?;
+    let res = check_and_create_withdraw_messages(
+        deps.as_ref(),
+        &env,
+        &info.sender,
+        &trade_info,
+        None,
+        None,
+    )?;
     trade_info.assets_withdrawn = true;
     TRADE_INFO.save(deps.storage, trade_id, &trade_info)?;
 
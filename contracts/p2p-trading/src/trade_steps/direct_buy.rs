@@ -1,36 +1,52 @@
 use std::convert::TryInto;
 
-use cosmwasm_std::{Coins, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response};
+use utils::state::AssetInfo;
 
 use crate::{
     counter_trade::{add_asset_to_counter_trade, confirm_counter_trade, suggest_counter_trade},
-    state::{can_suggest_counter_trade, LAST_USER_COUNTER_TRADE},
+    state::{can_suggest_counter_trade, TokensWanted, LAST_USER_COUNTER_TRADE},
     trade::accept_trade,
     ContractError,
 };
 
 pub fn direct_buy(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
     trade_id: u64,
     on_behalf_of: Option<String>,
+) -> Result<Response, ContractError> {
+    let sent_assets = info.funds.into_iter().map(AssetInfo::Coin).collect();
+    _direct_buy(deps, env, info.sender, sent_assets, trade_id, on_behalf_of)
+}
+
+/// Buys a trade with the sent assets (native coins or CW20 tokens)
+/// The sent assets must match the tokens wanted by the trader exactly
+pub fn _direct_buy(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    sent_assets: Vec<AssetInfo>,
+    trade_id: u64,
+    on_behalf_of: Option<String>,
 ) -> Result<Response, ContractError> {
     // We make sure the sender can buy the specified assets
     let buyer = on_behalf_of
         .map(|o| deps.api.addr_validate(&o))
         .transpose()?
-        .unwrap_or(info.sender);
+        .unwrap_or(sender);
     let trade_info = can_suggest_counter_trade(deps.storage, trade_id, &buyer)?;
 
     // We make sure the necessary funds are sent with this message
     {
-        let mut all_tokens_wanted: Coins = trade_info.additional_info.tokens_wanted.try_into()?;
+        let mut all_tokens_wanted: TokensWanted =
+            trade_info.additional_info.tokens_wanted.try_into()?;
         if all_tokens_wanted.is_empty() {
             return Err(ContractError::NotBuyableDirectly {});
         }
 
-        for token in info.funds.clone() {
+        for token in sent_assets.clone() {
             all_tokens_wanted.sub(token)?;
         }
 
@@ -61,19 +77,20 @@ pub fn direct_buy(
         )?;
         let counter_id = LAST_USER_COUNTER_TRADE.load(deps.storage, (&buyer, trade_id))?;
 
-        let all_add_assets_response = info
-            .funds
+        let all_add_assets_response = sent_assets
             .into_iter()
-            .map(|f| {
+            .map(|asset| {
                 let mut info = buyer_info.clone();
-                info.funds = vec![f.clone()];
+                if let AssetInfo::Coin(f) = &asset {
+                    info.funds = vec![f.clone()];
+                }
                 add_asset_to_counter_trade(
                     deps.branch(),
                     env.clone(),
                     info.clone(),
                     trade_id,
                     Some(counter_id),
-                    utils::state::AssetInfo::Coin(f),
+                    asset,
                 )
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -6,19 +6,22 @@ use cosmwasm_std::{
 use crate::{
     error::ContractError,
     execute::{
-        execute_buy_tickets, execute_cancel_raffle, execute_claim, execute_create_raffle,
-        execute_modify_raffle, execute_sudo_toggle_lock, execute_toggle_lock,
-        execute_update_config,
+        execute_add_prizes, execute_buy_tickets, execute_cancel_raffle, execute_claim,
+        execute_claim_referral_rewards, execute_claim_refund, execute_create_raffle,
+        execute_expire_raffle, execute_migrate_tickets, execute_modify_raffle, execute_receive,
+        execute_sudo_toggle_lock, execute_toggle_lock, execute_update_config,
     },
     msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RaffleResponse},
     query::{
         add_raffle_winners, query_all_raffles, query_all_tickets, query_config, query_discount,
-        query_ticket_count,
+        query_earliest_randomness_round, query_is_allowlisted, query_merkle_root,
+        query_prize_tiers, query_referrals, query_refund, query_ticket_cost, query_ticket_count,
     },
-    randomness::{execute_update_randomness, verify_randomness},
+    randomness::{execute_nois_receive, execute_update_randomness, verify_randomness},
     state::{
-        get_raffle_state, load_raffle, Config, CONFIG, MAX_TICKET_NUMBER, MINIMUM_RAFFLE_DURATION,
-        OLD_CONFIG, STATIC_RAFFLE_CREATION_FEE,
+        get_raffle_state, load_raffle, migrate_ticket_ranges, Config, CONFIG,
+        DEFAULT_RANDOMNESS_DEADLINE, MAX_TICKET_NUMBER, MINIMUM_RAFFLE_DURATION, OLD_CONFIG,
+        STATIC_RAFFLE_CREATION_FEE, TICKET_MIGRATION_BATCH,
     },
 };
 use utils::{
@@ -69,6 +72,7 @@ pub fn instantiate(
             .unwrap_or(MINIMUM_RAFFLE_DURATION)
             .max(MINIMUM_RAFFLE_DURATION),
         raffle_fee: msg.raffle_fee,
+        randomness_provider_fee: msg.randomness_provider_fee.unwrap_or_default(),
         locks: Locks {
             lock: false,
             sudo_lock: false,
@@ -82,6 +86,11 @@ pub fn instantiate(
             .collect::<Result<_, _>>()?,
 
         drand_config: msg.drand_config,
+        randomness_source: msg.randomness_source.unwrap_or_default().check(deps.api)?,
+        randomness_deadline: msg
+            .randomness_deadline
+            .unwrap_or(DEFAULT_RANDOMNESS_DEADLINE),
+        referral_fee: msg.referral_fee.unwrap_or_default(),
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -95,6 +104,9 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), ::cosmwasm_std::entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // Tickets are now stored as ranges. Only a first batch is converted here,
+    // the rest is converted with `ExecuteMsg::MigrateTickets`
+    migrate_ticket_ranges(deps.storage, TICKET_MIGRATION_BATCH)?;
     set_contract_version(
         deps.storage,
         env!("CARGO_PKG_NAME"),
@@ -116,6 +128,7 @@ pub fn execute(
             assets,
             raffle_options,
             raffle_ticket_price,
+            additional_ticket_prices,
         } => execute_create_raffle(
             deps,
             env,
@@ -123,12 +136,17 @@ pub fn execute(
             owner,
             assets,
             raffle_ticket_price,
+            additional_ticket_prices,
             raffle_options,
         ),
         ExecuteMsg::CancelRaffle { raffle_id } => execute_cancel_raffle(deps, env, info, raffle_id),
+        ExecuteMsg::AddPrizes { raffle_id, assets } => {
+            execute_add_prizes(deps, env, info, raffle_id, assets)
+        }
         ExecuteMsg::ModifyRaffle {
             raffle_id,
             raffle_ticket_price,
+            additional_ticket_prices,
             raffle_options,
         } => execute_modify_raffle(
             deps,
@@ -136,6 +154,7 @@ pub fn execute(
             info,
             raffle_id,
             raffle_ticket_price,
+            additional_ticket_prices,
             raffle_options,
         ),
         ExecuteMsg::BuyTicket {
@@ -143,6 +162,8 @@ pub fn execute(
             ticket_count,
             sent_assets,
             on_behalf_of,
+            allowlist_proof,
+            referrer,
         } => execute_buy_tickets(
             deps,
             env,
@@ -151,8 +172,17 @@ pub fn execute(
             ticket_count,
             sent_assets,
             on_behalf_of,
+            allowlist_proof,
+            referrer,
         ),
+        ExecuteMsg::Receive(cw20_msg) => execute_receive(deps, env, info, cw20_msg),
         ExecuteMsg::ClaimRaffle { raffle_id } => execute_claim(deps, env, raffle_id),
+        ExecuteMsg::ExpireRaffle { raffle_id } => execute_expire_raffle(deps, env, raffle_id),
+        ExecuteMsg::ClaimRefund { raffle_id } => execute_claim_refund(deps, env, info, raffle_id),
+        ExecuteMsg::ClaimReferralRewards { raffle_id } => {
+            execute_claim_referral_rewards(deps, env, info, raffle_id)
+        }
+        ExecuteMsg::MigrateTickets { limit } => execute_migrate_tickets(deps, env, info, limit),
         ExecuteMsg::ToggleLock { lock } => execute_toggle_lock(deps, env, info, lock),
         ExecuteMsg::UpdateConfig {
             name,
@@ -161,7 +191,11 @@ pub fn execute(
             minimum_raffle_duration,
             max_tickets_per_raffle,
             raffle_fee,
+            randomness_provider_fee,
             drand_config,
+            randomness_source,
+            randomness_deadline,
+            referral_fee,
             creation_coins,
             fee_discounts,
         } => execute_update_config(
@@ -174,7 +208,11 @@ pub fn execute(
             minimum_raffle_duration,
             max_tickets_per_raffle,
             raffle_fee,
+            randomness_provider_fee,
             drand_config,
+            randomness_source,
+            randomness_deadline,
+            referral_fee,
             creation_coins,
             fee_discounts,
         ),
@@ -182,6 +220,7 @@ pub fn execute(
             raffle_id,
             randomness,
         } => execute_update_randomness(deps, env, info, raffle_id, randomness),
+        ExecuteMsg::NoisReceive { callback } => execute_nois_receive(deps, env, info, callback),
     }
 }
 
@@ -208,6 +247,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<QueryResponse, Contr
             to_json_binary(&RaffleResponse {
                 raffle_id,
                 raffle_state,
+                prize_tiers: query_prize_tiers(&raffle_info),
                 raffle_info: Some(raffle_info),
             })?
         }
@@ -231,6 +271,27 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<QueryResponse, Contr
             to_json_binary(&query_ticket_count(deps, env, raffle_id, owner)?)?
         }
         QueryMsg::FeeDiscount { user } => to_json_binary(&query_discount(deps, user)?)?,
+        QueryMsg::Refund { user, raffle_id } => {
+            to_json_binary(&query_refund(deps, env, raffle_id, user)?)?
+        }
+        QueryMsg::TicketCost {
+            raffle_id,
+            ticket_count,
+        } => to_json_binary(&query_ticket_cost(deps, raffle_id, ticket_count)?)?,
+        QueryMsg::Referrals {
+            referrer,
+            start_after,
+            limit,
+        } => to_json_binary(&query_referrals(deps, referrer, start_after, limit)?)?,
+        QueryMsg::EarliestRandomnessRound { raffle_id } => {
+            to_json_binary(&query_earliest_randomness_round(deps, raffle_id)?)?
+        }
+        QueryMsg::MerkleRoot { raffle_id } => to_json_binary(&query_merkle_root(deps, raffle_id)?)?,
+        QueryMsg::IsAllowlisted {
+            raffle_id,
+            user,
+            proof,
+        } => to_json_binary(&query_is_allowlisted(deps, raffle_id, user, proof)?)?,
     };
     Ok(response)
 }
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use cosmwasm_std::{StdError, Timestamp};
+use cosmwasm_std::{Coin, StdError, Timestamp};
 use utils::state::AssetInfo;
 
 use crate::state::{AdvantageOptions, RaffleState};
@@ -128,20 +128,40 @@ pub enum ContractError {
     #[error("A raffle can only be done with CW721 or SG721 assets")]
     WrongAssetType {},
 
-    #[error("Tickets to a raffle can only be bought with native assets.")]
+    #[error("Tickets to a raffle can only be bought with native or CW20 assets.")]
     WrongFundsType {},
 
     #[error("The sent asset doesn't match the asset in the message sent along with it")]
     AssetMismatch {},
 
+    #[error("The randomness request price ({price}) must be sent along")]
+    RandomnessPaymentMissing { price: Coin },
+
     #[error("Please include at least one asset when creating a raffle")]
     NoAssets {},
 
+    #[error("A raffle can't have more than {max} prizes")]
+    TooManyAssets { max: u32 },
+
+    #[error("Prizes can't be added to a raffle with prize tiers")]
+    PrizeTiersLocked {},
+
     #[error("Duplicate assets are not allowed inside raffles !")]
     DuplicateAssets {},
 
-    // TODO: update to calculate value
-    #[error("The sent assets ({assets_received:?})  don't match the required assets ({assets_wanted:?}) multiplied ({ticket_count:?}) for this raffle")]
+    #[error("A raffle can't accept multiple ticket prices in the same currency")]
+    DuplicateTicketPrices {},
+
+    #[error("Ticket buyers can't refer themselves")]
+    SelfReferral {},
+
+    #[error("There are no referral rewards to claim")]
+    NoReferralRewards {},
+
+    #[error("Referral rewards were already claimed")]
+    ReferralRewardsAlreadyClaimed {},
+
+    #[error("The sent assets ({assets_received:?}) don't match the price of {ticket_count} tickets ({assets_wanted:?}) for this raffle")]
     PaymentNotSufficient {
         ticket_count: u32,
         assets_wanted: AssetInfo,
@@ -165,18 +185,53 @@ pub enum ContractError {
     #[error("The provided randomness is invalid current round : {current_round:?}")]
     RandomnessNotAccepted { current_round: u64 },
 
+    #[error("Randomness round {round:?} was published before the raffle closed. Earliest round : {earliest_round:?}")]
+    RandomnessTooEarly { round: u64, earliest_round: u64 },
+
+    #[error(
+        "Randomness published at {published} was generated before the raffle end : {raffle_end}"
+    )]
+    RandomnessPublishedTooEarly {
+        published: Timestamp,
+        raffle_end: Timestamp,
+    },
+
+    #[error("The randomness deadline can't be shorter than the randomness timeout")]
+    InvalidRandomnessDeadline {},
+
+    #[error("Only raffles that didn't receive randomness before the deadline can expire. Current status : {status:?}")]
+    WrongStateForExpire { status: RaffleState },
+
+    #[error("The prizes of this raffle were already returned to its owner")]
+    RaffleAlreadyExpired {},
+
+    #[error("This contract doesn't accept randomness from this source")]
+    WrongRandomnessSource {},
+
     #[error("This raffle is not ready to accept new randomness. Only Closed raffles can be decided upon. Current status : {status:?}")]
     WrongStateForRandomness { status: RaffleState },
 
     #[error("This raffle is not ready to be claimed.  Current status : {status:?}")]
     WrongStateForClaim { status: RaffleState },
 
+    #[error("Tickets of this raffle can't be refunded.  Current status : {status:?}")]
+    WrongStateForRefund { status: RaffleState },
+
+    #[error("There is nothing to refund for this user")]
+    NothingToRefund {},
+
+    #[error("The refund for this raffle was already claimed")]
+    RefundAlreadyClaimed {},
+
     #[error("This raffle cannot be cancelled anymore,   Current status : {status:?}")]
     WrongStateForCancel { status: RaffleState },
 
     #[error("This raffle is not able to be modified.  Current status : {status:?}")]
     WrongStateForModify { status: RaffleState },
 
+    #[error("Prizes can only be added before the raffle ends.  Current status : {status:?}")]
+    WrongStateForAddPrizes { status: RaffleState },
+
     #[error("This raffle has already started.")]
     RaffleAlreadyStarted {},
 
@@ -189,6 +244,9 @@ pub enum ContractError {
     #[error("The raffle comment is ({size}) bytes, must be <=  ({max}) bytes")]
     CommentTooLarge { size: u64, max: u64 },
 
+    #[error("The ticket buyer is not part of the raffle allowlist")]
+    NotAllowlisted {},
+
     #[error("The token gated conditions {condition:?} is not met for user {user}")]
     NotGatingCondition {
         condition: AdvantageOptions,
@@ -1,7 +1,9 @@
 use cosmwasm_std::{
-    ensure, ensure_eq, Addr, BankMsg, Coin, Coins, Decimal, DepsMut, Empty, Env, MessageInfo,
-    StdError, StdResult, Uint128,
+    ensure, ensure_eq, from_json, Addr, BankMsg, Coin, Coins, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, StdError, StdResult, Uint128,
 };
+use cw1155::Cw1155ExecuteMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw721::Cw721ExecuteMsg;
 use cw721_base::Extension;
 
@@ -9,23 +11,30 @@ use cw721_base::Extension;
 use {crate::query::is_sg721_owner, sg721::ExecuteMsg as Sg721ExecuteMsg};
 
 use utils::{
-    state::{all_elements_unique, into_cosmos_msg, is_valid_comment, is_valid_name, AssetInfo},
+    state::{
+        all_elements_unique, into_cosmos_msg, is_valid_comment, is_valid_name, AssetInfo, Cw20Coin,
+    },
     types::{CosmosMsg, Response},
 };
 
 use crate::{
     error::ContractError,
-    msg::DrandConfig,
-    query::is_nft_owner,
+    msg::{AllowlistProof, DrandConfig, ReceiveMsg},
+    query::{is_approved_cw1155, is_cw1155_owner, is_nft_owner},
+    randomness::{request_randomness_msgs, take_randomness_payment},
     state::{
-        get_raffle_state, load_raffle, Config, FeeDiscountMsg, RaffleInfo, RaffleOptions,
-        RaffleOptionsMsg, RaffleState, CONFIG, MINIMUM_RAFFLE_DURATION, RAFFLE_INFO,
-        RAFFLE_TICKETS, USER_TICKETS,
+        check_fee_total, get_raffle_state, load_raffle, migrate_ticket_ranges, Config,
+        FeeDiscountMsg, RaffleFees, RaffleInfo, RaffleOptions, RaffleOptionsMsg, RaffleState,
+        RandomnessSource, TicketRange, CLAIMED_REFUNDS, CONFIG, MAX_RAFFLE_ASSETS,
+        MINIMUM_RAFFLE_DURATION, RAFFLE_INFO, RAFFLE_TICKET_RANGES, REFERRALS,
+        TICKET_MIGRATION_BATCH, USER_ADDITIONAL_TICKETS, USER_TICKETS, USER_TICKET_DISCOUNTS,
     },
     utils::{
-        buyer_can_buy_ticket, can_buy_ticket, get_raffle_owner_funds_finished_messages,
-        get_raffle_owner_messages, get_raffle_refund_funds_finished_messages,
-        get_raffle_winner_messages, get_raffle_winners, is_raffle_owner, ticket_cost,
+        assert_allowlisted, buyer_can_buy_ticket, can_buy_ticket, check_ticket_prices,
+        get_payment_messages, get_raffle_owner_funds_finished_messages, get_raffle_owner_messages,
+        get_raffle_refund_messages, get_raffle_winner_messages, get_raffle_winners,
+        is_raffle_owner, is_raffle_refunded, is_refund_open, same_ticket_currency, ticket_cost,
+        ticket_payment, ticket_price_amount, user_ticket_payments,
     },
 };
 
@@ -39,20 +48,20 @@ pub fn execute_create_raffle(
     owner: Option<String>,
     all_assets: Vec<AssetInfo>,
     raffle_ticket_price: AssetInfo,
+    additional_ticket_prices: Vec<AssetInfo>,
     raffle_options: RaffleOptionsMsg,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    // verify ticket cost atleast 1
-    match raffle_ticket_price.clone() {
-        AssetInfo::Cw721Coin(_) => return Err(ContractError::InvalidTicketCost),
-        AssetInfo::Coin(coin) => {
-            if coin.amount < Uint128::one() {
-                return Err(ContractError::InvalidTicketCost {});
-            };
-        }
-        AssetInfo::Sg721Token(_) => return Err(ContractError::InvalidTicketCost),
-    }
+    // verify ticket costs atleast 1, each in its own currency
+    check_ticket_prices(
+        deps.api,
+        &[
+            vec![raffle_ticket_price.clone()],
+            additional_ticket_prices.clone(),
+        ]
+        .concat(),
+    )?;
 
     if config.locks.lock || config.locks.sudo_lock {
         return Err(ContractError::ContractIsLocked {});
@@ -60,6 +69,9 @@ pub fn execute_create_raffle(
 
     let mut sent_coins: Coins = info.funds.try_into()?;
 
+    // The owner pays for the randomness request
+    take_randomness_payment(&config, &mut sent_coins)?;
+
     // checks if the required fee was sent.
     let fee = sent_coins
         .iter()
@@ -87,6 +99,12 @@ pub fn execute_create_raffle(
     if all_assets.is_empty() {
         return Err(ContractError::NoAssets {});
     }
+    ensure!(
+        all_assets.len() <= MAX_RAFFLE_ASSETS as usize,
+        ContractError::TooManyAssets {
+            max: MAX_RAFFLE_ASSETS
+        }
+    );
 
     // Make sure there is no duplicate
     if !all_elements_unique(&all_assets) {
@@ -94,19 +112,75 @@ pub fn execute_create_raffle(
     }
 
     // Then we physcially transfer all the assets
-    let transfer_messages: Vec<CosmosMsg> = all_assets
+    let transfer_messages = get_asset_deposit_messages(
+        deps.as_ref(),
+        &env,
+        &info.sender,
+        &all_assets,
+        &mut sent_coins,
+    )?;
+
+    // Then we create the internal raffle structure
+    let owner = owner.map(|x| deps.api.addr_validate(&x)).transpose()?;
+    // defines the fee token to send to nois-proxy, by the smart contract
+    let raffle_id = _create_raffle(
+        deps.branch(),
+        env.clone(),
+        owner.clone().unwrap_or_else(|| info.sender.clone()),
+        all_assets,
+        raffle_ticket_price,
+        additional_ticket_prices,
+        raffle_options.clone(),
+    )?;
+
+    let raffle_options = RAFFLE_INFO.load(deps.storage, raffle_id)?.raffle_options;
+    let raffle_lifecycle = raffle_options.raffle_end().plus_seconds(NOIS_TIMEOUT);
+
+    // Randomness sources that need a request (Nois) are asked right away, for after the raffle end
+    let mut msgs = request_randomness_msgs(&config, raffle_id, raffle_lifecycle)?;
+
+    // bypass sending fee if static raffle creation cost is 0
+    if !fee.amount.is_zero() {
+        // transfer only the calculated fee amount from the coins sent
+        let transfer_fee_msg: CosmosMsg = BankMsg::Send {
+            to_address: config.fee_addr.to_string(),
+            amount: vec![fee],
+        }
+        .into();
+        // add msg to response
+        msgs.push(transfer_fee_msg);
+    };
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_messages(transfer_messages)
+        .add_attribute("action", "create_raffle")
+        .add_attribute("raffle_id", raffle_id.to_string())
+        .add_attribute("owner", owner.unwrap_or_else(|| info.sender.clone())))
+}
+
+/// Util to get the messages transferring assets from their depositor to the contract.
+/// Native coins must be part of `sent_coins`, they are removed from it
+fn get_asset_deposit_messages(
+    deps: Deps,
+    env: &Env,
+    depositor: &Addr,
+    assets: &[AssetInfo],
+    sent_coins: &mut Coins,
+) -> StdResult<Vec<CosmosMsg>> {
+    Ok(assets
         .iter()
         .map(|asset| match &asset {
             AssetInfo::Cw721Coin(token) => {
                 // Before the transfer, verify current NFT owner
                 // Otherwise, this would cause anyone to be able to create loans in the name of the owner if a bad approval was done
                 is_nft_owner(
-                    deps.as_ref(),
-                    info.sender.clone(),
+                    deps,
+                    depositor.clone(),
                     token.address.to_string(),
                     token.token_id.to_string(),
                 )?;
-                // Transfer the nft from raffle creator to the raffle contract.
+                // Transfer the nft from the depositor to the raffle contract.
                 let message = Cw721ExecuteMsg::TransferNft {
                     recipient: env.contract.address.clone().into(),
                     token_id: token.token_id.clone(),
@@ -118,8 +192,8 @@ pub fn execute_create_raffle(
             AssetInfo::Sg721Token(token) => {
                 // verify ownership
                 is_sg721_owner(
-                    deps.as_ref(),
-                    info.sender.clone(),
+                    deps,
+                    depositor.clone(),
                     token.address.to_string(),
                     token.token_id.to_string(),
                 )?;
@@ -138,50 +212,42 @@ pub fn execute_create_raffle(
                 // There is no additional message to send there
                 Ok(None)
             }
+            AssetInfo::Cw20Coin(token) => {
+                // The raffle contract needs an allowance on the tokens beforehand
+                let message = Cw20ExecuteMsg::TransferFrom {
+                    owner: depositor.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: token.amount,
+                };
+
+                into_cosmos_msg(message, token.address.clone(), None).map(Some)
+            }
+            AssetInfo::Cw1155Coin(token) => {
+                // verify balance and approval
+                is_cw1155_owner(
+                    deps,
+                    depositor.clone(),
+                    token.address.to_string(),
+                    token.token_id.to_string(),
+                    token.value,
+                )?;
+                is_approved_cw1155(deps, env, depositor.clone(), token.address.to_string())?;
+                // Transfer the tokens from the depositor to the raffle contract.
+                let message = Cw1155ExecuteMsg::SendFrom {
+                    from: depositor.to_string(),
+                    to: env.contract.address.to_string(),
+                    token_id: token.token_id.clone(),
+                    value: token.value,
+                    msg: None,
+                };
+
+                into_cosmos_msg(message, token.address.clone(), None).map(Some)
+            }
         })
         .collect::<Result<Vec<Option<CosmosMsg>>, StdError>>()?
         .into_iter()
         .flatten()
-        .collect();
-
-    // Then we create the internal raffle structure
-    let owner = owner.map(|x| deps.api.addr_validate(&x)).transpose()?;
-    // defines the fee token to send to nois-proxy, by the smart contract
-    let raffle_id = _create_raffle(
-        deps.branch(),
-        env.clone(),
-        owner.clone().unwrap_or_else(|| info.sender.clone()),
-        all_assets,
-        raffle_ticket_price,
-        raffle_options.clone(),
-    )?;
-
-    let raffle_options = RAFFLE_INFO.load(deps.storage, raffle_id)?.raffle_options;
-    let raffle_lifecycle = raffle_options
-        .raffle_start_timestamp
-        .plus_seconds(raffle_options.raffle_duration)
-        .plus_seconds(NOIS_TIMEOUT);
-
-    let mut msgs = vec![];
-
-    // bypass sending fee if static raffle creation cost is 0
-    if !fee.amount.is_zero() {
-        // transfer only the calculated fee amount from the coins sent
-        let transfer_fee_msg: CosmosMsg = BankMsg::Send {
-            to_address: config.fee_addr.to_string(),
-            amount: vec![fee],
-        }
-        .into();
-        // add msg to response
-        msgs.push(transfer_fee_msg);
-    };
-
-    Ok(Response::new()
-        .add_messages(msgs)
-        .add_messages(transfer_messages)
-        .add_attribute("action", "create_raffle")
-        .add_attribute("raffle_id", raffle_id.to_string())
-        .add_attribute("owner", owner.unwrap_or_else(|| info.sender.clone())))
+        .collect())
 }
 
 pub fn _create_raffle(
@@ -190,6 +256,7 @@ pub fn _create_raffle(
     owner: Addr,
     all_assets: Vec<AssetInfo>,
     raffle_ticket_price: AssetInfo,
+    additional_ticket_prices: Vec<AssetInfo>,
     raffle_options: RaffleOptionsMsg,
 ) -> Result<u64, ContractError> {
     let config = CONFIG.load(deps.storage)?;
@@ -217,14 +284,23 @@ pub fn _create_raffle(
             randomness: None,
             winners: vec![],
             is_cancelled: false,
+            is_expired: false,
             raffle_options: RaffleOptions::new(
                 deps.api,
                 env,
-                all_assets.len(),
+                &all_assets,
                 raffle_options,
-                config,
+                config.clone(),
             )?,
             drand_randomness: None,
+            additional_tickets_sold: vec![0; additional_ticket_prices.len()],
+            additional_ticket_prices,
+            ticket_discounts: vec![],
+            referral_rewards: vec![],
+            asset_depositors: vec![],
+            fees: Some(RaffleFees::from(&config)),
+            randomness_deadline: Some(config.randomness_deadline),
+            nois_request_after: None,
         }),
     })?;
     Ok(raffle_id)
@@ -270,6 +346,95 @@ pub fn execute_cancel_raffle(
         .add_attribute("raffle_id", raffle_id.to_string()))
 }
 
+/// Escrows additional prizes on a raffle, before its ticket sales end.
+/// Only the raffle owner and the raffle sponsors can add prizes, up to [`MAX_RAFFLE_ASSETS`] prizes in total.
+/// Raffles with prize tiers can't get new prizes
+pub fn execute_add_prizes(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    raffle_id: u64,
+    assets: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let mut raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let raffle_state = get_raffle_state(&env, &config, &raffle_info);
+
+    if raffle_state != RaffleState::Created && raffle_state != RaffleState::Started {
+        return Err(ContractError::WrongStateForAddPrizes {
+            status: raffle_state,
+        });
+    }
+    let raffle_options = &raffle_info.raffle_options;
+    ensure!(
+        info.sender == raffle_info.owner
+            || raffle_options
+                .sponsors
+                .as_ref()
+                .is_some_and(|sponsors| sponsors.contains(&info.sender)),
+        ContractError::Unauthorized {}
+    );
+    // The prizes of each tier are chosen by the raffle owner
+    ensure!(
+        raffle_options.prize_tiers.is_empty(),
+        ContractError::PrizeTiersLocked {}
+    );
+    // One ticket can't win more than one prize, so the minimum ticket number grows with the prizes.
+    // It can't change anymore once tickets were bought
+    ensure!(
+        !raffle_options.one_winner_per_asset || raffle_info.number_of_tickets == 0,
+        ContractError::RaffleAlreadyStarted {}
+    );
+
+    if assets.is_empty() {
+        return Err(ContractError::NoAssets {});
+    }
+    ensure!(
+        raffle_info.assets.len() + assets.len() <= MAX_RAFFLE_ASSETS as usize,
+        ContractError::TooManyAssets {
+            max: MAX_RAFFLE_ASSETS
+        }
+    );
+    if !all_elements_unique(&[raffle_info.assets.clone(), assets.clone()].concat()) {
+        return Err(ContractError::DuplicateAssets {});
+    }
+
+    let mut sent_coins: Coins = info.funds.try_into()?;
+    let transfer_messages =
+        get_asset_deposit_messages(deps.as_ref(), &env, &info.sender, &assets, &mut sent_coins)?;
+    if !sent_coins.is_empty() {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    // The sponsor is recorded to get the prizes back if nobody wins them
+    let first_prize = raffle_info.assets.len();
+    raffle_info
+        .asset_depositors
+        .resize(first_prize, raffle_info.owner.clone());
+    raffle_info
+        .asset_depositors
+        .extend(vec![info.sender.clone(); assets.len()]);
+    raffle_info.assets.extend(assets);
+
+    let raffle_options = &mut raffle_info.raffle_options;
+    // One ticket can't win more than one prize
+    if raffle_options.one_winner_per_asset {
+        raffle_options.min_ticket_number = Some(
+            raffle_options
+                .min_ticket_number
+                .unwrap_or_default()
+                .max(raffle_options.winner_count(raffle_info.assets.len())),
+        );
+    }
+    RAFFLE_INFO.save(deps.storage, raffle_id, &raffle_info)?;
+
+    Ok(Response::new()
+        .add_messages(transfer_messages)
+        .add_attribute("action", "add_prizes")
+        .add_attribute("raffle_id", raffle_id.to_string())
+        .add_attribute("sponsor", info.sender))
+}
+
 /// Modify the raffle characteristics
 /// A parameter is only modified if it is specified in the called message
 /// If None is provided, nothing changes for the parameter
@@ -280,6 +445,7 @@ pub fn execute_modify_raffle(
     info: MessageInfo,
     raffle_id: u64,
     raffle_ticket_price: Option<AssetInfo>,
+    additional_ticket_prices: Option<Vec<AssetInfo>>,
     raffle_options: RaffleOptionsMsg,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
@@ -310,20 +476,56 @@ pub fn execute_modify_raffle(
     }
 
     // Then modify the raffle characteristics
+    let previous_end = raffle_info.raffle_options.raffle_end();
     raffle_info.raffle_options = RaffleOptions::new_from(
         deps.api,
         raffle_info.raffle_options,
-        raffle_info.assets.len(),
+        &raffle_info.assets,
         raffle_options,
-        config,
+        config.clone(),
     )?;
-    // Then modify the ticket price
+    // No ticket was sold yet, so the raffle takes the current contract fees
+    raffle_info.fees = Some(RaffleFees::from(&config));
+    raffle_info.randomness_deadline = Some(config.randomness_deadline);
+    // Then modify the ticket prices
     if let Some(raffle_ticket_price) = raffle_ticket_price {
         raffle_info.raffle_ticket_price = raffle_ticket_price;
     }
+    if let Some(additional_ticket_prices) = additional_ticket_prices {
+        raffle_info.additional_tickets_sold = vec![0; additional_ticket_prices.len()];
+        raffle_info.additional_ticket_prices = additional_ticket_prices;
+    }
+    if !raffle_info.additional_ticket_prices.is_empty() {
+        check_ticket_prices(deps.api, &raffle_info.ticket_prices())?;
+    }
+
+    // The randomness already requested is only accepted if it's published after the raffle end,
+    // so it's only requested (and paid) again when the raffle ends later than before
+    let mut sent_coins: Coins = info.funds.try_into()?;
+    let requested_after = raffle_info
+        .nois_request_after
+        .unwrap_or_else(|| previous_end.plus_seconds(NOIS_TIMEOUT));
+    let request_after = raffle_info
+        .raffle_options
+        .raffle_end()
+        .plus_seconds(NOIS_TIMEOUT);
+    let msgs = if request_after > requested_after {
+        take_randomness_payment(&config, &mut sent_coins)?;
+        let msgs = request_randomness_msgs(&config, raffle_id, request_after)?;
+        if !msgs.is_empty() {
+            raffle_info.nois_request_after = Some(request_after);
+        }
+        msgs
+    } else {
+        vec![]
+    };
+    if !sent_coins.is_empty() {
+        return Err(ContractError::AssetMismatch {});
+    }
     RAFFLE_INFO.save(deps.storage, raffle_id, &raffle_info)?;
 
     Ok(Response::new()
+        .add_messages(msgs)
         .add_attribute("action", "modify_raffle")
         .add_attribute("raffle_id", raffle_id.to_string()))
 }
@@ -334,9 +536,15 @@ pub fn execute_modify_raffle(
 ///
 /// `assets` : the assets you want to deposit against a raffle ticket.
 /// These assets must be a native coin
-/// These must correspond to the raffle_info.raffle_ticket_price exactly
-/// This function needs the sender to approve token transfer (for CW20 tokens) priori to the transaction
-/// The next function provides a receiver message implementation if you prefer
+/// These must correspond to the raffle_info.raffle_ticket_price or to one of the additional ticket prices exactly
+/// Tickets priced in CW20 tokens are bought by sending the tokens to this contract,
+/// The next function provides the receiver message implementation for that
+///
+/// `allowlist_proof` : proves the ticket owner is part of the raffle merkle allowlist, if any
+///
+/// `referrer` : address credited with a share of the ticket sale, see [`RaffleOptions::referral_share`].
+/// It can be neither the ticket owner nor the sender paying for the tickets
+#[allow(clippy::too_many_arguments)]
 pub fn execute_buy_tickets(
     deps: DepsMut,
     env: Env,
@@ -345,6 +553,8 @@ pub fn execute_buy_tickets(
     ticket_count: u32,
     assets: AssetInfo,
     on_behalf_of: Option<String>,
+    allowlist_proof: Option<AllowlistProof>,
+    referrer: Option<String>,
 ) -> Result<Response, ContractError> {
     // First we physcially transfer the AssetInfo
     let transfer_messages: Vec<cosmwasm_std::CosmosMsg<sg_std::StargazeMsgWrapper>> = match &assets
@@ -386,7 +596,20 @@ pub fn execute_buy_tickets(
         .map(|a| deps.as_ref().api.addr_validate(&a))
         .transpose()?
         .unwrap_or(info.sender.clone());
-    _buy_tickets(deps, env.clone(), owner, raffle_id, ticket_count, assets)?;
+    let referrer = referrer
+        .map(|a| deps.as_ref().api.addr_validate(&a))
+        .transpose()?;
+    _buy_tickets(
+        deps,
+        env.clone(),
+        &info.sender,
+        owner,
+        raffle_id,
+        ticket_count,
+        assets,
+        allowlist_proof,
+        referrer,
+    )?;
 
     Ok(Response::new()
         .add_messages(transfer_messages)
@@ -397,41 +620,133 @@ pub fn execute_buy_tickets(
         .add_attribute("timestamp", env.block.time.to_string()))
 }
 
+/// Buy tickets with CW20 tokens.
+///
+/// The tokens are sent to this contract with a CW20 `Send` message wrapping a [`ReceiveMsg`].
+/// The token contract is the message sender, so the sent assets can't be spoofed.
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sent_assets = AssetInfo::Cw20Coin(Cw20Coin {
+        address: info.sender.to_string(),
+        amount: wrapper.amount,
+    });
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::BuyTicket {
+            raffle_id,
+            ticket_count,
+            on_behalf_of,
+            allowlist_proof,
+            referrer,
+        } => {
+            let owner = on_behalf_of
+                .map(|a| deps.as_ref().api.addr_validate(&a))
+                .transpose()?
+                .unwrap_or(sender.clone());
+            let referrer = referrer
+                .map(|a| deps.as_ref().api.addr_validate(&a))
+                .transpose()?;
+            _buy_tickets(
+                deps,
+                env.clone(),
+                &sender,
+                owner,
+                raffle_id,
+                ticket_count,
+                sent_assets,
+                allowlist_proof,
+                referrer,
+            )?;
+
+            Ok(Response::new()
+                .add_attribute("action", "buy_ticket")
+                .add_attribute("raffle_id", raffle_id.to_string())
+                .add_attribute("purchaser", sender)
+                .add_attribute("ticket_count", ticket_count.to_string())
+                .add_attribute("timestamp", env.block.time.to_string()))
+        }
+    }
+}
+
 /// Creates new raffle tickets and assigns them to the sender
 /// Internal function that doesn't check anything and buys multiple tickets
 /// The arguments are described on the execute_buy_tickets function above.
+/// `payer` is the address paying for the tickets, which can't refer itself either
+#[allow(clippy::too_many_arguments)]
 pub fn _buy_tickets(
     deps: DepsMut,
     env: Env,
+    payer: &Addr,
     owner: Addr,
     raffle_id: u64,
     ticket_count: u32,
     assets: AssetInfo,
+    allowlist_proof: Option<AllowlistProof>,
+    referrer: Option<Addr>,
 ) -> Result<(), ContractError> {
     let mut raffle_info = RAFFLE_INFO.load(deps.storage, raffle_id)?;
 
     let config = CONFIG.load(deps.storage)?;
-    // We first check the sent assets match the raffle assets
-    let tc = ticket_cost(raffle_info.clone(), ticket_count)?;
-    if let AssetInfo::Coin(x) = tc.clone() {
-        if !x.amount.is_zero() && tc != assets {
-            return Err(ContractError::PaymentNotSufficient {
+    // We first check the sent assets match one of the raffle ticket prices
+    let ticket_prices = raffle_info.ticket_prices();
+    let costs = ticket_prices
+        .iter()
+        .map(|ticket_price| {
+            ticket_cost(
+                &raffle_info.raffle_options,
+                ticket_price.clone(),
                 ticket_count,
-                assets_wanted: raffle_info.raffle_ticket_price,
-                // TODO: print correct assets_wanted value
-                assets_received: assets,
-            });
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut price_index = None;
+    for (i, tc) in costs.iter().enumerate() {
+        if ticket_price_amount(tc)?.is_zero() || tc == &assets {
+            price_index = Some(i);
+            break;
         }
     }
+    let Some(price_index) = price_index else {
+        // The wanted assets are in the currency that was sent, when the raffle accepts it
+        let assets_wanted = costs
+            .iter()
+            .find(|tc| same_ticket_currency(tc, &assets))
+            .unwrap_or(&costs[0])
+            .clone();
+        return Err(ContractError::PaymentNotSufficient {
+            ticket_count,
+            assets_wanted,
+            assets_received: assets,
+        });
+    };
+
+    if referrer
+        .as_ref()
+        .is_some_and(|referrer| *referrer == owner || referrer == payer)
+    {
+        return Err(ContractError::SelfReferral {});
+    }
 
     // We start by checking that the buyer has the gating rights to buy this ticket
     buyer_can_buy_ticket(deps.as_ref(), &raffle_info, owner.to_string())?;
+    let allowance = assert_allowlisted(&raffle_info, &owner, allowlist_proof.as_ref())?;
 
     // We then check the raffle is in the right state
     can_buy_ticket(env.clone(), &config, raffle_info.clone())?;
 
     // Then we check the user has the right to buy `ticket_count` more tickets
-    if let Some(max_ticket_per_address) = raffle_info.raffle_options.max_ticket_per_address {
+    // The allowlist allowance applies on top of the raffle limit
+    let max_ticket_per_address =
+        match (raffle_info.raffle_options.max_ticket_per_address, allowance) {
+            (Some(max), Some(allowance)) => Some(max.min(allowance)),
+            (max, allowance) => max.or(allowance),
+        };
+    if let Some(max_ticket_per_address) = max_ticket_per_address {
         let current_ticket_count = USER_TICKETS
             .load(deps.storage, (&owner, raffle_id))
             .unwrap_or(0);
@@ -455,12 +770,15 @@ pub fn _buy_tickets(
         }
     };
 
-    // Then we save the sender to the bought tickets
-    for n in 0..ticket_count {
-        RAFFLE_TICKETS.save(
+    // Then we save the bought tickets as a single range, right after the last ticket sold
+    if ticket_count > 0 {
+        RAFFLE_TICKET_RANGES.save(
             deps.storage,
-            (raffle_id, raffle_info.number_of_tickets + n),
-            &owner,
+            (raffle_id, raffle_info.number_of_tickets),
+            &TicketRange {
+                owner: owner.clone(),
+                count: ticket_count,
+            },
         )?;
     }
 
@@ -470,6 +788,66 @@ pub fn _buy_tickets(
     })?;
     raffle_info.number_of_tickets += ticket_count;
 
+    // Tickets bought with an additional ticket price are counted apart, to pay out and refund each currency
+    if price_index > 0 {
+        USER_ADDITIONAL_TICKETS.update::<_, ContractError>(
+            deps.storage,
+            (&owner, raffle_id, price_index as u32),
+            |x| Ok(x.unwrap_or(0) + ticket_count),
+        )?;
+        raffle_info
+            .additional_tickets_sold
+            .resize(raffle_info.additional_ticket_prices.len(), 0);
+        raffle_info.additional_tickets_sold[price_index - 1] += ticket_count;
+    }
+
+    // The pricing schedule discount is kept as well, to know the funds that were actually paid
+    let discount = ticket_price_amount(&ticket_prices[price_index])? * Uint128::from(ticket_count)
+        - ticket_price_amount(&costs[price_index])?;
+    if !discount.is_zero() {
+        USER_TICKET_DISCOUNTS.update::<_, ContractError>(
+            deps.storage,
+            (&owner, raffle_id, price_index as u32),
+            |x| Ok(x.unwrap_or_default() + discount),
+        )?;
+        raffle_info
+            .ticket_discounts
+            .resize(ticket_prices.len(), Uint128::zero());
+        raffle_info.ticket_discounts[price_index] += discount;
+    }
+
+    // The referrer is owed a share of the funds paid, it's set aside when the raffle is claimed
+    if let Some(referrer) = referrer {
+        let share = raffle_info
+            .raffle_options
+            .referral_share
+            .unwrap_or(raffle_info.fees(&config).referral_fee);
+        let reward = ticket_price_amount(&costs[price_index])? * share;
+        REFERRALS.update::<_, ContractError>(deps.storage, (&referrer, raffle_id), |x| {
+            let mut referral = x.unwrap_or_default();
+            referral.ticket_count += ticket_count;
+            if !reward.is_zero() {
+                match referral
+                    .rewards
+                    .iter_mut()
+                    .find(|r| same_ticket_currency(r, &costs[price_index]))
+                {
+                    Some(AssetInfo::Coin(coin)) => coin.amount += reward,
+                    Some(AssetInfo::Cw20Coin(token)) => token.amount += reward,
+                    Some(_) => return Err(ContractError::WrongAssetType {}),
+                    None => referral
+                        .rewards
+                        .push(ticket_payment(costs[price_index].clone(), reward)?),
+                }
+            }
+            Ok(referral)
+        })?;
+        raffle_info
+            .referral_rewards
+            .resize(ticket_prices.len(), Uint128::zero());
+        raffle_info.referral_rewards[price_index] += reward;
+    }
+
     // If all tickets have been bought, we stop the raffle.
     // The raffle duration is amended to reflect that
     // If not enough were bought before and we passed the threshold, we can send the randomness trigger as well
@@ -502,21 +880,12 @@ pub fn execute_claim(deps: DepsMut, env: Env, raffle_id: u64) -> Result<Response
     // If the minimum number of tickets is not reached, the winner is the raffle owner as well
     let msgs = if raffle_info.number_of_tickets == 0u32 {
         raffle_info.winners = vec![raffle_info.owner.clone()];
-        // No funds re-imbursement
-        get_raffle_winner_messages(deps.as_ref(), env.clone(), raffle_info.clone())?
-    } else if raffle_info.number_of_tickets
-        < raffle_info.raffle_options.min_ticket_number.unwrap_or(0)
-    {
+        // No funds re-imbursement, the prizes go back to their depositors
+        get_raffle_owner_messages(env.clone(), raffle_info.clone())?
+    } else if is_raffle_refunded(&raffle_info) {
         raffle_info.winners = vec![raffle_info.owner.clone()];
-        // No funds re-imbursement
-        let nft_msg = get_raffle_winner_messages(deps.as_ref(), env.clone(), raffle_info.clone())?;
-        let refund_msgs = get_raffle_refund_funds_finished_messages(
-            deps.storage,
-            env.clone(),
-            raffle_info.clone(),
-            raffle_id,
-        )?;
-        [refund_msgs, nft_msg].concat()
+        // Ticket buyers claim their refund themselves with `ClaimRefund`
+        get_raffle_owner_messages(env.clone(), raffle_info.clone())?
     } else {
         // We calculate the winner of the raffle and save it to the contract. The raffle is now claimed !
         raffle_info.winners =
@@ -549,6 +918,127 @@ pub fn execute_claim(deps: DepsMut, env: Env, raffle_id: u64) -> Result<Response
         ))
 }
 
+/// Returns the prizes of a raffle that didn't receive randomness before the deadline to its owner
+/// Anyone can expire a raffle, the ticket buyers then claim their refund with `ClaimRefund`
+pub fn execute_expire_raffle(
+    deps: DepsMut,
+    env: Env,
+    raffle_id: u64,
+) -> Result<Response, ContractError> {
+    let mut raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let raffle_state = get_raffle_state(&env, &config, &raffle_info);
+
+    if raffle_state != RaffleState::Expired {
+        return Err(ContractError::WrongStateForExpire {
+            status: raffle_state,
+        });
+    }
+    if raffle_info.is_expired {
+        return Err(ContractError::RaffleAlreadyExpired {});
+    }
+
+    raffle_info.is_expired = true;
+    RAFFLE_INFO.save(deps.storage, raffle_id, &raffle_info)?;
+
+    let transfer_messages = get_raffle_owner_messages(env, raffle_info)?;
+    Ok(Response::new()
+        .add_messages(transfer_messages)
+        .add_attribute("action", "expire_raffle")
+        .add_attribute("raffle_id", raffle_id.to_string()))
+}
+
+/// Refunds all the tickets the sender bought on a raffle that didn't reach its minimum ticket number
+/// or that expired before receiving randomness
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    raffle_id: u64,
+) -> Result<Response, ContractError> {
+    let mut raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let raffle_state = get_raffle_state(&env, &config, &raffle_info);
+
+    if !is_refund_open(&raffle_state, &raffle_info) {
+        return Err(ContractError::WrongStateForRefund {
+            status: raffle_state,
+        });
+    }
+    // Once refunds are paid, a later config update can't bring the raffle back before its deadline
+    if raffle_state == RaffleState::Expired && raffle_info.randomness_deadline.is_none() {
+        raffle_info.randomness_deadline = Some(config.randomness_deadline);
+        RAFFLE_INFO.save(deps.storage, raffle_id, &raffle_info)?;
+    }
+
+    let ticket_count = USER_TICKETS
+        .may_load(deps.storage, (&info.sender, raffle_id))?
+        .unwrap_or(0);
+    if ticket_count == 0 {
+        return Err(ContractError::NothingToRefund {});
+    }
+    if CLAIMED_REFUNDS.has(deps.storage, (&info.sender, raffle_id)) {
+        return Err(ContractError::RefundAlreadyClaimed {});
+    }
+    CLAIMED_REFUNDS.save(deps.storage, (&info.sender, raffle_id), &true)?;
+
+    let ticket_payments = user_ticket_payments(
+        deps.storage,
+        raffle_id,
+        &raffle_info,
+        &info.sender,
+        ticket_count,
+    )?;
+    let refund_msgs = get_raffle_refund_messages(&info.sender, ticket_payments)?;
+
+    Ok(Response::new()
+        .add_messages(refund_msgs)
+        .add_attribute("action", "claim_refund")
+        .add_attribute("raffle_id", raffle_id.to_string())
+        .add_attribute("receiver", info.sender)
+        .add_attribute("ticket_count", ticket_count.to_string()))
+}
+
+/// Pays a referrer the rewards they earned on a raffle, once its ticket sales were paid out
+pub fn execute_claim_referral_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    raffle_id: u64,
+) -> Result<Response, ContractError> {
+    let raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let raffle_state = get_raffle_state(&env, &config, &raffle_info);
+
+    if raffle_state != RaffleState::Claimed {
+        return Err(ContractError::WrongStateForClaim {
+            status: raffle_state,
+        });
+    }
+
+    // Refunded tickets don't earn any reward
+    let mut referral = REFERRALS
+        .may_load(deps.storage, (&info.sender, raffle_id))?
+        .unwrap_or_default();
+    if referral.rewards.is_empty() || is_raffle_refunded(&raffle_info) {
+        return Err(ContractError::NoReferralRewards {});
+    }
+    if referral.claimed {
+        return Err(ContractError::ReferralRewardsAlreadyClaimed {});
+    }
+    referral.claimed = true;
+    REFERRALS.save(deps.storage, (&info.sender, raffle_id), &referral)?;
+
+    let reward_msgs = get_payment_messages(&info.sender, referral.rewards)?;
+
+    Ok(Response::new()
+        .add_messages(reward_msgs)
+        .add_attribute("action", "claim_referral_rewards")
+        .add_attribute("raffle_id", raffle_id.to_string())
+        .add_attribute("receiver", info.sender)
+        .add_attribute("ticket_count", referral.ticket_count.to_string()))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
@@ -560,7 +1050,11 @@ pub fn execute_update_config(
     minimum_raffle_duration: Option<u64>,
     max_tickets_per_raffle: Option<u32>,
     raffle_fee: Option<Decimal>,
+    randomness_provider_fee: Option<Decimal>,
     drand_config: Option<DrandConfig>,
+    randomness_source: Option<RandomnessSource>,
+    randomness_deadline: Option<u64>,
+    referral_fee: Option<Decimal>,
     creation_coins: Option<Vec<Coin>>,
     fee_discounts: Option<Vec<FeeDiscountMsg>>,
 ) -> Result<Response, ContractError> {
@@ -599,6 +1093,11 @@ pub fn execute_update_config(
         }
         None => config.raffle_fee,
     };
+    let randomness_provider_fee = randomness_provider_fee.unwrap_or(config.randomness_provider_fee);
+    let referral_fee = referral_fee.unwrap_or(config.referral_fee);
+    // The fees can't take more than the ticket sales.
+    // They only apply to the raffles created after this update
+    check_fee_total(&[raffle_fee, randomness_provider_fee, referral_fee])?;
 
     let drand_config = match drand_config {
         Some(config) => {
@@ -608,6 +1107,18 @@ pub fn execute_update_config(
         None => config.drand_config,
     };
 
+    let randomness_source = match randomness_source {
+        Some(source) => source.check(deps.api)?,
+        None => config.randomness_source,
+    };
+
+    let randomness_deadline = randomness_deadline.unwrap_or(config.randomness_deadline);
+    // Raffles can't expire before the randomness can be provided
+    ensure!(
+        randomness_deadline >= drand_config.timeout,
+        ContractError::InvalidRandomnessDeadline {}
+    );
+
     // verifies all provided coins are greater than 0
     let creation_coins = match creation_coins {
         Some(mut crc) => {
@@ -641,12 +1152,16 @@ pub fn execute_update_config(
         fee_addr,
         minimum_raffle_duration,
         raffle_fee,
+        randomness_provider_fee,
         locks: config.locks,
         creation_coins,
         max_tickets_per_raffle: max_tickets_per_raffle.into(),
         last_raffle_id: config.last_raffle_id,
         fee_discounts,
         drand_config,
+        randomness_source,
+        randomness_deadline,
+        referral_fee,
     };
 
     CONFIG.save(deps.storage, &new_config)?;
@@ -654,6 +1169,23 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+/// Anyone can convert the tickets left in the legacy storage after a migration
+pub fn execute_migrate_tickets(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit
+        .unwrap_or(TICKET_MIGRATION_BATCH)
+        .min(TICKET_MIGRATION_BATCH);
+    let migrated = migrate_ticket_ranges(deps.storage, limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate_tickets")
+        .add_attribute("migrated", migrated.to_string()))
+}
+
 /// Locking the contract (lock=true) means preventing the creation of new raffles
 /// Tickets can still be bought and NFTs retrieved when a contract is locked
 pub fn execute_toggle_lock(
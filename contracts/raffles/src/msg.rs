@@ -1,10 +1,17 @@
 use crate::{
     error::ContractError,
-    state::{FeeDiscount, FeeDiscountMsg, RaffleInfo, RaffleOptionsMsg, RaffleState},
+    state::{
+        check_fee_total, FeeDiscount, FeeDiscountMsg, RaffleInfo, RaffleOptionsMsg, RaffleState,
+        RandomnessSource, DEFAULT_RANDOMNESS_DEADLINE,
+    },
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Coin, Decimal, Deps, HexBinary, StdError, StdResult};
-use randomness::DrandRandomness;
+use cosmwasm_std::{
+    ensure, Addr, Binary, Coin, Decimal, Deps, HexBinary, StdError, StdResult, Timestamp,
+};
+use cw20::Cw20ReceiveMsg;
+use nois::NoisCallback;
+use randomness::{DrandRandomness, DrandScheme};
 use utils::state::{is_valid_name, AssetInfo, Locks};
 
 #[cw_serde]
@@ -21,12 +28,20 @@ pub struct InstantiateMsg {
     pub max_ticket_number: Option<u32>,
     // % fee of raffle ticket sales to fee_addr
     pub raffle_fee: Decimal,
+    // % fee of raffle ticket sales to the randomness provider
+    pub randomness_provider_fee: Option<Decimal>,
 
     pub creation_coins: Option<Vec<Coin>>,
 
     pub fee_discounts: Vec<FeeDiscountMsg>,
 
     pub drand_config: DrandConfig,
+    // Where the raffle randomness comes from, drand by default
+    pub randomness_source: Option<RandomnessSource>,
+    // Time after a raffle ends, in seconds, before it expires without randomness
+    pub randomness_deadline: Option<u64>,
+    // % of the ticket sales brought by a referrer that goes to them
+    pub referral_fee: Option<Decimal>,
 }
 
 impl InstantiateMsg {
@@ -39,12 +54,23 @@ impl InstantiateMsg {
         }
 
         // Check the fee distribution
-        if self.raffle_fee >= Decimal::one() {
-            return Err(ContractError::InvalidFeeRate {});
-        }
+        check_fee_total(&[
+            self.raffle_fee,
+            self.randomness_provider_fee.unwrap_or_default(),
+            self.referral_fee.unwrap_or_default(),
+        ])?;
 
         self.drand_config.validate(deps)?;
 
+        // Raffles can't expire before the randomness can be provided
+        if self
+            .randomness_deadline
+            .unwrap_or(DEFAULT_RANDOMNESS_DEADLINE)
+            < self.drand_config.timeout
+        {
+            return Err(ContractError::InvalidRandomnessDeadline {});
+        }
+
         Ok(())
     }
 }
@@ -56,16 +82,50 @@ pub struct DrandConfig {
     pub drand_url: String,
     /// The contract that can verify the entropy signature
     pub verify_signature_contract: Addr,
+    /// Signature scheme of the drand network
+    #[serde(default)]
+    pub scheme: DrandScheme,
     /// Duration of the randomness providing round
     pub timeout: u64,
+    /// Publication time of the first round of the drand chain
+    #[serde(default = "default_drand_genesis_time")]
+    pub genesis_time: Timestamp,
+    /// Time between 2 rounds of the drand chain, in seconds
+    #[serde(default = "default_drand_period")]
+    pub period: u64,
+}
+
+/// Configs saved before rounds were bound to time were all using the drand mainnet chain
+fn default_drand_genesis_time() -> Timestamp {
+    Timestamp::from_seconds(1595431050)
+}
+
+fn default_drand_period() -> u64 {
+    30
 }
 
 impl DrandConfig {
     pub fn validate(&self, deps: Deps) -> StdResult<()> {
         deps.api
             .addr_validate(self.verify_signature_contract.as_ref())?;
+        ensure!(
+            self.period != 0,
+            StdError::generic_err("The drand period can't be 0")
+        );
         Ok(())
     }
+
+    /// Time at which a drand round is published
+    pub fn round_time(&self, round: u64) -> Timestamp {
+        self.genesis_time
+            .plus_seconds(round.saturating_sub(1).saturating_mul(self.period))
+    }
+
+    /// First drand round published at or after `time`
+    pub fn earliest_round_after(&self, time: Timestamp) -> u64 {
+        let elapsed = time.seconds().saturating_sub(self.genesis_time.seconds());
+        elapsed.div_ceil(self.period) + 1
+    }
 }
 
 #[cw_serde]
@@ -77,6 +137,9 @@ pub enum ExecuteMsg {
         assets: Vec<AssetInfo>,
         raffle_options: RaffleOptionsMsg,
         raffle_ticket_price: AssetInfo,
+        /// Other accepted costs per ticket, each in a different currency
+        #[serde(default)]
+        additional_ticket_prices: Vec<AssetInfo>,
     },
     CancelRaffle {
         raffle_id: u64,
@@ -84,6 +147,30 @@ pub enum ExecuteMsg {
     ClaimRaffle {
         raffle_id: u64,
     },
+    /// Escrows additional prizes on a raffle that didn't end yet, sent by its owner or one of its sponsors.
+    /// Each prize goes back to its depositor if the raffle is cancelled or has no winner
+    #[cw_orch(payable)]
+    AddPrizes {
+        raffle_id: u64,
+        assets: Vec<AssetInfo>,
+    },
+    /// Returns the prizes of a raffle that didn't receive randomness before the deadline to its owner.
+    /// Ticket buyers can then claim their refund with `ClaimRefund`
+    ExpireRaffle {
+        raffle_id: u64,
+    },
+    /// Refunds the sender's tickets when the raffle didn't reach its minimum ticket number
+    ClaimRefund {
+        raffle_id: u64,
+    },
+    /// Pays the sender's referral rewards of a claimed raffle
+    ClaimReferralRewards {
+        raffle_id: u64,
+    },
+    /// Converts up to `limit` tickets left in the legacy storage to ticket ranges
+    MigrateTickets {
+        limit: Option<u32>,
+    },
     UpdateConfig {
         name: Option<String>,
         owner: Option<String>,
@@ -91,13 +178,22 @@ pub enum ExecuteMsg {
         minimum_raffle_duration: Option<u64>,
         max_tickets_per_raffle: Option<u32>,
         raffle_fee: Option<Decimal>,
+        randomness_provider_fee: Option<Decimal>,
         creation_coins: Option<Vec<Coin>>,
         fee_discounts: Option<Vec<FeeDiscountMsg>>,
         drand_config: Option<DrandConfig>,
+        randomness_source: Option<RandomnessSource>,
+        randomness_deadline: Option<u64>,
+        referral_fee: Option<Decimal>,
     },
+    /// Raffles decided with Nois randomness pay for a new randomness request when they end later than before
+    #[cw_orch(payable)]
     ModifyRaffle {
         raffle_id: u64,
         raffle_ticket_price: Option<AssetInfo>,
+        /// Replaces the other accepted costs per ticket when specified
+        #[serde(default)]
+        additional_ticket_prices: Option<Vec<AssetInfo>>,
         raffle_options: RaffleOptionsMsg,
     },
     #[cw_orch(payable)]
@@ -106,13 +202,25 @@ pub enum ExecuteMsg {
         ticket_count: u32,
         sent_assets: AssetInfo,
         on_behalf_of: Option<String>,
+        /// Required when the raffle has a merkle allowlist
+        #[serde(default)]
+        allowlist_proof: Option<AllowlistProof>,
+        /// Address that brought the buyer, it gets a share of the ticket sale
+        #[serde(default)]
+        referrer: Option<String>,
     },
+    /// Buy tickets with CW20 tokens, the raffle ticket price must be a `Cw20Coin`
+    Receive(Cw20ReceiveMsg),
     /// Provide job_id for randomness contract
     /// Provide randomness from drand
     UpdateRandomness {
         raffle_id: u64,
         randomness: DrandRandomness,
     },
+    /// Randomness callback from the Nois proxy, when the contract uses the Nois randomness source
+    NoisReceive {
+        callback: NoisCallback,
+    },
 
     // Admin messages
     ToggleLock {
@@ -120,6 +228,20 @@ pub enum ExecuteMsg {
     },
 }
 
+/// Messages that can be embedded in a CW20 `Send` to the raffle contract
+#[cw_serde]
+pub enum ReceiveMsg {
+    BuyTicket {
+        raffle_id: u64,
+        ticket_count: u32,
+        on_behalf_of: Option<String>,
+        #[serde(default)]
+        allowlist_proof: Option<AllowlistProof>,
+        #[serde(default)]
+        referrer: Option<String>,
+    },
+}
+
 #[cw_serde]
 #[derive(QueryResponses, cw_orch::QueryFns)]
 pub enum QueryMsg {
@@ -143,6 +265,38 @@ pub enum QueryMsg {
     },
     #[returns(u32)]
     TicketCount { owner: String, raffle_id: u64 },
+    #[returns(RefundResponse)]
+    Refund { user: String, raffle_id: u64 },
+    /// Tickets and rewards a referrer brought to each raffle
+    #[returns(ReferralsResponse)]
+    Referrals {
+        referrer: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Price of tickets bought together, with the raffle pricing schedule applied
+    #[returns(TicketCostResponse)]
+    TicketCost { raffle_id: u64, ticket_count: u32 },
+    /// First drand round that can be used to decide the raffle
+    #[returns(u64)]
+    EarliestRandomnessRound { raffle_id: u64 },
+    #[returns(MerkleRootResponse)]
+    MerkleRoot { raffle_id: u64 },
+    #[returns(IsAllowlistedResponse)]
+    IsAllowlisted {
+        raffle_id: u64,
+        user: String,
+        proof: AllowlistProof,
+    },
+}
+
+/// Proves a ticket owner is part of a raffle merkle allowlist
+#[cw_serde]
+pub struct AllowlistProof {
+    /// Sibling hashes from the leaf to the root
+    pub proof: Vec<HexBinary>,
+    /// Maximum number of tickets the address can buy, when it's part of its leaf
+    pub allowance: Option<u32>,
 }
 
 #[cw_serde]
@@ -163,10 +317,14 @@ pub struct ConfigResponse {
     pub minimum_raffle_duration: u64, // The minimum interval in which users can buy raffle tickets
     pub max_tickets_per_raffle: Option<u32>,
     pub raffle_fee: Decimal, // The percentage of the resulting ticket-tokens that will go to the treasury
-    pub locks: Locks,        // Wether the contract can accept new raffles
+    pub randomness_provider_fee: Decimal, // The percentage of the resulting ticket-tokens that will go to the randomness provider
+    pub locks: Locks,                     // Wether the contract can accept new raffles
     pub creation_coins: Vec<Coin>,
     pub fee_discounts: Vec<FeeDiscount>,
     pub drand_config: DrandConfig,
+    pub randomness_source: RandomnessSource,
+    pub randomness_deadline: u64,
+    pub referral_fee: Decimal, // The percentage of the referred ticket sales that will go to the referrer
 }
 
 #[cw_serde]
@@ -180,6 +338,51 @@ pub struct RaffleResponse {
     pub raffle_id: u64,
     pub raffle_state: RaffleState,
     pub raffle_info: Option<RaffleInfo>,
+    /// Prizes and winners of each tier, when the raffle prizes are tiered
+    #[serde(default)]
+    pub prize_tiers: Vec<PrizeTierResponse>,
+}
+
+#[cw_serde]
+pub struct PrizeTierResponse {
+    pub assets: Vec<AssetInfo>,
+    /// The winner of each asset of the tier, or the winners sharing its single prize.
+    /// Empty until the winners are drawn
+    pub winners: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct RefundResponse {
+    pub ticket_count: u32,
+    /// Funds the user can get back, `None` if the raffle tickets are not refunded
+    /// or if the user only paid with the additional ticket prices
+    pub refund: Option<AssetInfo>,
+    /// Funds the user can get back in the raffle additional ticket currencies
+    #[serde(default)]
+    pub additional_refunds: Vec<AssetInfo>,
+    pub claimed: bool,
+}
+
+#[cw_serde]
+pub struct ReferralsResponse {
+    pub referrals: Vec<ReferralResponse>,
+}
+
+#[cw_serde]
+pub struct ReferralResponse {
+    pub raffle_id: u64,
+    pub ticket_count: u32,
+    pub rewards: Vec<AssetInfo>,
+    pub claimed: bool,
+}
+
+#[cw_serde]
+pub struct TicketCostResponse {
+    pub ticket_count: u32,
+    /// Cost in the main ticket currency
+    pub cost: AssetInfo,
+    /// Cost in each of the raffle additional ticket currencies
+    pub additional_costs: Vec<AssetInfo>,
 }
 
 #[cw_serde]
@@ -198,6 +401,12 @@ pub struct MerkleRootResponse {
     pub merkle_root: HexBinary,
 }
 
+#[cw_serde]
+pub struct IsAllowlistedResponse {
+    pub is_allowlisted: bool,
+    pub allowance: Option<u32>,
+}
+
 #[cw_serde]
 pub struct IsClaimedResponse {
     pub is_claimed: bool,
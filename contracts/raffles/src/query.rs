@@ -1,6 +1,8 @@
 use cosmwasm_std::{
-    to_json_binary, Addr, Decimal, Deps, Env, Order, QueryRequest, StdError, StdResult, WasmQuery,
+    to_json_binary, Addr, Decimal, Deps, Env, Order, QueryRequest, StdError, StdResult, Uint128,
+    WasmQuery,
 };
+use cw1155::{BalanceResponse, Cw1155QueryMsg, IsApprovedForAllResponse};
 use cw721::{Cw721QueryMsg, OwnerOfResponse};
 use cw_storage_plus::Bound;
 
@@ -11,12 +13,21 @@ mod filters;
 
 use crate::{
     error::ContractError,
-    msg::{AllRafflesResponse, ConfigResponse, FeeDiscountResponse, QueryFilters, RaffleResponse},
+    msg::{
+        AllRafflesResponse, AllowlistProof, ConfigResponse, FeeDiscountResponse,
+        IsAllowlistedResponse, MerkleRootResponse, PrizeTierResponse, QueryFilters, RaffleResponse,
+        ReferralResponse, ReferralsResponse, RefundResponse, TicketCostResponse,
+    },
+    randomness::earliest_randomness_round,
     state::{
-        get_raffle_state, load_raffle, RaffleInfo, RaffleState, CONFIG, RAFFLE_INFO,
-        RAFFLE_TICKETS, USER_TICKETS,
+        get_raffle_state, has_legacy_tickets, load_raffle, load_ticket_owner, RaffleInfo,
+        RaffleState, CLAIMED_REFUNDS, CONFIG, RAFFLE_INFO, RAFFLE_TICKET_RANGES, REFERRALS,
+        USER_TICKETS,
+    },
+    utils::{
+        assert_allowlisted, get_raffle_winners, is_raffle_refunded, ticket_cost,
+        user_ticket_payments,
     },
-    utils::get_raffle_winners,
 };
 
 use self::filters::{contains_token_filter, has_gated_rights_filter, owner_filter, state_filter};
@@ -35,11 +46,15 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         last_raffle_id: config.last_raffle_id.unwrap_or(0),
         minimum_raffle_duration: config.minimum_raffle_duration,
         raffle_fee: config.raffle_fee,
+        randomness_provider_fee: config.randomness_provider_fee,
         locks: config.locks,
         creation_coins: config.creation_coins,
         max_tickets_per_raffle: config.max_tickets_per_raffle,
         fee_discounts: config.fee_discounts,
         drand_config: config.drand_config,
+        randomness_source: config.randomness_source,
+        randomness_deadline: config.randomness_deadline,
+        referral_fee: config.referral_fee,
     })
 }
 
@@ -137,6 +152,7 @@ pub fn query_all_raffles_by_depositor(
                     raffle_id,
                     raffle_state: RaffleState::Claimed,
                     raffle_info: None,
+                    prize_tiers: vec![],
                 }]
             }
         }
@@ -159,13 +175,14 @@ fn parse_raffles(
             Ok(RaffleResponse {
                 raffle_id,
                 raffle_state,
+                prize_tiers: query_prize_tiers(&raffle),
                 raffle_info: Some(raffle),
             })
         })
 }
 
 /// Query all ticket onwers within a raffle
-///
+/// Ticket ranges are expanded, so one owner is returned per ticket
 pub fn query_all_tickets(
     deps: Deps,
     _env: Env,
@@ -174,14 +191,50 @@ pub fn query_all_tickets(
     limit: Option<u32>,
 ) -> StdResult<Vec<String>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(Bound::exclusive);
+    let first_ticket = start_after.map(|index| index + 1).unwrap_or(0);
+
+    // Tickets of raffles that are still being migrated are loaded one by one
+    if has_legacy_tickets(deps.storage, raffle_id) {
+        let raffle_info = RAFFLE_INFO.load(deps.storage, raffle_id)?;
+        return (first_ticket..raffle_info.number_of_tickets)
+            .take(limit)
+            .map(|index| {
+                load_ticket_owner(deps.storage, raffle_id, index).map(|owner| owner.to_string())
+            })
+            .collect();
+    }
 
-    RAFFLE_TICKETS
+    // We start from the range containing the first ticket
+    let first_range = RAFFLE_TICKET_RANGES
         .prefix(raffle_id)
-        .range(deps.storage, start.clone(), None, Order::Ascending)
-        .map(|kv_item| Ok(kv_item?.1.to_string()))
-        .take(limit)
-        .collect()
+        .keys(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(first_ticket)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?
+        .unwrap_or(first_ticket);
+
+    let mut tickets = Vec::with_capacity(limit);
+    for item in RAFFLE_TICKET_RANGES.prefix(raffle_id).range(
+        deps.storage,
+        Some(Bound::inclusive(first_range)),
+        None,
+        Order::Ascending,
+    ) {
+        if tickets.len() >= limit {
+            break;
+        }
+        let (start, range) = item?;
+        tickets.extend(
+            (start.max(first_ticket)..start + range.count)
+                .take(limit - tickets.len())
+                .map(|_| range.owner.to_string()),
+        );
+    }
+    Ok(tickets)
 }
 
 pub fn query_all_raffles_raw(
@@ -217,6 +270,7 @@ pub fn query_all_raffles_raw(
                     raffle_id,
                     raffle_state: RaffleState::Claimed,
                     raffle_info: None,
+                    prize_tiers: vec![],
                 }]
             }
         }
@@ -288,6 +342,53 @@ pub fn is_sg721_owner(
     Ok(())
 }
 
+pub fn is_cw1155_owner(
+    deps: Deps,
+    sender: Addr,
+    address: String,
+    token_id: String,
+    value: Uint128,
+) -> Result<(), StdError> {
+    let balance_response: BalanceResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: address,
+            msg: to_json_binary(&Cw1155QueryMsg::Balance {
+                owner: sender.to_string(),
+                token_id,
+            })?,
+        }))?;
+
+    if balance_response.balance < value {
+        return Err(StdError::generic_err(
+            "message sender is not owner of tokens being raffled",
+        ));
+    }
+    Ok(())
+}
+
+pub fn is_approved_cw1155(
+    deps: Deps,
+    env: &Env,
+    sender: Addr,
+    address: String,
+) -> Result<(), StdError> {
+    let approval_response: IsApprovedForAllResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: address,
+            msg: to_json_binary(&Cw1155QueryMsg::IsApprovedForAll {
+                owner: sender.to_string(),
+                operator: env.contract.address.to_string(),
+            })?,
+        }))?;
+
+    if !approval_response.approved {
+        return Err(StdError::generic_err(
+            "the raffle contract is not approved to transfer the tokens being raffled",
+        ));
+    }
+    Ok(())
+}
+
 /// Query the number of tickets a ticket_depositor bought in a specific raffle, designated by a raffle_id
 pub fn query_ticket_count(
     deps: Deps,
@@ -301,13 +402,174 @@ pub fn query_ticket_count(
     )
 }
 
+/// Query the funds a ticket depositor can get back from a raffle that didn't reach its minimum ticket number
+/// or that expired before receiving randomness
+pub fn query_refund(
+    deps: Deps,
+    env: Env,
+    raffle_id: u64,
+    user: String,
+) -> Result<RefundResponse, ContractError> {
+    let user = deps.api.addr_validate(&user)?;
+    let raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let ticket_count = USER_TICKETS
+        .may_load(deps.storage, (&user, raffle_id))?
+        .unwrap_or(0);
+
+    let config = CONFIG.load(deps.storage)?;
+    let raffle_state = get_raffle_state(&env, &config, &raffle_info);
+
+    let (refund, additional_refunds) = if (is_raffle_refunded(&raffle_info)
+        || raffle_state == RaffleState::Expired)
+        && ticket_count != 0
+    {
+        // One refund per ticket price, the main ticket price comes first
+        let mut refunds =
+            user_ticket_payments(deps.storage, raffle_id, &raffle_info, &user, ticket_count)?
+                .into_iter()
+                .map(|(count, paid)| (count != 0).then_some(paid));
+        (refunds.next().flatten(), refunds.flatten().collect())
+    } else {
+        (None, vec![])
+    };
+
+    Ok(RefundResponse {
+        ticket_count,
+        refund,
+        additional_refunds,
+        claimed: CLAIMED_REFUNDS.has(deps.storage, (&user, raffle_id)),
+    })
+}
+
+/// Query the tickets and rewards a referrer brought to each raffle, ordered by raffle id
+pub fn query_referrals(
+    deps: Deps,
+    referrer: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ReferralsResponse> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let referrals = REFERRALS
+        .prefix(&referrer)
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (raffle_id, referral) = item?;
+            Ok(ReferralResponse {
+                raffle_id,
+                ticket_count: referral.ticket_count,
+                rewards: referral.rewards,
+                claimed: referral.claimed,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ReferralsResponse { referrals })
+}
+
+/// Query the price of `ticket_count` tickets bought together, in every currency accepted by the raffle
+pub fn query_ticket_cost(
+    deps: Deps,
+    raffle_id: u64,
+    ticket_count: u32,
+) -> Result<TicketCostResponse, ContractError> {
+    let raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let mut costs = raffle_info
+        .ticket_prices()
+        .into_iter()
+        .map(|ticket_price| ticket_cost(&raffle_info.raffle_options, ticket_price, ticket_count))
+        .collect::<Result<Vec<_>, _>>()?;
+    let additional_costs = costs.split_off(1);
+
+    Ok(TicketCostResponse {
+        ticket_count,
+        cost: costs.remove(0),
+        additional_costs,
+    })
+}
+
+/// Query the first drand round that can decide a raffle
+pub fn query_earliest_randomness_round(deps: Deps, raffle_id: u64) -> StdResult<u64> {
+    let config = CONFIG.load(deps.storage)?;
+    let raffle_info = load_raffle(deps.storage, raffle_id)?;
+    Ok(earliest_randomness_round(&config, &raffle_info))
+}
+
+/// Query the merkle root of the raffle allowlist
+pub fn query_merkle_root(deps: Deps, raffle_id: u64) -> Result<MerkleRootResponse, ContractError> {
+    let merkle_root = load_raffle(deps.storage, raffle_id)?
+        .raffle_options
+        .merkle_root
+        .ok_or_else(|| StdError::not_found("merkle root"))?;
+    Ok(MerkleRootResponse { merkle_root })
+}
+
+/// Query whether a user can buy tickets on a raffle with the provided merkle proof
+pub fn query_is_allowlisted(
+    deps: Deps,
+    raffle_id: u64,
+    user: String,
+    proof: AllowlistProof,
+) -> Result<IsAllowlistedResponse, ContractError> {
+    let user = deps.api.addr_validate(&user)?;
+    let raffle_info = load_raffle(deps.storage, raffle_id)?;
+
+    Ok(
+        match assert_allowlisted(&raffle_info, &user, Some(&proof)) {
+            Ok(allowance) => IsAllowlistedResponse {
+                is_allowlisted: true,
+                allowance,
+            },
+            Err(_) => IsAllowlistedResponse {
+                is_allowlisted: false,
+                allowance: None,
+            },
+        },
+    )
+}
+
+/// Maps the prizes of each tier to their winners.
+/// Winners are drawn tier by tier, so each tier takes the next `winners` of the raffle winners
+pub fn query_prize_tiers(raffle_info: &RaffleInfo) -> Vec<PrizeTierResponse> {
+    let mut drawn = raffle_info.winners.iter();
+    raffle_info
+        .raffle_options
+        .prize_tiers
+        .iter()
+        .map(|tier| PrizeTierResponse {
+            assets: tier
+                .assets
+                .iter()
+                .map(|&i| raffle_info.assets[i as usize].clone())
+                .collect(),
+            winners: match raffle_info.winners.as_slice() {
+                [] => vec![],
+                // When nobody wins the raffle, the owner gets all the prizes back
+                [single_winner] => vec![single_winner.clone(); tier.winners as usize],
+                _ => drawn
+                    .by_ref()
+                    .take(tier.winners as usize)
+                    .cloned()
+                    .collect(),
+            },
+        })
+        .collect()
+}
+
 pub fn add_raffle_winners(
     deps: Deps,
     env: &Env,
     raffle_id: u64,
     raffle_info: &mut RaffleInfo,
 ) -> Result<(), ContractError> {
-    if raffle_info.drand_randomness.is_some() {
+    if raffle_info.randomness_seed().is_some() {
         if raffle_info.number_of_tickets == 0u32
             || raffle_info.number_of_tickets
                 < raffle_info.raffle_options.min_ticket_number.unwrap_or(0)
@@ -32,6 +32,8 @@ pub fn contains_token_filter(raffle_info: &RaffleInfo, filters: &QueryFilters) -
             AssetInfo::Coin(x) => x.denom == token.as_ref(),
             AssetInfo::Cw721Coin(x) => x.address == token.as_ref(),
             AssetInfo::Sg721Token(x) => x.address == token.as_ref(),
+            AssetInfo::Cw20Coin(x) => x.address == token.as_ref(),
+            AssetInfo::Cw1155Coin(x) => x.address == token.as_ref(),
         }),
         None => true,
     }
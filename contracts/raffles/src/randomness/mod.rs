@@ -1,11 +1,54 @@
+pub mod nois;
+
 use crate::contract::VERIFY_RANDOMNESS_REPLY_ID;
 use crate::error::ContractError;
-use crate::state::{get_raffle_state, load_raffle, RaffleState, CONFIG, RAFFLE_INFO};
+use crate::state::{
+    get_raffle_state, load_raffle, Config, RaffleInfo, RaffleState, RandomnessSource, CONFIG,
+    RAFFLE_INFO,
+};
 use cosmwasm_std::{
-    wasm_execute, Addr, Binary, Deps, DepsMut, Env, Event, MessageInfo, SubMsg, SubMsgResult,
+    wasm_execute, Addr, Binary, Coins, Deps, DepsMut, Env, Event, MessageInfo, StdResult, SubMsg,
+    SubMsgResult, Timestamp,
 };
 use randomness::{DrandRandomness, Randomness, VerifierExecuteMsg};
 use sg_std::Response;
+use utils::types::CosmosMsg;
+
+pub use self::nois::execute_nois_receive;
+
+/// Messages needed for the raffle to receive randomness published after `after`.
+/// drand randomness is pushed by anyone with `UpdateRandomness`, so nothing is requested.
+/// Nois randomness is requested to the proxy, which calls back `NoisReceive`
+pub fn request_randomness_msgs(
+    config: &Config,
+    raffle_id: u64,
+    after: Timestamp,
+) -> StdResult<Vec<CosmosMsg>> {
+    match &config.randomness_source {
+        RandomnessSource::Drand => Ok(vec![]),
+        RandomnessSource::Nois(proxy) => Ok(vec![nois::nois_randomness_request(
+            proxy, raffle_id, after,
+        )?]),
+    }
+}
+
+/// Randomness sources that need a request (Nois) are paid by the raffle owner.
+/// The request price is taken out of the funds they sent
+pub fn take_randomness_payment(
+    config: &Config,
+    sent_coins: &mut Coins,
+) -> Result<(), ContractError> {
+    if let RandomnessSource::Nois(proxy) = &config.randomness_source {
+        if !proxy.price.amount.is_zero() {
+            sent_coins.sub(proxy.price.clone()).map_err(|_| {
+                ContractError::RandomnessPaymentMissing {
+                    price: proxy.price.clone(),
+                }
+            })?;
+        }
+    }
+    Ok(())
+}
 
 /// Update the randomness assigned to a raffle
 /// The function receives and checks the randomness against the drand public_key registered with the account.
@@ -19,6 +62,9 @@ pub fn execute_update_randomness(
     randomness: DrandRandomness,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
+    if config.randomness_source != RandomnessSource::Drand {
+        return Err(ContractError::WrongRandomnessSource {});
+    }
     // We check the raffle can receive randomness (good state)
     let raffle_info = load_raffle(deps.storage, raffle_id)?;
     let raffle_state = get_raffle_state(&env, &config, &raffle_info);
@@ -46,6 +92,15 @@ pub fn assert_randomness_origin_and_order(
     let raffle_info = load_raffle(deps.storage, raffle_id)?;
     let contract_info = CONFIG.load(deps.storage)?;
 
+    // The randomness must not be known before the raffle closes
+    let earliest_round = earliest_randomness_round(&contract_info, &raffle_info);
+    if randomness.round < earliest_round {
+        return Err(ContractError::RandomnessTooEarly {
+            round: randomness.round,
+            earliest_round,
+        });
+    }
+
     if let Some(local_randomness) = raffle_info.drand_randomness {
         if randomness.round <= local_randomness.randomness_round {
             return Err(ContractError::RandomnessNotAccepted {
@@ -57,6 +112,7 @@ pub fn assert_randomness_origin_and_order(
     let msg = VerifierExecuteMsg::Verify {
         randomness,
         pubkey: contract_info.drand_config.random_pubkey,
+        scheme: contract_info.drand_config.scheme,
         raffle_id,
         owner: owner.to_string(),
     };
@@ -70,6 +126,13 @@ pub fn assert_randomness_origin_and_order(
     Ok(Response::new().add_submessage(msg))
 }
 
+/// First drand round published after the end of the raffle ticket sales
+pub fn earliest_randomness_round(config: &Config, raffle_info: &RaffleInfo) -> u64 {
+    config
+        .drand_config
+        .earliest_round_after(raffle_info.raffle_options.raffle_end())
+}
+
 /// This function is called after the randomness verifier has verified the current randomness
 /// We used this architecture to make sure the verification passes (because a query may return early)
 /// We verify the randomness provided matches the current state of the contract (good round, good raffle_id...)
@@ -0,0 +1,89 @@
+use cosmwasm_std::{ensure_eq, DepsMut, Env, MessageInfo, StdResult, Timestamp};
+use nois::{NoisCallback, ProxyExecuteMsg};
+use utils::{
+    state::into_cosmos_msg,
+    types::{CosmosMsg, Response},
+};
+
+use crate::{
+    error::ContractError,
+    state::{
+        get_raffle_state, load_raffle, NoisProxy, RaffleState, RandomnessSource, CONFIG,
+        RAFFLE_INFO,
+    },
+};
+
+const JOB_ID_PREFIX: &str = "raffle-";
+
+/// Asks the Nois proxy for a randomness published after `after`.
+/// The proxy price was paid to the contract by the raffle owner, see [`super::take_randomness_payment`]
+pub fn nois_randomness_request(
+    proxy: &NoisProxy,
+    raffle_id: u64,
+    after: Timestamp,
+) -> StdResult<CosmosMsg> {
+    into_cosmos_msg(
+        ProxyExecuteMsg::GetRandomnessAfter {
+            after,
+            job_id: format!("{JOB_ID_PREFIX}{raffle_id}"),
+        },
+        proxy.address.clone(),
+        Some(vec![proxy.price.clone()]),
+    )
+}
+
+/// Receives the randomness requested to the Nois proxy when the raffle was created (or modified)
+/// Only the configured proxy can provide randomness and it is only accepted once the raffle is closed
+pub fn execute_nois_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: NoisCallback,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let RandomnessSource::Nois(proxy) = &config.randomness_source else {
+        return Err(ContractError::WrongRandomnessSource {});
+    };
+    ensure_eq!(
+        info.sender,
+        proxy.address,
+        ContractError::UnauthorizedReceive
+    );
+
+    let raffle_id = callback
+        .job_id
+        .strip_prefix(JOB_ID_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+        .ok_or_else(|| ContractError::ParseError("job_id".to_string()))?;
+
+    let mut raffle_info = load_raffle(deps.storage, raffle_id)?;
+    let raffle_state = get_raffle_state(&env, &config, &raffle_info);
+    if raffle_state != RaffleState::Closed {
+        return Err(ContractError::WrongStateForRandomness {
+            status: raffle_state,
+        });
+    }
+    if raffle_info.randomness_seed().is_some() {
+        return Err(ContractError::RandomnessAlreadyProvided {});
+    }
+
+    // A job requested before the raffle was modified may have been published before the new raffle end
+    let raffle_end = raffle_info.raffle_options.raffle_end();
+    if callback.published < raffle_end {
+        return Err(ContractError::RandomnessPublishedTooEarly {
+            published: callback.published,
+            raffle_end,
+        });
+    }
+
+    if callback.randomness.len() != 32 {
+        return Err(ContractError::InvalidRandomness);
+    }
+    raffle_info.randomness = Some(callback.randomness);
+    RAFFLE_INFO.save(deps.storage, raffle_id, &raffle_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "nois_receive")
+        .add_attribute("raffle_id", raffle_id.to_string())
+        .add_attribute("published", callback.published.to_string()))
+}
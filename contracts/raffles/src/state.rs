@@ -1,10 +1,10 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    ensure, ensure_eq, Addr, Api, Coin, Decimal, Deps, Empty, Env, HexBinary, StdError, StdResult,
-    Storage, Timestamp, Uint128,
+    ensure, ensure_eq, Addr, Api, Coin, Decimal, Deps, Empty, Env, HexBinary, Order, StdError,
+    StdResult, Storage, Timestamp, Uint128,
 };
 use cw20::{Cw20Coin, Cw20CoinVerified};
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Bound, Item, Map};
 use dao_interface::voting::VotingPowerAtHeightResponse;
 use randomness::Randomness;
 use utils::state::{AssetInfo, Locks};
@@ -16,12 +16,33 @@ pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
 pub const OLD_CONFIG: Item<OldConfig> = Item::new(CONFIG_KEY);
 pub const MAX_TICKET_NUMBER: u32 = 100000; // The maximum amount of tickets () that can be in a raffle
 pub const MINIMUM_RAFFLE_DURATION: u64 = 1; // default minimum raffle duration, in seconds
+pub const DEFAULT_RANDOMNESS_DEADLINE: u64 = 604800; // default time to provide randomness after a raffle ends, in seconds (1 week)
 pub const RAFFLE_INFO: Map<u64, RaffleInfo> = Map::new("raffle_info");
-pub const RAFFLE_TICKETS: Map<(u64, u32), Addr> = Map::new("raffle_tickets");
+/// Tickets bought together are stored as a single range, keyed by the index of their first ticket
+pub const RAFFLE_TICKET_RANGES: Map<(u64, u32), TicketRange> = Map::new("raffle_ticket_ranges");
+/// Legacy ticket storage (one entry per ticket), only read when migrating
+pub const OLD_RAFFLE_TICKETS: Map<(u64, u32), Addr> = Map::new("raffle_tickets");
+/// Maximum number of legacy tickets converted to ranges in a single migration step
+pub const TICKET_MIGRATION_BATCH: u32 = 1000;
 pub const STATIC_RAFFLE_CREATION_FEE: u128 = 100; // default static tokens required to create raffle
 pub const USER_TICKETS: Map<(&Addr, u64), u32> = Map::new("user_tickets");
+/// Tickets bought by a user in each of the additional ticket currencies of a raffle, keyed by price index.
+/// The rest of the user tickets were paid with the main ticket price
+pub const USER_ADDITIONAL_TICKETS: Map<(&Addr, u64, u32), u32> =
+    Map::new("user_additional_tickets");
+/// Discount a user got from the raffle pricing schedule with each ticket price, keyed by price index
+pub const USER_TICKET_DISCOUNTS: Map<(&Addr, u64, u32), Uint128> =
+    Map::new("user_ticket_discounts");
+/// Ticket buyers that already claimed their refund on a raffle
+pub const CLAIMED_REFUNDS: Map<(&Addr, u64), bool> = Map::new("claimed_refunds");
+/// Tickets and rewards credited to a referrer on each raffle
+pub const REFERRALS: Map<(&Addr, u64), Referral> = Map::new("referrals");
 
 pub const NFT_TOKEN_LIMIT: u32 = 20;
+/// Maximum number of prizes in a raffle, including the ones added by sponsors
+pub const MAX_RAFFLE_ASSETS: u32 = 50;
+/// Maximum nesting of `All`, `Any` and `Not` gating conditions
+pub const MAX_GATING_DEPTH: u32 = 4;
 
 #[cw_serde]
 pub struct Config {
@@ -39,6 +60,9 @@ pub struct Config {
     pub max_tickets_per_raffle: Option<u32>,
     /// A % cut of all raffle fee's generated to go to the fee_addr
     pub raffle_fee: Decimal,
+    /// A % cut of all raffle fee's generated to go to the address that provided the raffle randomness
+    #[serde(default)]
+    pub randomness_provider_fee: Decimal,
     /// locks the contract from new raffles being created
     pub locks: Locks,
     pub creation_coins: Vec<Coin>,
@@ -47,6 +71,22 @@ pub struct Config {
     pub fee_discounts: Vec<FeeDiscount>,
 
     pub drand_config: DrandConfig,
+
+    /// Where the raffle randomness comes from
+    #[serde(default)]
+    pub randomness_source: RandomnessSource,
+
+    /// Time after the end of a raffle, in seconds, after which a raffle that didn't receive randomness expires
+    #[serde(default = "default_randomness_deadline")]
+    pub randomness_deadline: u64,
+
+    /// A % cut of the ticket sales brought by a referrer, paid to them out of the raffle owner share
+    #[serde(default)]
+    pub referral_fee: Decimal,
+}
+
+fn default_randomness_deadline() -> u64 {
+    DEFAULT_RANDOMNESS_DEADLINE
 }
 
 #[cw_serde]
@@ -111,6 +151,9 @@ impl FeeDiscountMsg {
             } => {}
             AdvantageOptionsMsg::DaoVotingPower { .. } => {}
             AdvantageOptionsMsg::Staking { .. } => {}
+            AdvantageOptionsMsg::All(_) => {}
+            AdvantageOptionsMsg::Any(_) => {}
+            AdvantageOptionsMsg::Not(_) => {}
         }
         Ok(FeeDiscount {
             discount: self.discount,
@@ -161,6 +204,9 @@ impl FeeDiscount {
             AdvantageOptions::Staking {
                 min_voting_power: _,
             } => self.discount,
+            AdvantageOptions::All(_) | AdvantageOptions::Any(_) | AdvantageOptions::Not(_) => {
+                self.discount
+            }
         };
         Ok(discount.min(Decimal::one()))
     }
@@ -190,6 +236,28 @@ pub struct NoisProxy {
     pub address: Addr,
 }
 
+#[cw_serde]
+#[derive(Default)]
+pub enum RandomnessSource {
+    /// drand beacons, submitted by anyone with `UpdateRandomness` and checked by the `drand_config` verifier
+    #[default]
+    Drand,
+    /// Randomness requested to a Nois proxy (paid by the contract) and delivered with `NoisReceive`
+    Nois(NoisProxy),
+}
+
+impl RandomnessSource {
+    pub fn check(self, api: &dyn Api) -> StdResult<Self> {
+        Ok(match self {
+            RandomnessSource::Drand => RandomnessSource::Drand,
+            RandomnessSource::Nois(proxy) => RandomnessSource::Nois(NoisProxy {
+                address: api.addr_validate(proxy.address.as_str())?,
+                price: proxy.price,
+            }),
+        })
+    }
+}
+
 // RAFFLES
 
 pub fn load_raffle(storage: &dyn Storage, raffle_id: u64) -> StdResult<RaffleInfo> {
@@ -207,6 +275,224 @@ pub struct RaffleInfo {
     pub is_cancelled: bool,
     pub raffle_options: RaffleOptions,
     pub drand_randomness: Option<Randomness>, // This for drand now, migrating away from nois
+    #[serde(default)]
+    pub is_expired: bool, // prizes were returned to the owner because randomness never arrived
+    /// Other accepted costs per ticket, tickets can be bought with any of them
+    #[serde(default)]
+    pub additional_ticket_prices: Vec<AssetInfo>,
+    /// Number of tickets bought with each of the additional ticket prices
+    #[serde(default)]
+    pub additional_tickets_sold: Vec<u32>,
+    /// Total discount given by the pricing schedule with each ticket price, in the order of [`Self::ticket_prices`]
+    #[serde(default)]
+    pub ticket_discounts: Vec<Uint128>,
+    /// Total rewards owed to referrers with each ticket price, in the order of [`Self::ticket_prices`]
+    #[serde(default)]
+    pub referral_rewards: Vec<Uint128>,
+    /// Address that deposited each asset, in the order of `assets`.
+    /// Assets without a recorded depositor were deposited by the raffle owner
+    #[serde(default)]
+    pub asset_depositors: Vec<Addr>,
+    /// Contract fees when the raffle was created, later config updates don't change them.
+    /// Raffles created before the fees were recorded use the current contract fees
+    #[serde(default)]
+    pub fees: Option<RaffleFees>,
+    /// Time after which the Nois randomness was last requested, when the raffle end was modified.
+    /// Before that, it was requested at creation for right after the raffle end
+    #[serde(default)]
+    pub nois_request_after: Option<Timestamp>,
+    /// Time to provide randomness after the raffle end, in seconds, taken from the config when the raffle was created.
+    /// Raffles created before the deadline was recorded use the current one, until a refund is claimed
+    #[serde(default)]
+    pub randomness_deadline: Option<u64>,
+}
+
+/// Fees taken out of the ticket sales of a raffle
+#[cw_serde]
+pub struct RaffleFees {
+    pub raffle_fee: Decimal,
+    pub randomness_provider_fee: Decimal,
+    pub referral_fee: Decimal,
+}
+
+impl From<&Config> for RaffleFees {
+    fn from(config: &Config) -> Self {
+        Self {
+            raffle_fee: config.raffle_fee,
+            randomness_provider_fee: config.randomness_provider_fee,
+            referral_fee: config.referral_fee,
+        }
+    }
+}
+
+/// The fees taken out of the ticket sales can't add up to more than the sales themselves
+pub fn check_fee_total(fees: &[Decimal]) -> Result<(), ContractError> {
+    let total = fees
+        .iter()
+        .try_fold(Decimal::zero(), |total, fee| total.checked_add(*fee))
+        .map_err(|_| ContractError::InvalidFeeRate {})?;
+    ensure!(total <= Decimal::one(), ContractError::InvalidFeeRate {});
+    Ok(())
+}
+
+impl RaffleInfo {
+    /// The fees applied to the raffle ticket sales
+    pub fn fees(&self, config: &Config) -> RaffleFees {
+        self.fees.clone().unwrap_or_else(|| config.into())
+    }
+
+    /// Time to provide randomness after the raffle end, in seconds
+    pub fn randomness_deadline(&self, config: &Config) -> u64 {
+        self.randomness_deadline
+            .unwrap_or(config.randomness_deadline)
+    }
+
+    /// The randomness used to draw the raffle winners, whatever its source
+    pub fn randomness_seed(&self) -> Option<[u8; 32]> {
+        match (&self.drand_randomness, &self.randomness) {
+            (Some(drand_randomness), _) => Some(drand_randomness.randomness),
+            (None, Some(randomness)) => randomness.to_array().ok(),
+            (None, None) => None,
+        }
+    }
+
+    /// All the accepted costs per ticket, starting with the main ticket price
+    pub fn ticket_prices(&self) -> Vec<AssetInfo> {
+        let mut prices = vec![self.raffle_ticket_price.clone()];
+        prices.extend(self.additional_ticket_prices.iter().cloned());
+        prices
+    }
+
+    /// Number of tickets bought with each ticket price, in the order of [`Self::ticket_prices`]
+    pub fn tickets_sold_per_price(&self) -> Vec<(AssetInfo, u32)> {
+        let additional_tickets: u32 = self.additional_tickets_sold.iter().sum();
+        let mut tickets_sold = vec![(
+            self.raffle_ticket_price.clone(),
+            self.number_of_tickets - additional_tickets,
+        )];
+        tickets_sold.extend(
+            self.additional_ticket_prices
+                .iter()
+                .cloned()
+                .zip(self.additional_tickets_sold.iter().copied()),
+        );
+        tickets_sold
+    }
+
+    /// Total discount given by the pricing schedule with the ticket price at `price_index`
+    pub fn ticket_discount(&self, price_index: usize) -> Uint128 {
+        self.ticket_discounts
+            .get(price_index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Total rewards owed to referrers with the ticket price at `price_index`
+    pub fn referral_reward(&self, price_index: usize) -> Uint128 {
+        self.referral_rewards
+            .get(price_index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Address that deposited the asset at `asset_index`, it gets the asset back when nobody wins it
+    pub fn asset_depositor(&self, asset_index: usize) -> Addr {
+        self.asset_depositors
+            .get(asset_index)
+            .unwrap_or(&self.owner)
+            .clone()
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Referral {
+    /// Number of tickets bought by the referred buyers
+    pub ticket_count: u32,
+    /// Rewards in each ticket currency, paid once the raffle is claimed
+    pub rewards: Vec<AssetInfo>,
+    pub claimed: bool,
+}
+
+#[cw_serde]
+pub struct TicketRange {
+    pub owner: Addr,
+    pub count: u32,
+}
+
+/// Loads the owner of a ticket.
+/// The ticket belongs to the last range starting at or before its index, so only one entry is read.
+/// Tickets that weren't migrated to ranges yet are read from the legacy storage
+pub fn load_ticket_owner(
+    storage: &dyn Storage,
+    raffle_id: u64,
+    ticket_index: u32,
+) -> StdResult<Addr> {
+    let range = RAFFLE_TICKET_RANGES
+        .prefix(raffle_id)
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(ticket_index)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?;
+
+    match range {
+        Some((start, range)) if ticket_index < start + range.count => Ok(range.owner),
+        _ => OLD_RAFFLE_TICKETS
+            .may_load(storage, (raffle_id, ticket_index))?
+            .ok_or_else(|| StdError::not_found("raffle ticket")),
+    }
+}
+
+/// Converts up to `limit` tickets of the legacy per-ticket storage to ticket ranges, returning how many were converted.
+/// Consecutive tickets owned by the same address are merged in a single range, also across batches
+pub fn migrate_ticket_ranges(storage: &mut dyn Storage, limit: u32) -> StdResult<u32> {
+    let old_tickets = OLD_RAFFLE_TICKETS
+        .range(storage, None, None, Order::Ascending)
+        .take(limit as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    let migrated = old_tickets.len() as u32;
+
+    for ((raffle_id, ticket_index), owner) in old_tickets {
+        OLD_RAFFLE_TICKETS.remove(storage, (raffle_id, ticket_index));
+        let previous_range = RAFFLE_TICKET_RANGES
+            .prefix(raffle_id)
+            .range(
+                storage,
+                None,
+                Some(Bound::exclusive(ticket_index)),
+                Order::Descending,
+            )
+            .next()
+            .transpose()?;
+        match previous_range {
+            Some((start, mut range))
+                if start + range.count == ticket_index && range.owner == owner =>
+            {
+                range.count += 1;
+                RAFFLE_TICKET_RANGES.save(storage, (raffle_id, start), &range)?;
+            }
+            _ => RAFFLE_TICKET_RANGES.save(
+                storage,
+                (raffle_id, ticket_index),
+                &TicketRange { owner, count: 1 },
+            )?,
+        }
+    }
+
+    Ok(migrated)
+}
+
+/// Whether some tickets of the raffle are still in the legacy storage
+pub fn has_legacy_tickets(storage: &dyn Storage, raffle_id: u64) -> bool {
+    OLD_RAFFLE_TICKETS
+        .prefix(raffle_id)
+        .keys_raw(storage, None, None, Order::Ascending)
+        .next()
+        .is_some()
 }
 
 #[cw_serde]
@@ -217,6 +503,7 @@ pub enum RaffleState {
     Claimed,
     Finished,
     Cancelled,
+    Expired,
 }
 
 impl std::fmt::Display for RaffleState {
@@ -228,6 +515,7 @@ impl std::fmt::Display for RaffleState {
             RaffleState::Claimed => write!(f, "claimed"),
             RaffleState::Finished => write!(f, "finished"),
             RaffleState::Cancelled => write!(f, "cancelled"),
+            RaffleState::Expired => write!(f, "expired"),
         }
     }
 }
@@ -248,13 +536,22 @@ pub fn get_raffle_state(env: &Env, config: &Config, raffle_info: &RaffleInfo) ->
             .plus_seconds(raffle_info.raffle_options.raffle_duration)
     {
         RaffleState::Started
+    } else if raffle_info.is_expired
+        || (raffle_info.randomness_seed().is_none()
+            && env.block.time
+                >= raffle_info
+                    .raffle_options
+                    .raffle_end()
+                    .plus_seconds(raffle_info.randomness_deadline(config)))
+    {
+        RaffleState::Expired
     } else if env.block.time
         < raffle_info
             .raffle_options
             .raffle_start_timestamp
             .plus_seconds(raffle_info.raffle_options.raffle_duration)
             .plus_seconds(config.drand_config.timeout)
-        || raffle_info.drand_randomness.is_none()
+        || raffle_info.randomness_seed().is_none()
     {
         RaffleState::Closed
     } else if raffle_info.winners.is_empty() {
@@ -275,10 +572,114 @@ pub struct RaffleOptions {
     pub min_ticket_number: Option<u32>,    // Minimum ticket number for a raffle to close.
     pub one_winner_per_asset: bool, // Allows to set multiple winners per raffle (one per asset)
     pub whitelist: Option<Vec<Addr>>,
+    /// Root of the merkle tree of allowed ticket buyers (and their optional ticket allowance)
+    #[serde(default)]
+    pub merkle_root: Option<HexBinary>,
+    /// Ranked groups of prizes, drawn in order. Empty when the prizes are not tiered
+    #[serde(default)]
+    pub prize_tiers: Vec<PrizeTier>,
+    /// Volume pricing applied to tickets bought together
+    #[serde(default)]
+    pub ticket_pricing: Option<TicketPricing>,
+    /// Share of the referred ticket sales paid to referrers, replaces the contract `referral_fee`
+    #[serde(default)]
+    pub referral_share: Option<Decimal>,
+    /// Addresses allowed to add prizes along with the raffle owner. Only the owner can add prizes when not set
+    #[serde(default)]
+    pub sponsors: Option<Vec<Addr>>,
 
     pub gating_raffle: Vec<AdvantageOptions>, // Allows for token gating raffle tickets. Only owners of those tokens can buy raffle tickets
 }
 
+/// A group of raffle prizes and the number of winners drawn for them.
+/// With as many winners as prizes, each winner gets one prize.
+/// A tier with a single coin prize can also split it evenly between its winners,
+/// e.g. 5 winners of 100 STARS each out of 500 STARS
+#[cw_serde]
+pub struct PrizeTier {
+    /// Indexes of the prizes in the raffle `assets`
+    pub assets: Vec<u32>,
+    pub winners: u32,
+}
+
+impl PrizeTier {
+    /// Whether the winners of the tier share its single prize
+    pub fn is_split(&self) -> bool {
+        self.winners as usize != self.assets.len()
+    }
+}
+
+/// Pricing schedule of the tickets bought together, it applies to every ticket price of the raffle
+#[cw_serde]
+pub enum TicketPricing {
+    /// Each full bundle of `tickets` tickets costs the price of `paid_tickets` tickets,
+    /// e.g. 5 tickets for the price of 4
+    Bundle { tickets: u32, paid_tickets: u32 },
+    /// The discount of the largest tier reached by the purchase applies to all its tickets
+    Graduated { tiers: Vec<VolumeDiscount> },
+}
+
+#[cw_serde]
+pub struct VolumeDiscount {
+    pub min_tickets: u32,
+    pub discount: Decimal,
+}
+
+impl TicketPricing {
+    pub fn check(self) -> StdResult<Self> {
+        match &self {
+            TicketPricing::Bundle {
+                tickets,
+                paid_tickets,
+            } => ensure!(
+                *paid_tickets != 0 && paid_tickets < tickets,
+                StdError::generic_err("A ticket bundle must cost less than its tickets")
+            ),
+            TicketPricing::Graduated { tiers } => {
+                ensure!(
+                    !tiers.is_empty(),
+                    StdError::generic_err("Graduated pricing needs at least one tier")
+                );
+                ensure!(
+                    tiers.iter().all(|tier| tier.discount < Decimal::one()),
+                    StdError::generic_err("A ticket discount must be lower than 100%")
+                );
+                ensure!(
+                    tiers
+                        .windows(2)
+                        .all(|pair| pair[0].min_tickets < pair[1].min_tickets),
+                    StdError::generic_err("Discount tiers must be sorted by ticket number")
+                );
+            }
+        }
+        Ok(self)
+    }
+
+    /// Amount to pay for `ticket_count` tickets bought together, at `unit_price` per ticket
+    pub fn cost(&self, unit_price: Uint128, ticket_count: u32) -> Uint128 {
+        match self {
+            TicketPricing::Bundle {
+                tickets,
+                paid_tickets,
+            } => {
+                let paid_tickets = ticket_count / tickets * paid_tickets + ticket_count % tickets;
+                unit_price * Uint128::from(paid_tickets)
+            }
+            TicketPricing::Graduated { tiers } => {
+                let full_price = unit_price * Uint128::from(ticket_count);
+                let discount = tiers
+                    .iter()
+                    .rev()
+                    .find(|tier| tier.min_tickets <= ticket_count)
+                    .map(|tier| tier.discount)
+                    .unwrap_or_default();
+                // The discount is rounded down, so that tickets are never free
+                full_price - full_price * discount
+            }
+        }
+    }
+}
+
 impl From<RaffleOptions> for RaffleOptionsMsg {
     fn from(value: RaffleOptions) -> Self {
         Self {
@@ -294,6 +695,13 @@ impl From<RaffleOptions> for RaffleOptionsMsg {
             whitelist: value
                 .whitelist
                 .map(|v| v.into_iter().map(Into::into).collect()),
+            merkle_root: value.merkle_root,
+            prize_tiers: value.prize_tiers,
+            ticket_pricing: value.ticket_pricing,
+            referral_share: value.referral_share,
+            sponsors: value
+                .sponsors
+                .map(|v| v.into_iter().map(Into::into).collect()),
         }
     }
 }
@@ -317,6 +725,12 @@ pub enum AdvantageOptions {
     Staking {
         min_voting_power: Uint128,
     },
+    /// Met when all the conditions are met
+    All(Vec<AdvantageOptions>),
+    /// Met when at least one of the conditions is met
+    Any(Vec<AdvantageOptions>),
+    /// Met when the condition is not met
+    Not(Box<AdvantageOptions>),
 }
 
 impl AdvantageOptions {
@@ -436,6 +850,40 @@ impl AdvantageOptions {
                 );
                 Ok(())
             }
+            crate::state::AdvantageOptions::All(conditions) => conditions
+                .iter()
+                .try_for_each(|condition| condition.has_advantage(deps, user.clone())),
+            crate::state::AdvantageOptions::Any(conditions) => {
+                for condition in conditions {
+                    if condition.is_met(deps, &user)? {
+                        return Ok(());
+                    }
+                }
+                Err(ContractError::NotGatingCondition {
+                    condition: self.clone(),
+                    user,
+                })
+            }
+            crate::state::AdvantageOptions::Not(condition) => {
+                ensure!(
+                    !condition.is_met(deps, &user)?,
+                    ContractError::NotGatingCondition {
+                        condition: self.clone(),
+                        user
+                    }
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether the user meets the condition.
+    /// Only an unmet condition counts as `false`, other errors (failed queries, ...) are returned
+    fn is_met(&self, deps: Deps, user: &str) -> Result<bool, ContractError> {
+        match self.has_advantage(deps, user.to_string()) {
+            Ok(()) => Ok(true),
+            Err(ContractError::NotGatingCondition { .. }) => Ok(false),
+            Err(err) => Err(err),
         }
     }
 }
@@ -451,6 +899,19 @@ pub struct RaffleOptionsMsg {
     pub one_winner_per_asset: bool,
     pub min_ticket_number: Option<u32>,
     pub whitelist: Option<Vec<String>>,
+    /// Hex encoded sha256 merkle root, see [`crate::utils::allowlist_leaf`] for the leaves format
+    #[serde(default)]
+    pub merkle_root: Option<HexBinary>,
+    /// When set, every asset of the raffle must belong to exactly one tier.
+    /// The first tier is drawn first and gets the grand prizes
+    #[serde(default)]
+    pub prize_tiers: Vec<PrizeTier>,
+    #[serde(default)]
+    pub ticket_pricing: Option<TicketPricing>,
+    #[serde(default)]
+    pub referral_share: Option<Decimal>,
+    #[serde(default)]
+    pub sponsors: Option<Vec<String>>,
 
     pub gating_raffle: Vec<AdvantageOptionsMsg>,
 }
@@ -474,11 +935,42 @@ pub enum AdvantageOptionsMsg {
     Staking {
         min_voting_power: Uint128,
     },
+    All(Vec<AdvantageOptionsMsg>),
+    Any(Vec<AdvantageOptionsMsg>),
+    Not(Box<AdvantageOptionsMsg>),
 }
 
 impl AdvantageOptionsMsg {
     pub fn check(self, api: &dyn Api) -> StdResult<AdvantageOptions> {
+        self.check_nested(api, 0)
+    }
+
+    /// Validates the condition, `depth` is the number of `All`, `Any` or `Not` conditions above it
+    fn check_nested(self, api: &dyn Api, depth: u32) -> StdResult<AdvantageOptions> {
+        let check_child = |condition: AdvantageOptionsMsg| -> StdResult<AdvantageOptions> {
+            ensure!(
+                depth < MAX_GATING_DEPTH,
+                StdError::generic_err("Too many nested gating conditions")
+            );
+            condition.check_nested(api, depth + 1)
+        };
+        let check_group =
+            |conditions: Vec<AdvantageOptionsMsg>| -> StdResult<Vec<AdvantageOptions>> {
+                ensure!(
+                    !conditions.is_empty(),
+                    StdError::generic_err("Gating condition groups can't be empty")
+                );
+                conditions
+                    .into_iter()
+                    .map(check_child)
+                    .collect::<StdResult<Vec<_>>>()
+            };
         Ok(match self {
+            AdvantageOptionsMsg::All(conditions) => AdvantageOptions::All(check_group(conditions)?),
+            AdvantageOptionsMsg::Any(conditions) => AdvantageOptions::Any(check_group(conditions)?),
+            AdvantageOptionsMsg::Not(condition) => {
+                AdvantageOptions::Not(Box::new(check_child(*condition)?))
+            }
             AdvantageOptionsMsg::Cw721Coin {
                 nft_count,
                 nft_address,
@@ -556,6 +1048,15 @@ impl From<AdvantageOptions> for AdvantageOptionsMsg {
             AdvantageOptions::Staking { min_voting_power } => {
                 AdvantageOptionsMsg::Staking { min_voting_power }
             }
+            AdvantageOptions::All(conditions) => {
+                AdvantageOptionsMsg::All(conditions.into_iter().map(Into::into).collect())
+            }
+            AdvantageOptions::Any(conditions) => {
+                AdvantageOptionsMsg::Any(conditions.into_iter().map(Into::into).collect())
+            }
+            AdvantageOptions::Not(condition) => {
+                AdvantageOptionsMsg::Not(Box::new((*condition).into()))
+            }
         }
     }
 }
@@ -564,10 +1065,16 @@ impl RaffleOptions {
     pub fn new(
         api: &dyn Api,
         env: Env,
-        assets_len: usize,
+        assets: &[AssetInfo],
         raffle_options: RaffleOptionsMsg,
         config: Config,
     ) -> StdResult<Self> {
+        let assets_len = assets.len();
+        check_prize_tiers(&raffle_options.prize_tiers, assets)?;
+        // Tiered prizes have multiple winners
+        let one_winner_per_asset =
+            raffle_options.one_winner_per_asset || !raffle_options.prize_tiers.is_empty();
+        let nb_winners = winner_count(&raffle_options.prize_tiers, assets_len);
         Ok(Self {
             raffle_start_timestamp: raffle_options
                 .raffle_start_timestamp
@@ -599,14 +1106,14 @@ impl RaffleOptions {
                 })
                 .unwrap_or(0u32),
 
-            one_winner_per_asset: raffle_options.one_winner_per_asset,
+            one_winner_per_asset,
             // We need to enforce a min ticket number in case we have one winner per asset
             // Because one ticket can't win more than one NFT
-            min_ticket_number: if raffle_options.one_winner_per_asset {
+            min_ticket_number: if one_winner_per_asset {
                 if let Some(min_ticket_number) = raffle_options.min_ticket_number {
-                    Some(min_ticket_number.max(assets_len as u32))
+                    Some(min_ticket_number.max(nb_winners))
                 } else {
-                    Some(assets_len as u32)
+                    Some(nb_winners)
                 }
             } else {
                 raffle_options.min_ticket_number
@@ -619,6 +1126,33 @@ impl RaffleOptions {
                         .collect::<Result<Vec<_>, _>>()
                 })
                 .transpose()?,
+            merkle_root: raffle_options
+                .merkle_root
+                .map(|root| -> StdResult<HexBinary> {
+                    ensure!(
+                        root.len() == 32,
+                        StdError::generic_err("The merkle root should be a sha256 hash")
+                    );
+                    Ok(root)
+                })
+                .transpose()?,
+            prize_tiers: raffle_options.prize_tiers,
+            ticket_pricing: raffle_options
+                .ticket_pricing
+                .map(TicketPricing::check)
+                .transpose()?,
+            referral_share: raffle_options
+                .referral_share
+                .map(|share| check_referral_share(share, &config))
+                .transpose()?,
+            sponsors: raffle_options
+                .sponsors
+                .map(|v| {
+                    v.into_iter()
+                        .map(|a| api.addr_validate(&a))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?,
 
             gating_raffle: raffle_options
                 .gating_raffle
@@ -631,10 +1165,21 @@ impl RaffleOptions {
     pub fn new_from(
         api: &dyn Api,
         current_options: RaffleOptions,
-        assets_len: usize,
+        assets: &[AssetInfo],
         raffle_options: RaffleOptionsMsg,
         config: Config,
     ) -> StdResult<Self> {
+        let assets_len = assets.len();
+        // The current tiers are kept when none are sent
+        let prize_tiers = if raffle_options.prize_tiers.is_empty() {
+            current_options.prize_tiers
+        } else {
+            raffle_options.prize_tiers
+        };
+        check_prize_tiers(&prize_tiers, assets)?;
+        // Tiered prizes have multiple winners
+        let one_winner_per_asset = raffle_options.one_winner_per_asset || !prize_tiers.is_empty();
+        let nb_winners = winner_count(&prize_tiers, assets_len);
         Ok(Self {
             raffle_start_timestamp: raffle_options
                 .raffle_start_timestamp
@@ -661,14 +1206,14 @@ impl RaffleOptions {
                     }
                 })
                 .unwrap_or(current_options.raffle_preview),
-            one_winner_per_asset: raffle_options.one_winner_per_asset,
+            one_winner_per_asset,
             // We need to enforce a min ticket number in case we have one winner per asset
             // Because one ticket can't win more than one NFT
-            min_ticket_number: if raffle_options.one_winner_per_asset {
+            min_ticket_number: if one_winner_per_asset {
                 if let Some(min_ticket_number) = raffle_options.min_ticket_number {
-                    Some(min_ticket_number.min(assets_len as u32))
+                    Some(min_ticket_number.max(nb_winners))
                 } else {
-                    Some(assets_len as u32)
+                    Some(nb_winners)
                 }
             } else {
                 raffle_options.min_ticket_number
@@ -681,6 +1226,36 @@ impl RaffleOptions {
                         .collect::<Result<Vec<_>, _>>()
                 })
                 .transpose()?,
+            merkle_root: raffle_options
+                .merkle_root
+                .map(|root| -> StdResult<HexBinary> {
+                    ensure!(
+                        root.len() == 32,
+                        StdError::generic_err("The merkle root should be a sha256 hash")
+                    );
+                    Ok(root)
+                })
+                .transpose()?,
+            prize_tiers,
+            ticket_pricing: raffle_options
+                .ticket_pricing
+                .map(TicketPricing::check)
+                .transpose()?
+                .or(current_options.ticket_pricing),
+            referral_share: raffle_options
+                .referral_share
+                .map(|share| check_referral_share(share, &config))
+                .transpose()?
+                .or(current_options.referral_share),
+            sponsors: raffle_options
+                .sponsors
+                .map(|v| {
+                    v.into_iter()
+                        .map(|a| api.addr_validate(&a))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()?
+                .or(current_options.sponsors),
 
             gating_raffle: raffle_options
                 .gating_raffle
@@ -689,4 +1264,82 @@ impl RaffleOptions {
                 .collect::<Result<Vec<_>, _>>()?,
         })
     }
+
+    /// End of the ticket sales
+    pub fn raffle_end(&self) -> Timestamp {
+        self.raffle_start_timestamp
+            .plus_seconds(self.raffle_duration)
+    }
+
+    /// Number of winners drawn for the raffle
+    pub fn winner_count(&self, assets_len: usize) -> u32 {
+        if self.one_winner_per_asset {
+            winner_count(&self.prize_tiers, assets_len)
+        } else {
+            1
+        }
+    }
+}
+
+/// Tiered prizes have the winners of each tier, other raffles with multiple winners have one per asset
+fn winner_count(prize_tiers: &[PrizeTier], assets_len: usize) -> u32 {
+    if prize_tiers.is_empty() {
+        assets_len as u32
+    } else {
+        prize_tiers.iter().map(|tier| tier.winners).sum()
+    }
+}
+
+/// Referrers are paid out of the ticket sales left after the contract fees
+fn check_referral_share(share: Decimal, config: &Config) -> StdResult<Decimal> {
+    check_fee_total(&[config.raffle_fee, config.randomness_provider_fee, share]).map_err(|_| {
+        StdError::generic_err("The referral share can't exceed the ticket sales left after fees")
+    })?;
+    Ok(share)
+}
+
+/// Every raffle asset must be in exactly one tier, and tiers can't be empty.
+/// A tier has one winner per prize, unless it splits a single coin prize evenly between its winners
+fn check_prize_tiers(prize_tiers: &[PrizeTier], assets: &[AssetInfo]) -> StdResult<()> {
+    if prize_tiers.is_empty() {
+        return Ok(());
+    }
+    let mut in_tier = vec![false; assets.len()];
+    for tier in prize_tiers {
+        ensure!(
+            !tier.assets.is_empty(),
+            StdError::generic_err("A prize tier needs at least one prize")
+        );
+        for &asset in &tier.assets {
+            match in_tier.get_mut(asset as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => {
+                    return Err(StdError::generic_err(format!(
+                        "Prize {asset} is not a raffle asset or is in multiple tiers"
+                    )))
+                }
+            }
+        }
+        if tier.is_split() {
+            let splittable = match (tier.assets.as_slice(), tier.winners) {
+                ([prize], winners) if winners > 0 => match &assets[*prize as usize] {
+                    AssetInfo::Coin(coin) => (coin.amount % Uint128::from(winners)).is_zero(),
+                    AssetInfo::Cw20Coin(token) => (token.amount % Uint128::from(winners)).is_zero(),
+                    _ => false,
+                },
+                _ => false,
+            };
+            ensure!(
+                splittable,
+                StdError::generic_err(
+                    "A prize tier needs one winner per prize, or a single coin prize split evenly between its winners"
+                )
+            );
+        }
+    }
+    ensure!(
+        in_tier.into_iter().all(|seen| seen),
+        StdError::generic_err("Every raffle asset must be in a prize tier")
+    );
+    Ok(())
 }
@@ -6,37 +6,37 @@ use sg721::ExecuteMsg as Sg721ExecuteMsg;
 
 use crate::{
     error::ContractError,
+    msg::AllowlistProof,
     state::{
-        get_raffle_state, Config, RaffleInfo, RaffleState, CONFIG, RAFFLE_INFO, RAFFLE_TICKETS,
+        get_raffle_state, load_ticket_owner, Config, RaffleInfo, RaffleOptions, RaffleState,
+        CONFIG, RAFFLE_INFO, USER_ADDITIONAL_TICKETS, USER_TICKET_DISCOUNTS,
     },
 };
 use cosmwasm_std::{
-    coins, Addr, BankMsg, Coin, Decimal, Deps, Empty, Env, HexBinary, Order, StdError, StdResult,
+    coins, Addr, Api, BankMsg, Coin, Decimal, Deps, Empty, Env, HexBinary, StdError, StdResult,
     Storage, Uint128,
 };
+use cw1155::Cw1155ExecuteMsg;
+use cw20::Cw20ExecuteMsg;
 use cw721::Cw721ExecuteMsg;
 use cw721_base::Extension;
+use sha2::{Digest, Sha256};
 
 use rand::Rng;
 use utils::{
-    state::{dedupe, into_cosmos_msg, AssetInfo},
+    state::{dedupe, into_cosmos_msg, AssetInfo, Cw20Coin},
     types::CosmosMsg,
 };
 
 /// Util to get the organizers and helpers messages to return when claiming a Raffle (returns the funds)
+/// The funds collected in each ticket currency are split separately
 pub fn get_raffle_owner_funds_finished_messages(
     deps: Deps,
     _env: Env,
     raffle_info: RaffleInfo,
 ) -> Result<Vec<CosmosMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-
-    // We start by splitting the fees between owner & treasury
-    let total_paid = match raffle_info.raffle_ticket_price.clone() {
-        // only native coins accepted for raffle fees currently
-        AssetInfo::Coin(coin) => coin.amount,
-        _ => return Err(ContractError::WrongFundsType {}),
-    } * Uint128::from(raffle_info.number_of_tickets);
+    let fees = raffle_info.fees(&config);
 
     // use raffle_fee % to calculate treasury distribution
     let discount_rate: Decimal = config
@@ -56,65 +56,170 @@ pub fn get_raffle_owner_funds_finished_messages(
             )
         })
         .fold(Decimal::one(), |acc, el| acc * el);
-    let treasury_amount = total_paid * config.raffle_fee * discount_rate;
-
-    let owner_amount = total_paid - treasury_amount;
-
-    // Then we craft the messages needed for asset transfers
-    match raffle_info.raffle_ticket_price {
-        AssetInfo::Coin(coin) => {
-            let mut messages: Vec<CosmosMsg> = vec![];
-            if treasury_amount != Uint128::zero() {
-                messages.push(
-                    BankMsg::Send {
-                        to_address: config.fee_addr.to_string(),
-                        amount: coins(treasury_amount.u128(), coin.denom.clone()),
-                    }
-                    .into(),
-                );
-            };
-            if owner_amount != Uint128::zero() {
-                messages.push(
-                    BankMsg::Send {
-                        to_address: raffle_info.owner.to_string(),
-                        amount: coins(owner_amount.u128(), coin.denom),
-                    }
-                    .into(),
-                );
-            };
 
-            Ok(messages)
+    let mut msgs = vec![];
+    for (i, (ticket_price, tickets_sold)) in
+        raffle_info.tickets_sold_per_price().into_iter().enumerate()
+    {
+        if tickets_sold == 0 {
+            continue;
+        }
+
+        // We start by splitting the fees between owner & treasury
+        let total_paid = match ticket_price.clone() {
+            // only native coins and cw20 tokens accepted for raffle fees currently
+            AssetInfo::Coin(coin) => coin.amount,
+            AssetInfo::Cw20Coin(token) => token.amount,
+            _ => return Err(ContractError::WrongFundsType {}),
+        } * Uint128::from(tickets_sold)
+            - raffle_info.ticket_discount(i);
+        let treasury_amount = total_paid * fees.raffle_fee * discount_rate;
+
+        // The randomness provider gets a cut for deciding the raffle
+        let (provider, provider_amount) = match &raffle_info.drand_randomness {
+            Some(randomness) => (
+                Some(randomness.randomness_owner.clone()),
+                total_paid * fees.randomness_provider_fee,
+            ),
+            None => (None, Uint128::zero()),
+        };
+
+        // Referral rewards stay in the contract until referrers claim them
+        let owner_amount = total_paid
+            .checked_sub(treasury_amount)
+            .and_then(|amount| amount.checked_sub(provider_amount))
+            .and_then(|amount| amount.checked_sub(raffle_info.referral_reward(i)))
+            .map_err(StdError::from)?;
+
+        let payouts = [
+            (Some(config.fee_addr.clone()), treasury_amount),
+            (provider, provider_amount),
+            (Some(raffle_info.owner.clone()), owner_amount),
+        ]
+        .into_iter()
+        .filter_map(|(recipient, amount)| match recipient {
+            Some(recipient) if !amount.is_zero() => Some((recipient, amount)),
+            _ => None,
+        });
+
+        // Then we craft the messages needed for asset transfers
+        match ticket_price {
+            AssetInfo::Coin(coin) => msgs.extend(payouts.map(|(recipient, amount)| {
+                CosmosMsg::from(BankMsg::Send {
+                    to_address: recipient.to_string(),
+                    amount: coins(amount.u128(), coin.denom.clone()),
+                })
+            })),
+            AssetInfo::Cw20Coin(token) => msgs.extend(
+                payouts
+                    .map(|(recipient, amount)| {
+                        into_cosmos_msg(
+                            Cw20ExecuteMsg::Transfer {
+                                recipient: recipient.to_string(),
+                                amount,
+                            },
+                            token.address.clone(),
+                            None,
+                        )
+                    })
+                    .collect::<StdResult<Vec<_>>>()?,
+            ),
+            _ => return Err(ContractError::WrongFundsType {}),
         }
-        _ => Err(ContractError::WrongFundsType {}),
     }
+
+    Ok(msgs)
+}
+
+/// Ticket funds are refunded to the buyers when the raffle ended without reaching its minimum ticket number
+pub fn is_raffle_refunded(raffle_info: &RaffleInfo) -> bool {
+    raffle_info.number_of_tickets != 0
+        && raffle_info.number_of_tickets < raffle_info.raffle_options.min_ticket_number.unwrap_or(0)
 }
 
-/// Util to get the refund of funds for raffle participants
-pub fn get_raffle_refund_funds_finished_messages(
+/// Ticket buyers can get their funds back once a raffle without enough tickets is decided,
+/// or as soon as a raffle expired without randomness
+pub fn is_refund_open(raffle_state: &RaffleState, raffle_info: &RaffleInfo) -> bool {
+    match raffle_state {
+        RaffleState::Expired => raffle_info.number_of_tickets != 0,
+        RaffleState::Finished | RaffleState::Claimed => is_raffle_refunded(raffle_info),
+        _ => false,
+    }
+}
+
+/// Number of tickets a participant bought with each ticket price and the funds they paid for them,
+/// in the order of [`RaffleInfo::ticket_prices`]
+pub fn user_ticket_payments(
     storage: &dyn Storage,
-    _env: Env,
-    raffle_info: RaffleInfo,
     raffle_id: u64,
+    raffle_info: &RaffleInfo,
+    user: &Addr,
+    ticket_count: u32,
+) -> Result<Vec<(u32, AssetInfo)>, ContractError> {
+    let mut tickets_per_price = vec![0];
+    for i in 1..=raffle_info.additional_ticket_prices.len() as u32 {
+        tickets_per_price.push(
+            USER_ADDITIONAL_TICKETS
+                .may_load(storage, (user, raffle_id, i))?
+                .unwrap_or(0),
+        );
+    }
+    tickets_per_price[0] = ticket_count - tickets_per_price.iter().sum::<u32>();
+
+    raffle_info
+        .ticket_prices()
+        .into_iter()
+        .zip(tickets_per_price)
+        .enumerate()
+        .map(|(i, (ticket_price, count))| -> Result<_, ContractError> {
+            let discount = USER_TICKET_DISCOUNTS
+                .may_load(storage, (user, raffle_id, i as u32))?
+                .unwrap_or_default();
+            let paid = ticket_price_amount(&ticket_price)? * Uint128::from(count) - discount;
+            Ok((count, ticket_payment(ticket_price, paid)?))
+        })
+        .collect()
+}
+
+/// Util to get the refund messages of all the tickets a participant bought, one per ticket currency
+pub fn get_raffle_refund_messages(
+    receiver: &Addr,
+    ticket_payments: Vec<(u32, AssetInfo)>,
 ) -> Result<Vec<CosmosMsg>, ContractError> {
-    // We refund all the raffle ticket funds to the tickets buyers
-    let raffle_ticket_buyers = RAFFLE_TICKETS
-        .prefix(raffle_id)
-        .range(storage, None, None, Order::Descending)
-        .map(|r| {
-            r.and_then(|(_k, v)| {
-                // We get the funds transfer message
-                match &raffle_info.raffle_ticket_price {
-                    AssetInfo::Coin(ticket_price) => Ok(BankMsg::Send {
-                        to_address: v.to_string(),
-                        amount: vec![ticket_price.clone()],
-                    }
-                    .into()),
-                    _ => Err(StdError::generic_err("Invalid Ticket")),
-                }
-            })
+    get_payment_messages(
+        receiver,
+        ticket_payments
+            .into_iter()
+            .filter(|(ticket_count, _)| *ticket_count != 0)
+            .map(|(_, refund)| refund)
+            .collect(),
+    )
+}
+
+/// Util to send native coins and cw20 tokens held by the contract, one message per asset
+pub fn get_payment_messages(
+    receiver: &Addr,
+    payments: Vec<AssetInfo>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    payments
+        .into_iter()
+        .map(|payment| match payment {
+            AssetInfo::Coin(payment) => Ok(BankMsg::Send {
+                to_address: receiver.to_string(),
+                amount: vec![payment],
+            }
+            .into()),
+            AssetInfo::Cw20Coin(payment) => Ok(into_cosmos_msg(
+                Cw20ExecuteMsg::Transfer {
+                    recipient: receiver.to_string(),
+                    amount: payment.amount,
+                },
+                payment.address,
+                None,
+            )?),
+            _ => Err(ContractError::WrongFundsType {}),
         })
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(raffle_ticket_buyers)
+        .collect()
 }
 
 /// Picking the winner of the raffle
@@ -126,30 +231,50 @@ pub fn get_raffle_winners(
 ) -> Result<Vec<Addr>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     // if randomness not has been provided then we expect an error
-    if raffle_info.drand_randomness.is_none() {
+    let Some(randomness) = raffle_info.randomness_seed() else {
         return Err(ContractError::WrongStateForClaim {
             status: get_raffle_state(env, &config, &raffle_info),
         });
-    }
+    };
 
-    // We initiate the random number generator
-    let randomness = raffle_info.drand_randomness.unwrap().randomness;
+    let prize_tiers = &raffle_info.raffle_options.prize_tiers;
+    if !prize_tiers.is_empty() {
+        // Winners are drawn tier by tier, see [`get_raffle_winner_messages`].
+        // A ticket owner only wins once, tiers left without winners go back to the raffle owner
+        let mut tickets = TicketDraw::new(randomness, raffle_info.number_of_tickets);
+        let mut winners: Vec<Addr> = vec![];
+        for tier in prize_tiers {
+            for _ in 0..tier.winners {
+                let winner = loop {
+                    let Some(ticket) = tickets.next() else {
+                        break raffle_info.owner.clone();
+                    };
+                    let owner = load_ticket_owner(deps.storage, raffle_id, ticket)?;
+                    if !winners.contains(&owner) {
+                        break owner;
+                    }
+                };
+                winners.push(winner);
+            }
+        }
+        return Ok(winners);
+    }
 
-    let nb_winners = if raffle_info.raffle_options.one_winner_per_asset {
-        raffle_info.assets.len()
-    } else {
-        1
-    };
+    let nb_winners = raffle_info
+        .raffle_options
+        .winner_count(raffle_info.assets.len());
 
-    let winner_ids =
-        pick_m_single_winners_among_n(randomness, raffle_info.number_of_tickets, nb_winners)?;
+    let winner_ids = pick_m_single_winners_among_n(
+        randomness,
+        raffle_info.number_of_tickets,
+        nb_winners as usize,
+    )?;
 
-    let winners = winner_ids
+    winner_ids
         .into_iter()
-        .map(|winner_id| RAFFLE_TICKETS.load(deps.storage, (raffle_id, winner_id)))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(winners)
+        .map(|winner_id| load_ticket_owner(deps.storage, raffle_id, winner_id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ContractError::from)
 }
 
 /// In this function, we are getting nb_winners different winners among n ticket.
@@ -174,24 +299,48 @@ pub fn pick_m_single_winners_among_n(
     n: u32,
     nb_winners: usize, // m
 ) -> Result<Vec<u32>, ContractError> {
-    let mut map = HashMap::new();
-    let mut rng = make_prng(randomness);
-    let mut results = vec![];
-    for m in 0..nb_winners {
+    Ok(TicketDraw::new(randomness, n).take(nb_winners).collect())
+}
+
+/// Draws the tickets among n one by one, without repeats, see [`pick_m_single_winners_among_n`]
+pub struct TicketDraw {
+    map: HashMap<u32, u32>,
+    rng: Xoshiro256PlusPlus,
+    n: u32,
+    drawn: u32,
+}
+
+impl TicketDraw {
+    pub fn new(randomness: [u8; 32], n: u32) -> Self {
+        Self {
+            map: HashMap::new(),
+            rng: make_prng(randomness),
+            n,
+            drawn: 0,
+        }
+    }
+}
+
+impl Iterator for TicketDraw {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.drawn >= self.n {
+            return None;
+        }
         // We start by selecting a number between 0 and the current maximum
-        let current_maximum = n - 1 - m as u32;
-        let selected_index = rng.gen_range(0..=current_maximum);
+        let current_maximum = self.n - 1 - self.drawn;
+        let selected_index = self.rng.gen_range(0..=current_maximum);
 
         // We consider the array to
-        let selected_element = *map.get(&selected_index).unwrap_or(&selected_index);
-        map.insert(
+        let selected_element = *self.map.get(&selected_index).unwrap_or(&selected_index);
+        self.map.insert(
             selected_index,
-            *map.get(&current_maximum).unwrap_or(&current_maximum),
+            *self.map.get(&current_maximum).unwrap_or(&current_maximum),
         );
-        results.push(selected_element);
+        self.drawn += 1;
+        Some(selected_element)
     }
-
-    Ok(results)
 }
 
 pub fn make_prng(randomness: [u8; 32]) -> Xoshiro256PlusPlus {
@@ -207,15 +356,18 @@ pub fn make_prng(randomness: [u8; 32]) -> Xoshiro256PlusPlus {
     Xoshiro256PlusPlus::from_seed(randomness)
 }
 
-/// Util to get the raffle creator messages to return when the Raffle is cancelled (returns the raffled asset)
+/// Util to get the messages returning the raffled assets when nobody wins them (cancelled or expired Raffle, no winner).
+/// Each asset goes back to the address that deposited it
 pub fn get_raffle_owner_messages(env: Env, raffle_info: RaffleInfo) -> StdResult<Vec<CosmosMsg>> {
-    let owner: Addr = raffle_info.owner.clone();
-    _get_raffle_end_asset_messages(env, raffle_info, vec![owner])
+    let depositors: Vec<Addr> = (0..raffle_info.assets.len())
+        .map(|i| raffle_info.asset_depositor(i))
+        .collect();
+    _get_raffle_end_asset_messages(env, raffle_info, depositors)
 }
 
 /// Util to get the assets back from a raffle
 fn _get_raffle_end_asset_messages(
-    _env: Env,
+    env: Env,
     raffle_info: RaffleInfo,
     receivers: Vec<Addr>,
 ) -> StdResult<Vec<CosmosMsg>> {
@@ -226,36 +378,73 @@ fn _get_raffle_end_asset_messages(
         .enumerate()
         .map(|(i, asset)| {
             let receiver = if receivers.len() == 1 {
-                receivers[0].to_string()
+                &receivers[0]
             } else {
-                receivers[i].to_string()
+                &receivers[i]
             };
-            match asset {
-                AssetInfo::Cw721Coin(nft) => {
-                    let message = Cw721ExecuteMsg::TransferNft {
-                        recipient: receiver,
-                        token_id: nft.token_id.clone(),
-                    };
-                    into_cosmos_msg(message, nft.address.clone(), None)
-                }
-                #[cfg(feature = "sg")]
-                AssetInfo::Sg721Token(sg721_token) => {
-                    let message = Sg721ExecuteMsg::<Extension, Empty>::TransferNft {
-                        recipient: receiver,
-                        token_id: sg721_token.token_id.clone(),
-                    };
-                    into_cosmos_msg(message, sg721_token.address.clone(), None)
-                }
-                AssetInfo::Coin(coin) => Ok(BankMsg::Send {
-                    to_address: receiver,
-                    amount: vec![coin.clone()],
-                }
-                .into()),
-            }
+            get_asset_message(&env, asset, receiver)
         })
         .collect()
 }
 
+/// Equal share of a coin prize split between `winners` winners
+fn prize_share(prize: &AssetInfo, winners: u32) -> StdResult<AssetInfo> {
+    match prize {
+        AssetInfo::Coin(coin) => Ok(AssetInfo::Coin(Coin {
+            denom: coin.denom.clone(),
+            amount: coin.amount / Uint128::from(winners),
+        })),
+        AssetInfo::Cw20Coin(token) => Ok(AssetInfo::Cw20Coin(Cw20Coin {
+            address: token.address.clone(),
+            amount: token.amount / Uint128::from(winners),
+        })),
+        _ => Err(StdError::generic_err("Only coin prizes can be split")),
+    }
+}
+
+/// Message sending an asset held by the contract to `receiver`
+fn get_asset_message(env: &Env, asset: &AssetInfo, receiver: &Addr) -> StdResult<CosmosMsg> {
+    match asset {
+        AssetInfo::Cw721Coin(nft) => {
+            let message = Cw721ExecuteMsg::TransferNft {
+                recipient: receiver.to_string(),
+                token_id: nft.token_id.clone(),
+            };
+            into_cosmos_msg(message, nft.address.clone(), None)
+        }
+        #[cfg(feature = "sg")]
+        AssetInfo::Sg721Token(sg721_token) => {
+            let message = Sg721ExecuteMsg::<Extension, Empty>::TransferNft {
+                recipient: receiver.to_string(),
+                token_id: sg721_token.token_id.clone(),
+            };
+            into_cosmos_msg(message, sg721_token.address.clone(), None)
+        }
+        AssetInfo::Coin(coin) => Ok(BankMsg::Send {
+            to_address: receiver.to_string(),
+            amount: vec![coin.clone()],
+        }
+        .into()),
+        AssetInfo::Cw20Coin(token) => {
+            let message = Cw20ExecuteMsg::Transfer {
+                recipient: receiver.to_string(),
+                amount: token.amount,
+            };
+            into_cosmos_msg(message, token.address.clone(), None)
+        }
+        AssetInfo::Cw1155Coin(token) => {
+            let message = Cw1155ExecuteMsg::SendFrom {
+                from: env.contract.address.to_string(),
+                to: receiver.to_string(),
+                token_id: token.token_id.clone(),
+                value: token.value,
+                msg: None,
+            };
+            into_cosmos_msg(message, token.address.clone(), None)
+        }
+    }
+}
+
 pub fn is_raffle_owner(
     storage: &dyn Storage,
     raffle_id: u64,
@@ -269,18 +458,90 @@ pub fn is_raffle_owner(
     }
 }
 
-/// Computes the ticket cost for multiple tickets bought together
-pub fn ticket_cost(raffle_info: RaffleInfo, ticket_count: u32) -> Result<AssetInfo, ContractError> {
-    // enforces only Coin is a ticket cost currently.
-    Ok(match raffle_info.raffle_ticket_price {
+/// Computes the ticket cost for multiple tickets bought together with one of the raffle ticket prices.
+/// The raffle pricing schedule applies to the whole purchase
+pub fn ticket_cost(
+    raffle_options: &RaffleOptions,
+    ticket_price: AssetInfo,
+    ticket_count: u32,
+) -> Result<AssetInfo, ContractError> {
+    let unit_price = ticket_price_amount(&ticket_price)?;
+    let cost = match &raffle_options.ticket_pricing {
+        Some(ticket_pricing) => ticket_pricing.cost(unit_price, ticket_count),
+        None => Uint128::from(ticket_count) * unit_price,
+    };
+    ticket_payment(ticket_price, cost)
+}
+
+/// Amount of a single ticket price
+pub fn ticket_price_amount(ticket_price: &AssetInfo) -> Result<Uint128, ContractError> {
+    // enforces only Coin and Cw20 are a ticket cost currently.
+    match ticket_price {
+        AssetInfo::Coin(x) => Ok(x.amount),
+        AssetInfo::Cw20Coin(x) => Ok(x.amount),
+        _ => Err(ContractError::WrongAssetType {}),
+    }
+}
+
+/// An amount of funds in the currency of a ticket price
+pub fn ticket_payment(
+    ticket_price: AssetInfo,
+    amount: Uint128,
+) -> Result<AssetInfo, ContractError> {
+    Ok(match ticket_price {
         AssetInfo::Coin(x) => AssetInfo::Coin(Coin {
             denom: x.denom,
-            amount: Uint128::from(ticket_count) * x.amount,
+            amount,
+        }),
+        AssetInfo::Cw20Coin(x) => AssetInfo::Cw20Coin(Cw20Coin {
+            address: x.address,
+            amount,
         }),
         _ => return Err(ContractError::WrongAssetType {}),
     })
 }
 
+/// Whether two ticket payments are made in the same currency
+pub fn same_ticket_currency(a: &AssetInfo, b: &AssetInfo) -> bool {
+    match (a, b) {
+        (AssetInfo::Coin(a), AssetInfo::Coin(b)) => a.denom == b.denom,
+        (AssetInfo::Cw20Coin(a), AssetInfo::Cw20Coin(b)) => a.address == b.address,
+        _ => false,
+    }
+}
+
+/// Verifies the ticket prices of a raffle are at least 1 and don't share a currency
+pub fn check_ticket_prices(
+    api: &dyn Api,
+    ticket_prices: &[AssetInfo],
+) -> Result<(), ContractError> {
+    for (i, ticket_price) in ticket_prices.iter().enumerate() {
+        match ticket_price {
+            AssetInfo::Coin(coin) => {
+                if coin.amount < Uint128::one() {
+                    return Err(ContractError::InvalidTicketCost {});
+                };
+            }
+            AssetInfo::Cw20Coin(token) => {
+                api.addr_validate(&token.address)?;
+                if token.amount < Uint128::one() {
+                    return Err(ContractError::InvalidTicketCost {});
+                };
+            }
+            AssetInfo::Cw721Coin(_) | AssetInfo::Sg721Token(_) | AssetInfo::Cw1155Coin(_) => {
+                return Err(ContractError::InvalidTicketCost)
+            }
+        };
+        if ticket_prices[..i]
+            .iter()
+            .any(|other| same_ticket_currency(other, ticket_price))
+        {
+            return Err(ContractError::DuplicateTicketPrices {});
+        }
+    }
+    Ok(())
+}
+
 /// Can only buy a ticket when the raffle has started and is not closed
 pub fn can_buy_ticket(
     env: Env,
@@ -314,12 +575,94 @@ pub fn buyer_can_buy_ticket(
         .try_for_each(|options| options.has_advantage(deps, buyer.clone()))
 }
 
+/// Domain separation between the leaves and the inner nodes of the merkle allowlist
+const ALLOWLIST_LEAF_PREFIX: u8 = 0x00;
+const ALLOWLIST_NODE_PREFIX: u8 = 0x01;
+
+/// Leaf of the merkle allowlist: sha256 of `0x00`, the address length (u32 big endian) and the address,
+/// followed by the ticket allowance (u32 big endian) if there is one
+pub fn allowlist_leaf(user: &str, allowance: Option<u32>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([ALLOWLIST_LEAF_PREFIX]);
+    hasher.update((user.len() as u32).to_be_bytes());
+    hasher.update(user.as_bytes());
+    if let Some(allowance) = allowance {
+        hasher.update(allowance.to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Parent node of the merkle allowlist: sha256 of `0x01` and both children.
+/// Children are sorted so proofs don't need to indicate sides
+pub fn allowlist_parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let mut hashes = [a, b];
+    hashes.sort_unstable();
+    let mut hasher = Sha256::new();
+    hasher.update([ALLOWLIST_NODE_PREFIX]);
+    hasher.update(hashes.concat());
+    hasher.finalize().into()
+}
+
+/// Checks the user is part of the raffle merkle allowlist (if any).
+/// Returns the maximum number of tickets the user can buy, if the allowlist limits it
+pub fn assert_allowlisted(
+    raffle_info: &RaffleInfo,
+    user: &Addr,
+    allowlist_proof: Option<&AllowlistProof>,
+) -> Result<Option<u32>, ContractError> {
+    let Some(merkle_root) = &raffle_info.raffle_options.merkle_root else {
+        return Ok(None);
+    };
+    let allowlist_proof = allowlist_proof.ok_or(ContractError::NotAllowlisted {})?;
+
+    let root = allowlist_proof.proof.iter().try_fold(
+        allowlist_leaf(user.as_str(), allowlist_proof.allowance),
+        |hash, sibling| -> Result<_, ContractError> {
+            let sibling = sibling
+                .to_array()
+                .map_err(|_| ContractError::WrongLength {})?;
+            Ok(allowlist_parent(hash, sibling))
+        },
+    )?;
+
+    if merkle_root.as_slice() != root {
+        return Err(ContractError::NotAllowlisted {});
+    }
+    Ok(allowlist_proof.allowance)
+}
+
 pub fn get_raffle_winner_messages(
     _deps: Deps,
     env: Env,
     raffle_info: RaffleInfo,
 ) -> StdResult<Vec<CosmosMsg>> {
     let winners = raffle_info.winners.clone();
+    let prize_tiers = &raffle_info.raffle_options.prize_tiers;
+    // With prize tiers, winners are stored tier by tier.
+    // Each tier winner gets one of its prizes or an equal share of its single prize
+    if !prize_tiers.is_empty() && winners.len() > 1 {
+        let mut drawn = winners.iter();
+        let mut msgs = vec![];
+        for tier in prize_tiers {
+            let tier_winners: Vec<&Addr> = drawn.by_ref().take(tier.winners as usize).collect();
+            if tier.is_split() {
+                let share =
+                    prize_share(&raffle_info.assets[tier.assets[0] as usize], tier.winners)?;
+                for winner in tier_winners {
+                    msgs.push(get_asset_message(&env, &share, winner)?);
+                }
+            } else {
+                for (&prize, winner) in tier.assets.iter().zip(tier_winners) {
+                    msgs.push(get_asset_message(
+                        &env,
+                        &raffle_info.assets[prize as usize],
+                        winner,
+                    )?);
+                }
+            }
+        }
+        return Ok(msgs);
+    }
     // generate state modifications for
     _get_raffle_end_asset_messages(env, raffle_info, winners)
 }
@@ -328,11 +671,17 @@ pub fn get_raffle_winner_messages(
 
 #[cfg(test)]
 pub mod test {
-    use cosmwasm_std::HexBinary;
-
-    use crate::error::ContractError;
+    use cosmwasm_std::{testing::MockStorage, Addr, HexBinary, Order, StdResult};
+
+    use crate::{
+        error::ContractError,
+        state::{
+            load_ticket_owner, migrate_ticket_ranges, TicketRange, OLD_RAFFLE_TICKETS,
+            RAFFLE_TICKET_RANGES,
+        },
+    };
 
-    use super::pick_m_single_winners_among_n;
+    use super::{allowlist_leaf, allowlist_parent, pick_m_single_winners_among_n};
 
     #[test]
     fn large_random() -> Result<(), ContractError> {
@@ -364,4 +713,116 @@ pub mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn ticket_owner_lookup() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        for (start, owner, count) in [(0, "alice", 3), (3, "bob", 1000), (1003, "alice", 2)] {
+            RAFFLE_TICKET_RANGES.save(
+                &mut storage,
+                (0, start),
+                &TicketRange {
+                    owner: Addr::unchecked(owner),
+                    count,
+                },
+            )?;
+        }
+
+        assert_eq!(load_ticket_owner(&storage, 0, 0)?, "alice");
+        assert_eq!(load_ticket_owner(&storage, 0, 2)?, "alice");
+        assert_eq!(load_ticket_owner(&storage, 0, 3)?, "bob");
+        assert_eq!(load_ticket_owner(&storage, 0, 1002)?, "bob");
+        assert_eq!(load_ticket_owner(&storage, 0, 1004)?, "alice");
+        load_ticket_owner(&storage, 0, 1005).unwrap_err();
+        load_ticket_owner(&storage, 1, 0).unwrap_err();
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_tickets_to_ranges() -> StdResult<()> {
+        let mut storage = MockStorage::new();
+        for (raffle_id, index, owner) in [
+            (0, 0, "alice"),
+            (0, 1, "alice"),
+            (0, 2, "bob"),
+            (0, 3, "alice"),
+            (1, 0, "alice"),
+            (1, 1, "alice"),
+        ] {
+            OLD_RAFFLE_TICKETS.save(&mut storage, (raffle_id, index), &Addr::unchecked(owner))?;
+        }
+
+        // The first batch stops in the middle of alice's first range
+        assert_eq!(migrate_ticket_ranges(&mut storage, 1)?, 1);
+        // Tickets that weren't migrated yet can still be loaded
+        assert_eq!(load_ticket_owner(&storage, 0, 2)?, Addr::unchecked("bob"));
+
+        assert_eq!(migrate_ticket_ranges(&mut storage, 3)?, 3);
+        assert_eq!(migrate_ticket_ranges(&mut storage, 10)?, 2);
+        assert_eq!(migrate_ticket_ranges(&mut storage, 10)?, 0);
+
+        let ranges = RAFFLE_TICKET_RANGES
+            .range(&storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let range = |owner: &str, count| TicketRange {
+            owner: Addr::unchecked(owner),
+            count,
+        };
+        assert_eq!(
+            ranges,
+            vec![
+                ((0, 0), range("alice", 2)),
+                ((0, 2), range("bob", 1)),
+                ((0, 3), range("alice", 1)),
+                ((1, 0), range("alice", 2)),
+            ]
+        );
+        assert!(OLD_RAFFLE_TICKETS.is_empty(&storage));
+
+        Ok(())
+    }
+
+    #[test]
+    fn allowlist_merkle_tree() {
+        let alice = allowlist_leaf("alice", None);
+        let bob = allowlist_leaf("bob", Some(3));
+        let carol = allowlist_leaf("carol", Some(1));
+
+        // The allowance is part of the leaf
+        assert_ne!(bob, allowlist_leaf("bob", None));
+        assert_ne!(bob, allowlist_leaf("bob", Some(4)));
+
+        // The address and the allowance can't be confused
+        assert_ne!(
+            allowlist_leaf("user1", Some(23)),
+            allowlist_leaf("user12", Some(3))
+        );
+        assert_ne!(
+            allowlist_leaf("user1", Some(23)),
+            allowlist_leaf("user123", None)
+        );
+        assert_ne!(
+            allowlist_leaf("bob", Some(3)),
+            allowlist_leaf("bob\0\0\0\x03", None)
+        );
+
+        // Sibling order doesn't matter
+        assert_eq!(allowlist_parent(alice, bob), allowlist_parent(bob, alice));
+
+        let root = allowlist_parent(allowlist_parent(alice, bob), carol);
+        assert_eq!([bob, carol].into_iter().fold(alice, allowlist_parent), root);
+        assert_eq!(
+            [allowlist_parent(bob, alice)]
+                .into_iter()
+                .fold(carol, allowlist_parent),
+            root
+        );
+        assert_ne!(
+            [bob, carol]
+                .into_iter()
+                .fold(allowlist_leaf("dave", None), allowlist_parent),
+            root
+        );
+    }
 }
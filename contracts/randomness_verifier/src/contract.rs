@@ -4,9 +4,18 @@ use cosmwasm_std::{
     entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
 };
 
-use drand_verify::{derive_randomness, g1_from_variable, verify};
-use randomness::DrandRandomness;
-pub use randomness::VerifierExecuteMsg;
+use cosmwasm_std::{to_json_binary, HexBinary, Order};
+use cw_storage_plus::Bound;
+use drand_verify::{derive_randomness, G1Pubkey, G2PubkeyRfc, Pubkey};
+use randomness::{BeaconsResponse, DrandRandomness, DrandScheme, VerifiedBeacon};
+pub use randomness::{VerifierExecuteMsg, VerifierQueryMsg};
+use sha2::{Digest, Sha256};
+
+use crate::state::BEACONS;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 100;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -21,7 +30,7 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
     msg: VerifierExecuteMsg,
@@ -30,45 +39,126 @@ pub fn execute(
         VerifierExecuteMsg::Verify {
             randomness,
             pubkey,
+            scheme,
             raffle_id,
             owner,
-        } => execute_verify(randomness, pubkey, raffle_id, owner),
+        } => execute_verify(deps, randomness, pubkey, scheme, raffle_id, owner),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
-    Err(StdError::generic_err("No queries"))
+pub fn query(deps: Deps, _env: Env, msg: VerifierQueryMsg) -> StdResult<Binary> {
+    match msg {
+        VerifierQueryMsg::Beacon { network, round } => {
+            to_json_binary(&BEACONS.load(deps.storage, (network.as_slice(), round))?)
+        }
+        VerifierQueryMsg::LatestBeacon { network } => {
+            to_json_binary(&query_latest_beacon(deps, network)?)
+        }
+        VerifierQueryMsg::Beacons {
+            network,
+            start_after,
+            limit,
+        } => to_json_binary(&query_beacons(deps, network, start_after, limit)?),
+    }
 }
 
+/// Verifies a drand beacon and returns its randomness.
+/// Verified rounds are cached per network, a known round is answered from storage without checking the signature again
 pub fn execute_verify(
+    deps: DepsMut,
     randomness: DrandRandomness,
     pubkey: Binary,
+    scheme: DrandScheme,
     raffle_id: u64,
     owner: String,
 ) -> StdResult<Response> {
-    let pk = g1_from_variable(&pubkey).map_err(|_| StdError::generic_err("Invalid Public Key"))?;
-    let valid = verify(
-        &pk,
-        randomness.round,
-        randomness.previous_signature.as_slice(),
-        randomness.signature.as_slice(),
-    )
-    .unwrap_or(false);
-
-    if !valid {
-        return Err(StdError::generic_err("Invalid Signature"));
-    }
+    let pubkey_hash = HexBinary::from(Sha256::digest(pubkey.as_slice()).to_vec());
 
-    let randomness_result = derive_randomness(&randomness.signature);
+    let key = (pubkey_hash.as_slice(), randomness.round);
+    let beacon = match BEACONS.may_load(deps.storage, key)? {
+        Some(beacon) => beacon,
+        None => {
+            verify_signature(&randomness, &pubkey, scheme)?;
+            let beacon = VerifiedBeacon {
+                round: randomness.round,
+                randomness: Binary::from(derive_randomness(&randomness.signature)),
+                pubkey_hash: pubkey_hash.clone(),
+            };
+            BEACONS.save(deps.storage, key, &beacon)?;
+            beacon
+        }
+    };
 
     Ok(Response::new()
-        .add_attribute("round", randomness.round.to_string())
-        .add_attribute("randomness", Binary::from(randomness_result).to_string())
+        .add_attribute("round", beacon.round.to_string())
+        .add_attribute("randomness", beacon.randomness.to_string())
         .add_attribute("raffle_id", raffle_id.to_string())
         .add_attribute("owner", owner))
 }
 
+/// Checks the beacon signature against the drand network public key
+fn verify_signature(
+    randomness: &DrandRandomness,
+    pubkey: &Binary,
+    scheme: DrandScheme,
+) -> StdResult<()> {
+    let invalid_pubkey = |_| StdError::generic_err("Invalid Public Key");
+    let verified = match scheme {
+        DrandScheme::Chained => {
+            let previous_signature = randomness.previous_signature.as_ref().ok_or_else(|| {
+                StdError::generic_err("The chained scheme needs the previous signature")
+            })?;
+            G1Pubkey::from_variable(pubkey)
+                .map_err(invalid_pubkey)?
+                .verify(randomness.round, previous_signature, &randomness.signature)
+        }
+        // Unchained rounds only sign the round number, which is the same as an empty previous signature
+        DrandScheme::Unchained => G1Pubkey::from_variable(pubkey)
+            .map_err(invalid_pubkey)?
+            .verify(randomness.round, b"", &randomness.signature),
+        DrandScheme::Quicknet => G2PubkeyRfc::from_variable(pubkey)
+            .map_err(invalid_pubkey)?
+            .verify(randomness.round, b"", &randomness.signature),
+    };
+
+    if !verified.unwrap_or(false) {
+        return Err(StdError::generic_err("Invalid Signature"));
+    }
+    Ok(())
+}
+
+pub fn query_latest_beacon(deps: Deps, network: HexBinary) -> StdResult<VerifiedBeacon> {
+    BEACONS
+        .prefix(network.as_slice())
+        .range(deps.storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map(|(_, beacon)| beacon)
+        .ok_or_else(|| StdError::not_found("beacon"))
+}
+
+pub fn query_beacons(
+    deps: Deps,
+    network: HexBinary,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<BeaconsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let beacons = BEACONS
+        .prefix(network.as_slice())
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|kv| kv.map(|(_, beacon)| beacon))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(BeaconsResponse { beacons })
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -79,6 +169,10 @@ pub mod tests {
     use serialize::base64::{self, ToBase64};
     use serialize::hex::FromHex;
     const HEX_PUBKEY: &str = "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31";
+    /// drand quicknet (52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971)
+    const QUICKNET_HEX_PUBKEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+    /// Unchained test network key, derived from the secret scalar 0x5eed1234abcd
+    const UNCHAINED_HEX_PUBKEY: &str = "a3ece330be6cc27d4c75d05b7d9667e305a17f76ad0eae54064b4e0e6a7d8b2ce7c9e73493f7bf05a2cec7441d158fec";
 
     fn init_helper(deps: DepsMut) {
         let instantiate_msg = Empty {};
@@ -98,7 +192,7 @@ pub mod tests {
         let randomness = DrandRandomness{
             round:2098475,
             signature:Binary::from_base64(&"84bcb438f4505ef5282804c3d98a76b536970f9755004ea103fc15183c1b314a1e95582cb4dbaf3330272c72fe5675550ac6718ef53dfe18fca65c37f223a7dfbc1920a9c32dbf01a227290293a67fa4682dd266f717f5078829d83912926649".from_hex().unwrap().to_base64(base64::STANDARD)).unwrap(),
-            previous_signature:Binary::from_base64(&"b49ee4089fc510300b38d75ebba84576097bed61c171574acf2557f636c7144b471b57e18e5b0c3f8774e194344931c011873149d0db51fc70d22448bfc264d230be7ed6fcd3eb3b61fdc877d657dfa0d8ecaea6c1fa35f90bc84e88c1af17d4".from_hex().unwrap().to_base64(base64::STANDARD)).unwrap(),
+            previous_signature:Some(Binary::from_base64(&"b49ee4089fc510300b38d75ebba84576097bed61c171574acf2557f636c7144b471b57e18e5b0c3f8774e194344931c011873149d0db51fc70d22448bfc264d230be7ed6fcd3eb3b61fdc877d657dfa0d8ecaea6c1fa35f90bc84e88c1af17d4".from_hex().unwrap().to_base64(base64::STANDARD)).unwrap()),
         };
 
         let pubkey =
@@ -114,6 +208,7 @@ pub mod tests {
             VerifierExecuteMsg::Verify {
                 randomness,
                 pubkey,
+                scheme: DrandScheme::Chained,
                 raffle_id,
                 owner,
             },
@@ -128,4 +223,315 @@ pub mod tests {
                 .add_attribute("owner", "anyone")
         );
     }
+
+    fn hex_binary(hex: &str) -> Binary {
+        Binary::from(hex.from_hex().unwrap())
+    }
+
+    fn chained_randomness() -> DrandRandomness {
+        DrandRandomness {
+            round: 2098475,
+            signature: hex_binary("84bcb438f4505ef5282804c3d98a76b536970f9755004ea103fc15183c1b314a1e95582cb4dbaf3330272c72fe5675550ac6718ef53dfe18fca65c37f223a7dfbc1920a9c32dbf01a227290293a67fa4682dd266f717f5078829d83912926649"),
+            previous_signature: Some(hex_binary("b49ee4089fc510300b38d75ebba84576097bed61c171574acf2557f636c7144b471b57e18e5b0c3f8774e194344931c011873149d0db51fc70d22448bfc264d230be7ed6fcd3eb3b61fdc877d657dfa0d8ecaea6c1fa35f90bc84e88c1af17d4")),
+        }
+    }
+
+    fn quicknet_randomness() -> DrandRandomness {
+        DrandRandomness {
+            round: 1000,
+            signature: hex_binary("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: None,
+        }
+    }
+
+    #[test]
+    fn verify_unchained() {
+        let mut deps = mock_dependencies();
+        let randomness = DrandRandomness {
+            round: 1234,
+            signature: hex_binary("b304ea8c64ec87e8a03da787e0ac025dcac90a10371bf943d2eeef877cd67e6d0fb332ab132e6d7c294c5abd7366fe8d0dd93c608acbe652e466f047abb03dc008193a523352f825638bc975b30a848e4847228b0cfa7804f7f736bf12a3cf33"),
+            previous_signature: None,
+        };
+        let pubkey = hex_binary(UNCHAINED_HEX_PUBKEY);
+
+        // The signature only matches its own round
+        execute_verify(
+            deps.as_mut(),
+            DrandRandomness {
+                round: 1235,
+                ..randomness.clone()
+            },
+            pubkey.clone(),
+            DrandScheme::Unchained,
+            0,
+            "anyone".to_string(),
+        )
+        .unwrap_err();
+        // Unchained signatures can't be verified as chained
+        execute_verify(
+            deps.as_mut(),
+            DrandRandomness {
+                previous_signature: Some(hex_binary("b49ee4089fc510300b38d75ebba84576097bed61c171574acf2557f636c7144b471b57e18e5b0c3f8774e194344931c011873149d0db51fc70d22448bfc264d230be7ed6fcd3eb3b61fdc877d657dfa0d8ecaea6c1fa35f90bc84e88c1af17d4")),
+                ..randomness.clone()
+            },
+            pubkey.clone(),
+            DrandScheme::Chained,
+            0,
+            "anyone".to_string(),
+        )
+        .unwrap_err();
+
+        let response = execute_verify(
+            deps.as_mut(),
+            randomness,
+            pubkey,
+            DrandScheme::Unchained,
+            0,
+            "anyone".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            response,
+            Response::new()
+                .add_attribute("round", "1234")
+                .add_attribute("randomness", "cfesWrKlpc2Q4K/xd6L36qhHLBOtPJ4yu5rujqZ84zI=")
+                .add_attribute("raffle_id", "0")
+                .add_attribute("owner", "anyone")
+        );
+    }
+
+    #[test]
+    fn verify_quicknet() {
+        let mut deps = mock_dependencies();
+        let randomness = quicknet_randomness();
+        let pubkey = hex_binary(QUICKNET_HEX_PUBKEY);
+
+        execute_verify(
+            deps.as_mut(),
+            DrandRandomness {
+                round: 1001,
+                ..randomness.clone()
+            },
+            pubkey.clone(),
+            DrandScheme::Quicknet,
+            3,
+            "anyone".to_string(),
+        )
+        .unwrap_err();
+        // A G2 public key is not valid for the other schemes
+        execute_verify(
+            deps.as_mut(),
+            randomness.clone(),
+            pubkey.clone(),
+            DrandScheme::Unchained,
+            3,
+            "anyone".to_string(),
+        )
+        .unwrap_err();
+
+        let response = execute_verify(
+            deps.as_mut(),
+            randomness,
+            pubkey,
+            DrandScheme::Quicknet,
+            3,
+            "anyone".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            response,
+            Response::new()
+                .add_attribute("round", "1000")
+                .add_attribute("randomness", "/ikL7KEIcu8vsWTSqkRC3kVmGD7FHFb/PNYD2TDlT90=")
+                .add_attribute("raffle_id", "3")
+                .add_attribute("owner", "anyone")
+        );
+    }
+
+    #[test]
+    fn chained_needs_previous_signature() {
+        let mut deps = mock_dependencies();
+        let err = execute_verify(
+            deps.as_mut(),
+            DrandRandomness {
+                previous_signature: None,
+                ..chained_randomness()
+            },
+            hex_binary(HEX_PUBKEY),
+            DrandScheme::Chained,
+            0,
+            "anyone".to_string(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("The chained scheme needs the previous signature")
+        );
+    }
+
+    #[test]
+    fn verified_beacons_are_cached() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        execute_verify(
+            deps.as_mut(),
+            chained_randomness(),
+            hex_binary(HEX_PUBKEY),
+            DrandScheme::Chained,
+            0,
+            "anyone".to_string(),
+        )
+        .unwrap();
+        execute_verify(
+            deps.as_mut(),
+            quicknet_randomness(),
+            hex_binary(QUICKNET_HEX_PUBKEY),
+            DrandScheme::Quicknet,
+            1,
+            "anyone".to_string(),
+        )
+        .unwrap();
+
+        // A known round is answered from the cache, the signature isn't checked again
+        let response = execute_verify(
+            deps.as_mut(),
+            DrandRandomness {
+                round: 2098475,
+                previous_signature: None,
+                signature: Binary::from(b"not a signature"),
+            },
+            hex_binary(HEX_PUBKEY),
+            DrandScheme::Chained,
+            2,
+            "someone".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            response,
+            Response::new()
+                .add_attribute("round", "2098475")
+                .add_attribute("randomness", "iVgPamOa3WyQ3PPSIuNUFfidnuLNbvb8TyMTTN/6XR4=")
+                .add_attribute("raffle_id", "2")
+                .add_attribute("owner", "someone")
+        );
+        // The cache is bound to the network that signed the round
+        execute_verify(
+            deps.as_mut(),
+            DrandRandomness {
+                round: 1000,
+                ..chained_randomness()
+            },
+            hex_binary(HEX_PUBKEY),
+            DrandScheme::Chained,
+            2,
+            "someone".to_string(),
+        )
+        .unwrap_err();
+
+        let chained_network =
+            HexBinary::from(Sha256::digest(hex_binary(HEX_PUBKEY).as_slice()).to_vec());
+        let quicknet_network =
+            HexBinary::from(Sha256::digest(hex_binary(QUICKNET_HEX_PUBKEY).as_slice()).to_vec());
+        let chained_beacon = VerifiedBeacon {
+            round: 2098475,
+            randomness: Binary::from_base64("iVgPamOa3WyQ3PPSIuNUFfidnuLNbvb8TyMTTN/6XR4=")
+                .unwrap(),
+            pubkey_hash: chained_network.clone(),
+        };
+        let quicknet_beacon = VerifiedBeacon {
+            round: 1000,
+            randomness: Binary::from_base64("/ikL7KEIcu8vsWTSqkRC3kVmGD7FHFb/PNYD2TDlT90=")
+                .unwrap(),
+            pubkey_hash: quicknet_network.clone(),
+        };
+
+        let beacon: VerifiedBeacon = cosmwasm_std::from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                VerifierQueryMsg::Beacon {
+                    network: quicknet_network.clone(),
+                    round: 1000,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(beacon, quicknet_beacon);
+        query(
+            deps.as_ref(),
+            env.clone(),
+            VerifierQueryMsg::Beacon {
+                network: quicknet_network.clone(),
+                round: 1001,
+            },
+        )
+        .unwrap_err();
+        // Rounds are only known for the network that signed them
+        query(
+            deps.as_ref(),
+            env.clone(),
+            VerifierQueryMsg::Beacon {
+                network: chained_network.clone(),
+                round: 1000,
+            },
+        )
+        .unwrap_err();
+
+        let beacon: VerifiedBeacon = cosmwasm_std::from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                VerifierQueryMsg::LatestBeacon {
+                    network: chained_network.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(beacon, chained_beacon);
+        let beacon: VerifiedBeacon = cosmwasm_std::from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                VerifierQueryMsg::LatestBeacon {
+                    network: quicknet_network.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(beacon, quicknet_beacon);
+
+        let beacons: BeaconsResponse = cosmwasm_std::from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                VerifierQueryMsg::Beacons {
+                    network: chained_network,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(beacons.beacons, vec![chained_beacon]);
+
+        let beacons: BeaconsResponse = cosmwasm_std::from_json(
+            query(
+                deps.as_ref(),
+                env,
+                VerifierQueryMsg::Beacons {
+                    network: quicknet_network,
+                    start_after: Some(1000),
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(beacons.beacons.is_empty());
+    }
 }
@@ -1,9 +1,9 @@
 use cw_orch::{interface, prelude::*};
 
 pub const CONTRACT_ID: &str = "verifier";
-use crate::contract::VerifierExecuteMsg;
+use crate::contract::{VerifierExecuteMsg, VerifierQueryMsg};
 
-#[interface(Empty, VerifierExecuteMsg, Empty, Empty, id = CONTRACT_ID)]
+#[interface(Empty, VerifierExecuteMsg, VerifierQueryMsg, Empty, id = CONTRACT_ID)]
 pub struct Verifier;
 
 impl<Chain> Uploadable for Verifier<Chain> {
@@ -1,4 +1,5 @@
 pub mod contract;
+pub mod state;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod interface;
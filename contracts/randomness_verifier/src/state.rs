@@ -0,0 +1,5 @@
+use cw_storage_plus::Map;
+use randomness::VerifiedBeacon;
+
+/// Beacons that were already verified, by network public key hash and round
+pub const BEACONS: Map<(&[u8], u64), VerifiedBeacon> = Map::new("network_beacons");
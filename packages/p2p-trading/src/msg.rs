@@ -2,6 +2,7 @@ use crate::state::{Comment, CounterTradeInfo, TradeInfo, TradeState};
 use cosmwasm_std::{
     to_json_binary, Addr, Binary, Coin, CosmosMsg, Decimal, StdError, StdResult, Timestamp, WasmMsg,
 };
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
@@ -66,6 +67,19 @@ pub enum AddAssetAction {
     ToCounterTrade { trade_id: u64, counter_id: u64 },
 }
 
+/// Messages that can be sent along CW20 tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Adds the received tokens to a trade or a counter trade
+    AddAsset { action: AddAssetAction },
+    /// Buys a trade directly with the received tokens
+    DirectBuy {
+        trade_id: u64,
+        on_behalf_of: Option<String>,
+    },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 #[derive(cw_orch::ExecuteFns)]
@@ -79,6 +93,8 @@ pub enum ExecuteMsg {
         action: AddAssetAction,
         asset: AssetInfo,
     },
+    /// CW20 tokens can only be added to trades through this receive hook
+    Receive(Cw20ReceiveMsg),
     RemoveAssets {
         trade_id: u64,
         counter_id: Option<u64>,
@@ -115,15 +131,15 @@ pub enum ExecuteMsg {
 
     AddTokensWanted {
         trade_id: Option<u64>,
-        tokens_wanted: Vec<Coin>,
+        tokens_wanted: Vec<AssetInfo>,
     },
     RemoveTokensWanted {
         trade_id: u64,
-        tokens_wanted: Vec<Coin>,
+        tokens_wanted: Vec<AssetInfo>,
     },
     SetTokensWanted {
         trade_id: Option<u64>,
-        tokens_wanted: Vec<Coin>,
+        tokens_wanted: Vec<AssetInfo>,
     },
     FlushTokensWanted {
         trade_id: u64,
@@ -260,7 +276,7 @@ pub struct AdditionalTradeInfoResponse {
     pub owner_comment: Option<Comment>,
     pub trader_comment: Option<Comment>,
     pub nfts_wanted: Vec<Addr>,
-    pub tokens_wanted: Vec<Coin>, // The tokens wanted can only be a coin
+    pub tokens_wanted: Vec<AssetInfo>, // The tokens wanted can only be a coin or a CW20 token
     pub trade_preview: Option<AssetInfo>, // The preview can only be a CW1155 or a CW721 token.
 }
 
@@ -43,7 +43,7 @@ pub struct AdditionalTradeInfo {
     pub owner_comment: Option<Comment>,
     pub trader_comment: Option<Comment>,
     pub nfts_wanted: HashSet<Addr>,
-    pub tokens_wanted: Vec<Coin>, // The tokens wanted can only be a coin
+    pub tokens_wanted: Vec<AssetInfo>, // The tokens wanted can only be a coin or a CW20 token
     pub trade_preview: Option<AssetInfo>, // The preview can only be a CW1155 or a CW721 token.
 }
 
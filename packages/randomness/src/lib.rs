@@ -1,13 +1,27 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary};
+use cosmwasm_std::{Addr, Binary, HexBinary};
 
 #[cw_serde]
 pub struct DrandRandomness {
     pub round: u64,
-    pub previous_signature: Binary,
+    /// Only needed for the chained scheme
+    pub previous_signature: Option<Binary>,
     pub signature: Binary,
 }
 
+/// Signature scheme of the drand network the randomness comes from
+#[cw_serde]
+#[derive(Default)]
+pub enum DrandScheme {
+    /// `pedersen-bls-chained` : G1 public key, G2 signatures of the previous signature and the round
+    #[default]
+    Chained,
+    /// `pedersen-bls-unchained` : G1 public key, G2 signatures of the round only
+    Unchained,
+    /// `bls-unchained-g1-rfc9380` (quicknet) : G2 public key, G1 signatures of the round only
+    Quicknet,
+}
+
 #[cw_serde]
 pub struct Randomness {
     pub randomness: [u8; 32],
@@ -21,6 +35,8 @@ pub enum VerifierExecuteMsg {
     Verify {
         randomness: DrandRandomness,
         pubkey: Binary,
+        #[serde(default)]
+        scheme: DrandScheme,
         raffle_id: u64,
         owner: String,
     },
@@ -28,4 +44,33 @@ pub enum VerifierExecuteMsg {
 
 #[cw_serde]
 #[derive(QueryResponses, cw_orch::QueryFns)]
-pub enum VerifierQueryMsg {}
+pub enum VerifierQueryMsg {
+    /// Verified beacon of a drand round.
+    /// The network is the sha256 of its public key, rounds of different networks are unrelated
+    #[returns(VerifiedBeacon)]
+    Beacon { network: HexBinary, round: u64 },
+    /// Verified beacon of the network with the highest round
+    #[returns(VerifiedBeacon)]
+    LatestBeacon { network: HexBinary },
+    /// Verified beacons of the network, by ascending round
+    #[returns(BeaconsResponse)]
+    Beacons {
+        network: HexBinary,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+/// A drand beacon whose signature was checked by the verifier
+#[cw_serde]
+pub struct VerifiedBeacon {
+    pub round: u64,
+    pub randomness: Binary,
+    /// sha256 of the public key of the drand network that signed the beacon
+    pub pubkey_hash: HexBinary,
+}
+
+#[cw_serde]
+pub struct BeaconsResponse {
+    pub beacons: Vec<VerifiedBeacon>,
+}
@@ -24,21 +24,36 @@ pub struct Cw721Coin {
     pub token_id: String,
 }
 
+#[cw_serde]
+pub struct Cw20Coin {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Cw1155Coin {
+    pub address: String,
+    pub token_id: String,
+    pub value: Uint128,
+}
+
 #[cfg(feature = "sg")]
 #[cw_serde]
 pub enum AssetInfo {
     Cw721Coin(Cw721Coin),
     Coin(Coin),
     Sg721Token(Sg721Token),
+    Cw20Coin(Cw20Coin),
+    Cw1155Coin(Cw1155Coin),
 }
 
 impl AssetInfo {
     pub fn overlaps(&self, asset: &AssetInfo) -> bool {
         match self {
-            AssetInfo::Coin(_) => false,
+            AssetInfo::Coin(_) | AssetInfo::Cw20Coin(_) => false,
             AssetInfo::Sg721Token(Sg721Token { address, token_id })
             | AssetInfo::Cw721Coin(Cw721Coin { address, token_id }) => match asset {
-                AssetInfo::Coin(_) => false,
+                AssetInfo::Coin(_) | AssetInfo::Cw20Coin(_) => false,
                 AssetInfo::Sg721Token(Sg721Token {
                     address: address1,
                     token_id: token_id1,
@@ -47,14 +62,30 @@ impl AssetInfo {
                     address: address1,
                     token_id: token_id1,
                 }) => address == address1 && token_id1 == token_id,
+                AssetInfo::Cw1155Coin(_) => false,
+            },
+            // For Cw1155 we only match the address and the token_id
+            AssetInfo::Cw1155Coin(Cw1155Coin {
+                address, token_id, ..
+            }) => match asset {
+                AssetInfo::Cw1155Coin(Cw1155Coin {
+                    address: address1,
+                    token_id: token_id1,
+                    ..
+                }) => address == address1 && token_id1 == token_id,
+                _ => false,
             },
         }
     }
     pub fn debug_str(&self) -> String {
         match self {
             AssetInfo::Coin(c) => c.denom.to_string(),
+            AssetInfo::Cw20Coin(c) => c.address.to_string(),
             AssetInfo::Sg721Token(Sg721Token { address, token_id })
-            | AssetInfo::Cw721Coin(Cw721Coin { address, token_id }) => {
+            | AssetInfo::Cw721Coin(Cw721Coin { address, token_id })
+            | AssetInfo::Cw1155Coin(Cw1155Coin {
+                address, token_id, ..
+            }) => {
                 format!("{}-{}", address, token_id)
             }
         }
@@ -66,6 +97,8 @@ impl AssetInfo {
 pub enum AssetInfo {
     Cw721Coin(Cw721Coin),
     Coin(Coin),
+    Cw20Coin(Cw20Coin),
+    Cw1155Coin(Cw1155Coin),
 }
 
 impl AssetInfo {
@@ -80,12 +113,27 @@ impl AssetInfo {
         })
     }
 
+    pub fn cw20(amount: u128, address: &str) -> Self {
+        AssetInfo::Cw20Coin(Cw20Coin {
+            address: address.to_string(),
+            amount: Uint128::from(amount),
+        })
+    }
+
     pub fn cw721(address: &str, token_id: &str) -> Self {
         AssetInfo::Cw721Coin(Cw721Coin {
             address: address.to_string(),
             token_id: token_id.to_string(),
         })
     }
+    pub fn cw1155(address: &str, token_id: &str, value: u128) -> Self {
+        AssetInfo::Cw1155Coin(Cw1155Coin {
+            address: address.to_string(),
+            token_id: token_id.to_string(),
+            value: Uint128::from(value),
+        })
+    }
+
     #[cfg(feature = "sg")]
     pub fn sg721(address: &str, token_id: &str) -> Self {
         AssetInfo::Sg721Token(Sg721Token {
@@ -29,7 +29,7 @@ pub fn main() -> anyhow::Result<()> {
             raffle_id: RAFFLE_ID,
             randomness: DrandRandomness {
                 round: 0,
-                previous_signature: Binary::from(b"dummy"),
+                previous_signature: None,
                 signature: Binary::from(b"dummy"),
             },
         })?,
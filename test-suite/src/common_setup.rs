@@ -3,6 +3,7 @@ pub mod contract_boxes;
 pub mod create_raffles;
 pub mod helpers;
 pub mod msg;
+pub mod nois_proxy;
 pub mod setup_accounts_and_block;
 pub mod setup_loan;
 pub mod setup_minter;
@@ -27,6 +27,16 @@ pub fn contract_randomness_verifier() -> Box<dyn Contract<StargazeMsgWrapper>> {
     Box::new(contract)
 }
 
+pub fn contract_nois_proxy() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new_with_empty(
+        super::nois_proxy::execute,
+        super::nois_proxy::instantiate,
+        super::nois_proxy::query,
+    )
+    .with_sudo_empty(super::nois_proxy::sudo);
+    Box::new(contract)
+}
+
 pub fn contract_vending_factory() -> Box<dyn Contract<StargazeMsgWrapper>> {
     let contract = ContractWrapper::new(
         vending_factory::contract::execute,
@@ -66,6 +76,15 @@ pub fn contract_nft_loans() -> Box<dyn Contract<StargazeMsgWrapper>> {
     Box::new(contract)
 }
 
+pub fn contract_cw1155() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new_with_empty(
+        cw1155_base::contract::execute,
+        cw1155_base::contract::instantiate,
+        cw1155_base::contract::query,
+    );
+    Box::new(contract)
+}
+
 pub fn contract_cw20() -> Box<dyn Contract<StargazeMsgWrapper>> {
     let contract = ContractWrapper::new_with_empty(
         cw20_base::contract::execute,
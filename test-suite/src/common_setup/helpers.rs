@@ -1,9 +1,20 @@
 use anyhow::Error;
-use cosmwasm_std::{Timestamp, Uint128};
-use cw_multi_test::AppResponse;
+use cosmwasm_std::{coins, Addr, Timestamp, Uint128};
+use cw20::Cw20Coin;
+use cw_multi_test::{AppResponse, Executor};
+use nft_loans_nc::{msg::ExecuteMsg as LoanExecuteMsg, state::LoanTerms};
+use sg_std::NATIVE_DENOM;
+use utils::state::{AssetInfo, Sg721Token};
 
-use super::app::StargazeApp;
-use crate::common_setup::setup_minter::common::constants::TREASURY_ADDR;
+use super::{
+    app::StargazeApp,
+    contract_boxes::{contract_cw1155, contract_cw20},
+    setup_accounts_and_block::setup_accounts,
+    setup_loan::{configure_loan_assets, proper_loan_instantiate, NATIVE_LOAN_LISTING_AMT},
+};
+use crate::common_setup::setup_minter::common::constants::{
+    OWNER_ADDR, SG721_CONTRACT, TREASURY_ADDR,
+};
 
 pub fn setup_block_time(router: &mut StargazeApp, nanos: u64, height: Option<u64>, chain_id: &str) {
     let mut block = router.block_info();
@@ -44,3 +55,132 @@ pub fn assert_treasury_balance(app: &StargazeApp, denom: &str, amount: u128) {
     let treasury_balance = app.wrap().query_balance(TREASURY_ADDR, denom.to_string());
     assert_eq!(treasury_balance.unwrap().amount, Uint128::new(amount));
 }
+
+pub fn instantiate_cw20(app: &mut StargazeApp, initial_balances: Vec<Cw20Coin>) -> Addr {
+    let code_id = app.store_code(contract_cw20());
+
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked(OWNER_ADDR),
+        &cw20_base::msg::InstantiateMsg {
+            decimals: 6,
+            initial_balances,
+            marketing: None,
+            mint: None,
+            symbol: "CWCW".to_string(),
+            name: "cw20".to_string(),
+        },
+        &[],
+        "cw20_example",
+        None,
+    )
+    .unwrap()
+}
+
+/// Instantiates a cw1155 contract and mints `value` tokens of each `token_id` to the associated address
+pub fn instantiate_cw1155(app: &mut StargazeApp, mints: Vec<(Addr, &str, u128)>) -> Addr {
+    let code_id = app.store_code(contract_cw1155());
+
+    let cw1155_addr = app
+        .instantiate_contract(
+            code_id,
+            Addr::unchecked(OWNER_ADDR),
+            &cw1155_base::msg::InstantiateMsg {
+                minter: OWNER_ADDR.to_string(),
+            },
+            &[],
+            "cw1155_example",
+            None,
+        )
+        .unwrap();
+
+    for (to, token_id, value) in mints {
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            cw1155_addr.clone(),
+            &cw1155::Cw1155ExecuteMsg::Mint {
+                to: to.to_string(),
+                token_id: token_id.to_string(),
+                value: value.into(),
+                msg: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    cw1155_addr
+}
+
+pub fn cw1155_balance(app: &StargazeApp, token: &Addr, owner: &Addr, token_id: &str) -> Uint128 {
+    let res: cw1155::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token.clone(),
+            &cw1155::Cw1155QueryMsg::Balance {
+                owner: owner.to_string(),
+                token_id: token_id.to_string(),
+            },
+        )
+        .unwrap();
+    res.balance
+}
+
+pub fn cw20_balance(app: &StargazeApp, token: &Addr, address: &Addr) -> Uint128 {
+    let res: cw20::BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token.clone(),
+            &cw20_base::msg::QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    res.balance
+}
+
+/// Lists and starts loan_id 0 with the given terms
+/// Returns the loan contract and the accounts used (borrower, lender, unused account)
+pub fn setup_started_loan(terms: LoanTerms) -> (StargazeApp, Addr, Addr, Addr, Addr) {
+    let (mut app, loan_addr, factory_addr) = proper_loan_instantiate();
+    let (owner_addr, other_addr, lender_addr) = setup_accounts(&mut app);
+    configure_loan_assets(&mut app, owner_addr.clone(), factory_addr);
+
+    let principle = terms.principle_amount().unwrap().u128();
+    app.execute_contract(
+        owner_addr.clone(),
+        loan_addr.clone(),
+        &LoanExecuteMsg::ListCollaterals {
+            tokens: vec![AssetInfo::Sg721Token(Sg721Token {
+                address: SG721_CONTRACT.to_string(),
+                token_id: "63".to_string(),
+            })],
+            terms: Some(terms),
+            comment: None,
+            loan_preview: None,
+        },
+        &coins(NATIVE_LOAN_LISTING_AMT, NATIVE_DENOM),
+    )
+    .unwrap();
+    app.execute_contract(
+        lender_addr.clone(),
+        loan_addr.clone(),
+        &LoanExecuteMsg::AcceptLoan {
+            borrower: owner_addr.to_string(),
+            loan_id: 0,
+            comment: None,
+        },
+        &coins(principle, NATIVE_DENOM),
+    )
+    .unwrap();
+
+    (app, loan_addr, owner_addr, lender_addr, other_addr)
+}
+
+pub fn balance(app: &StargazeApp, addr: &str) -> u128 {
+    app.wrap()
+        .query_balance(addr, NATIVE_DENOM)
+        .unwrap()
+        .amount
+        .u128()
+}
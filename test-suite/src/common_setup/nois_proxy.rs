@@ -0,0 +1,117 @@
+//! A local stand-in for the Nois proxy contract.
+//! It stores the randomness requests it receives and answers them when the test publishes a beacon with `sudo`
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    ensure, to_json_binary, Addr, Binary, Coin, Deps, DepsMut, Env, HexBinary, MessageInfo, Order,
+    Response, StdError, StdResult, Timestamp, WasmMsg,
+};
+use cw_storage_plus::{Item, Map};
+use nois::{NoisCallback, ProxyExecuteMsg};
+
+const PRICE: Item<Coin> = Item::new("price");
+/// Pending jobs, indexed by requester and job id, with the time after which they can be answered
+const JOBS: Map<(&Addr, &str), Timestamp> = Map::new("jobs");
+
+#[cw_serde]
+pub struct MockNoisInstantiateMsg {
+    pub price: Coin,
+}
+
+#[cw_serde]
+pub enum MockNoisSudoMsg {
+    /// Publishes `randomness` at the current block time.
+    /// Every job that can be answered receives it
+    Publish { randomness: HexBinary },
+}
+
+#[cw_serde]
+pub enum MockNoisQueryMsg {
+    Jobs {},
+}
+
+#[cw_serde]
+pub struct NoisJob {
+    pub requester: Addr,
+    pub job_id: String,
+    pub after: Timestamp,
+}
+
+/// The message the real proxy sends back to its customers
+#[cw_serde]
+enum ReceiverExecuteMsg {
+    NoisReceive { callback: NoisCallback },
+}
+
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: MockNoisInstantiateMsg,
+) -> StdResult<Response> {
+    PRICE.save(deps.storage, &msg.price)?;
+    Ok(Response::new())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ProxyExecuteMsg,
+) -> StdResult<Response> {
+    let (after, job_id) = match msg {
+        ProxyExecuteMsg::GetNextRandomness { job_id } => (env.block.time, job_id),
+        ProxyExecuteMsg::GetRandomnessAfter { after, job_id } => (after, job_id),
+        _ => return Err(StdError::generic_err("Not supported by the mock proxy")),
+    };
+    let price = PRICE.load(deps.storage)?;
+    ensure!(
+        info.funds.contains(&price),
+        StdError::generic_err("Insufficient payment for the randomness")
+    );
+    JOBS.save(deps.storage, (&info.sender, &job_id), &after)?;
+    Ok(Response::new().add_attribute("action", "get_randomness_after"))
+}
+
+pub fn sudo(deps: DepsMut, env: Env, msg: MockNoisSudoMsg) -> StdResult<Response> {
+    let MockNoisSudoMsg::Publish { randomness } = msg;
+    let ready = pending_jobs(deps.as_ref())?
+        .into_iter()
+        .filter(|job| job.after <= env.block.time)
+        .collect::<Vec<_>>();
+
+    let mut callbacks = vec![];
+    for job in ready {
+        JOBS.remove(deps.storage, (&job.requester, &job.job_id));
+        callbacks.push(WasmMsg::Execute {
+            contract_addr: job.requester.to_string(),
+            msg: to_json_binary(&ReceiverExecuteMsg::NoisReceive {
+                callback: NoisCallback {
+                    job_id: job.job_id,
+                    published: env.block.time,
+                    randomness: randomness.clone(),
+                },
+            })?,
+            funds: vec![],
+        });
+    }
+    Ok(Response::new().add_messages(callbacks))
+}
+
+pub fn query(deps: Deps, _env: Env, msg: MockNoisQueryMsg) -> StdResult<Binary> {
+    match msg {
+        MockNoisQueryMsg::Jobs {} => to_json_binary(&pending_jobs(deps)?),
+    }
+}
+
+fn pending_jobs(deps: Deps) -> StdResult<Vec<NoisJob>> {
+    JOBS.range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((requester, job_id), after) = item?;
+            Ok(NoisJob {
+                requester,
+                job_id,
+                after,
+            })
+        })
+        .collect()
+}
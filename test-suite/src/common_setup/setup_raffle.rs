@@ -11,14 +11,19 @@ use super::{
 };
 use crate::common_setup::{
     contract_boxes::{
-        contract_raffles, contract_randomness_verifier, contract_sg721_base,
+        contract_nois_proxy, contract_raffles, contract_randomness_verifier, contract_sg721_base,
         contract_vending_factory, contract_vending_minter, custom_mock_app,
     },
+    nois_proxy::MockNoisInstantiateMsg,
     setup_minter::common::constants::{OWNER_ADDR, RAFFLE_NAME},
 };
-use cosmwasm_std::{coin, Addr, Binary, Coin, Decimal, Empty, Uint128};
+use cosmwasm_std::{coin, Addr, Binary, Coin, Decimal, Empty, Timestamp, Uint128};
 use cw_multi_test::Executor;
-use raffles::msg::{DrandConfig, InstantiateMsg};
+use raffles::{
+    msg::{DrandConfig, ExecuteMsg, InstantiateMsg},
+    state::{NoisProxy, RandomnessSource},
+};
+use randomness::DrandScheme;
 use rustc_serialize::hex::FromHex;
 use sg_std::NATIVE_DENOM;
 use vending_factory::state::{ParamsExtension, VendingMinterParams};
@@ -27,6 +32,11 @@ pub const HEX_PUBKEY: &str = "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce
 pub const DRAND_URL: &str = "https://api.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/public/latest";
 /// One Hour
 pub const DRAND_TIMEOUT: u64 = 3600u64;
+/// drand mainnet chain timing
+pub const DRAND_GENESIS_TIME: u64 = 1595431050u64;
+pub const DRAND_PERIOD: u64 = 30u64;
+/// Price of a randomness request to the mock Nois proxy
+pub const NOIS_PRICE: u128 = 50u128;
 
 pub fn proper_raffle_instantiate() -> (StargazeApp, RaffleContracts) {
     proper_raffle_instantiate_precise(None)
@@ -107,6 +117,7 @@ pub fn proper_raffle_instantiate_precise(
                 minimum_raffle_duration: None,
                 max_ticket_number,
                 raffle_fee: Decimal::percent(50),
+                randomness_provider_fee: None,
                 creation_coins: vec![
                     coin(CREATION_FEE_AMNT_NATIVE, NATIVE_DENOM.to_string()),
                     coin(CREATION_FEE_AMNT_STARS, "ustars".to_string()),
@@ -114,6 +125,9 @@ pub fn proper_raffle_instantiate_precise(
                 .into(),
                 fee_discounts: vec![],
                 drand_config: default_drand_config(&randomness_verifier_addr),
+                randomness_source: None,
+                randomness_deadline: None,
+                referral_fee: None,
             },
             &[],
             "raffle",
@@ -131,6 +145,54 @@ pub fn proper_raffle_instantiate_precise(
     )
 }
 
+/// Raffle contract drawing its randomness from a local mock of the Nois proxy.
+/// Returns the address of the mock proxy along with the contracts
+pub fn nois_raffle_instantiate() -> (StargazeApp, RaffleContracts, Addr) {
+    let (mut app, contracts) = proper_raffle_instantiate();
+    let owner = Addr::unchecked(OWNER_ADDR);
+
+    let nois_proxy_code_id = app.store_code(contract_nois_proxy());
+    let nois_proxy = app
+        .instantiate_contract(
+            nois_proxy_code_id,
+            owner.clone(),
+            &MockNoisInstantiateMsg {
+                price: coin(NOIS_PRICE, NATIVE_DENOM),
+            },
+            &[],
+            "nois-proxy",
+            None,
+        )
+        .unwrap();
+
+    app.execute_contract(
+        owner,
+        contracts.raffle.clone(),
+        &ExecuteMsg::UpdateConfig {
+            name: None,
+            owner: None,
+            fee_addr: None,
+            minimum_raffle_duration: None,
+            max_tickets_per_raffle: None,
+            raffle_fee: None,
+            randomness_provider_fee: None,
+            creation_coins: None,
+            fee_discounts: None,
+            drand_config: None,
+            randomness_source: Some(RandomnessSource::Nois(NoisProxy {
+                price: coin(NOIS_PRICE, NATIVE_DENOM),
+                address: nois_proxy.clone(),
+            })),
+            randomness_deadline: None,
+            referral_fee: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    (app, contracts, nois_proxy)
+}
+
 pub fn raffle_template_code_ids(router: &mut StargazeApp) -> RaffleCodeIds {
     let raffle_code_id = router.store_code(contract_raffles());
     let factory_code_id = router.store_code(contract_vending_factory());
@@ -152,7 +214,10 @@ pub fn default_drand_config(verifier: &Addr) -> DrandConfig {
         random_pubkey: Binary::from(HEX_PUBKEY.from_hex().unwrap()),
         drand_url: DRAND_URL.to_string(),
         verify_signature_contract: verifier.clone(),
+        scheme: DrandScheme::Chained,
         // ONE HOUR TIMEOUT
         timeout: DRAND_TIMEOUT,
+        genesis_time: Timestamp::from_seconds(DRAND_GENESIS_TIME),
+        period: DRAND_PERIOD,
     }
 }
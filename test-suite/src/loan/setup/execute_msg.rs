@@ -61,6 +61,7 @@ pub fn instantate_loan_contract(
 pub fn create_loan_function(params: CreateLoanParams) -> Result<AppResponse, anyhow_error> {
     let owner_addr = params.owner_addr;
     let loans_contract_addr = params.loan_contract_addr;
+    let duration = params.duration;
 
     params.app.execute_contract(
         owner_addr.clone(),
@@ -77,12 +78,13 @@ pub fn create_loan_function(params: CreateLoanParams) -> Result<AppResponse, any
                 }),
             ],
             terms: Some(LoanTerms {
-                principle: Coin {
+                principle: AssetInfo::Coin(Coin {
                     denom: NATIVE_DENOM.to_string(),
                     amount: Uint128::new(100),
-                },
+                }),
                 interest: Uint128::new(50),
-                duration_in_blocks: 15,
+                duration,
+                pro_rata_interest: false,
             }),
             comment: Some("be water, my friend".to_string()),
             loan_preview: None,
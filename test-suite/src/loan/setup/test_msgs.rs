@@ -1,4 +1,5 @@
 use cosmwasm_std::{Addr, Decimal};
+use nft_loans_nc::state::LoanDuration;
 
 pub struct InstantiateParams<'a> {
     pub app: &'a mut crate::common_setup::app::StargazeApp,
@@ -12,4 +13,5 @@ pub struct CreateLoanParams<'a> {
     pub app: &'a mut crate::common_setup::app::StargazeApp,
     pub loan_contract_addr: Addr,
     pub owner_addr: Addr,
+    pub duration: LoanDuration,
 }
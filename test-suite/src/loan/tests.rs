@@ -4,3 +4,9 @@ pub mod accept_loan;
 pub mod init;
 pub mod config_coverage;
 pub mod collection_offer;
+pub mod cw20_loans;
+pub mod cw1155_loans;
+pub mod partial_repay;
+pub mod refinance;
+pub mod extension;
+pub mod time_loans;
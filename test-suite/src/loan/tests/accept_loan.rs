@@ -6,7 +6,7 @@ mod tests {
     use nft_loans_nc::{
         error::ContractError,
         msg::{ExecuteMsg, InstantiateMsg},
-        state::{CollateralInfo, LoanState, LoanTerms},
+        state::{CollateralInfo, LoanDuration, LoanState, LoanTerms},
     };
     use sg721::CollectionInfo;
     use sg_std::NATIVE_DENOM;
@@ -252,12 +252,13 @@ mod tests {
                         }),
                     ],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("Real living is living for others".to_string()),
                     loan_preview: None,
@@ -2,8 +2,9 @@ use crate::common_setup::app::StargazeApp;
 use cosmwasm_std::{coin, coins, Addr, BlockInfo, Coin, Decimal, Empty, Timestamp, Uint128};
 use cw_multi_test::{BankSudo, Executor, SudoMsg};
 use nft_loans_nc::{
+    error::ContractError,
     msg::{ExecuteMsg, InstantiateMsg, MultipleCollectionOffersResponse, QueryMsg},
-    state::{CollateralInfo, LoanState, LoanTerms},
+    state::{CollateralInfo, LoanDuration, LoanState, LoanTerms, TraitConstraint},
 };
 use sg721::CollectionInfo;
 use sg_std::NATIVE_DENOM;
@@ -18,6 +19,7 @@ use crate::common_setup::{
         contract_nft_loans, contract_sg721_base, contract_vending_factory, contract_vending_minter,
         custom_mock_app,
     },
+    helpers::assert_error,
     setup_minter::common::constants::OWNER_ADDR,
 };
 
@@ -271,16 +273,19 @@ pub fn collection_offer_works() {
             Addr::unchecked(OFFERER_ADDR),
             nft_loan_addr.clone(),
             &ExecuteMsg::MakeCollectionOffer {
-                collection: nft.to_string(),
+                collections: vec![nft.to_string()],
+                token_count: None,
                 terms: LoanTerms {
-                    principle: Coin {
+                    principle: AssetInfo::Coin(Coin {
                         denom: NATIVE_DENOM.to_string(),
                         amount: Uint128::new(100),
-                    },
+                    }),
                     interest: Uint128::new(50),
-                    duration_in_blocks: 15,
+                    duration: LoanDuration::Blocks(15),
+                    pro_rata_interest: false,
                 },
                 comment: None,
+                traits: None,
             },
             &[Coin {
                 denom: NATIVE_DENOM.to_string(),
@@ -295,10 +300,10 @@ pub fn collection_offer_works() {
             nft_loan_addr.clone(),
             &ExecuteMsg::AcceptCollectionOffer {
                 collection_offer_id: 1,
-                token: AssetInfo::Sg721Token(Sg721Token {
+                tokens: vec![AssetInfo::Sg721Token(Sg721Token {
                     address: nft.to_string(),
                     token_id: "63".to_string(),
-                }),
+                })],
             },
             &coins(LISTING_FEE_NATIVE, NATIVE_DENOM),
         )
@@ -335,16 +340,19 @@ pub fn query_works() {
             Addr::unchecked(OFFERER_ADDR),
             nft_loan_addr.clone(),
             &ExecuteMsg::MakeCollectionOffer {
-                collection: nft.to_string(),
+                collections: vec![nft.to_string()],
+                token_count: None,
                 terms: LoanTerms {
-                    principle: Coin {
+                    principle: AssetInfo::Coin(Coin {
                         denom: NATIVE_DENOM.to_string(),
                         amount: Uint128::new(100),
-                    },
+                    }),
                     interest: Uint128::new(50),
-                    duration_in_blocks: 15,
+                    duration: LoanDuration::Blocks(15),
+                    pro_rata_interest: false,
                 },
                 comment: None,
+                traits: None,
             },
             &[Coin {
                 denom: NATIVE_DENOM.to_string(),
@@ -358,16 +366,19 @@ pub fn query_works() {
             Addr::unchecked(OFFERER_ADDR),
             nft_loan_addr.clone(),
             &ExecuteMsg::MakeCollectionOffer {
-                collection: nft_1.to_string(),
+                collections: vec![nft_1.to_string()],
+                token_count: None,
                 terms: LoanTerms {
-                    principle: Coin {
+                    principle: AssetInfo::Coin(Coin {
                         denom: NATIVE_DENOM.to_string(),
                         amount: Uint128::new(100),
-                    },
+                    }),
                     interest: Uint128::new(50),
-                    duration_in_blocks: 15,
+                    duration: LoanDuration::Blocks(15),
+                    pro_rata_interest: false,
                 },
                 comment: None,
+                traits: None,
             },
             &[Coin {
                 denom: NATIVE_DENOM.to_string(),
@@ -393,8 +404,8 @@ pub fn query_works() {
     assert_eq!(res.next_offer, None);
     assert_eq!(res.offers.len(), 1);
     assert_eq!(
-        res.offers[0].collection_offer_info.collection,
-        nft.to_string()
+        res.offers[0].collection_offer_info.collections,
+        vec![nft.clone()]
     );
 
     let res: MultipleCollectionOffersResponse = app
@@ -412,7 +423,189 @@ pub fn query_works() {
     assert_eq!(res.next_offer, None);
     assert_eq!(res.offers.len(), 1);
     assert_eq!(
-        res.offers[0].collection_offer_info.collection,
-        nft_1.to_string()
+        res.offers[0].collection_offer_info.collections,
+        vec![nft_1.clone()]
+    );
+}
+
+#[test]
+pub fn basket_collection_offer() {
+    let (mut app, nft_loan_addr, factory_addr, _minter, nft) = proper_instantiate();
+    let (_minter_1, nft_1) =
+        create_nft_collection(&mut app, factory_addr.clone(), nft_loan_addr.clone());
+
+    // Any 2 tokens from one of the 2 collections
+    app.execute_contract(
+        Addr::unchecked(OFFERER_ADDR),
+        nft_loan_addr.clone(),
+        &ExecuteMsg::MakeCollectionOffer {
+            collections: vec![nft.to_string(), nft_1.to_string()],
+            token_count: Some(2),
+            terms: LoanTerms {
+                principle: AssetInfo::coin(100, NATIVE_DENOM),
+                interest: Uint128::new(50),
+                duration: LoanDuration::Blocks(15),
+                pro_rata_interest: false,
+            },
+            comment: None,
+            traits: None,
+        },
+        &coins(100, NATIVE_DENOM),
+    )
+    .unwrap();
+
+    // The offer can be found from both collections
+    for collection in [&nft, &nft_1] {
+        let res: MultipleCollectionOffersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &nft_loan_addr,
+                &QueryMsg::CollectionOffers {
+                    collection: collection.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(res.offers[0].collection_offer_info.token_count, 2);
+    }
+
+    let token = |address: &Addr, token_id: &str| {
+        AssetInfo::Sg721Token(Sg721Token {
+            address: address.to_string(),
+            token_id: token_id.to_string(),
+        })
+    };
+    let mut accept = |tokens: Vec<AssetInfo>| {
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            nft_loan_addr.clone(),
+            &ExecuteMsg::AcceptCollectionOffer {
+                collection_offer_id: 1,
+                tokens,
+            },
+            &coins(LISTING_FEE_NATIVE, NATIVE_DENOM),
+        )
+    };
+
+    assert_error(
+        accept(vec![token(&nft, "63")]),
+        ContractError::WrongTokenCount {
+            expected: 2,
+            actual: 1,
+        }
+        .to_string(),
+    );
+    assert_error(
+        accept(vec![token(&nft, "63"), token(&nft, "63")]),
+        ContractError::DuplicateToken {}.to_string(),
+    );
+    assert_error(
+        accept(vec![token(&nft, "63"), token(&nft_loan_addr, "63")]),
+        ContractError::CollectionNotInOffer {
+            collection: nft_loan_addr.to_string(),
+        }
+        .to_string(),
+    );
+    accept(vec![token(&nft, "63"), token(&nft_1, "65")]).unwrap();
+
+    let collateral: CollateralInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &nft_loan_addr,
+            &QueryMsg::CollateralInfo {
+                borrower: OWNER_ADDR.to_string(),
+                loan_id: 0,
+            },
+        )
+        .unwrap();
+    assert_eq!(collateral.state, LoanState::Started);
+    assert_eq!(collateral.associated_assets.len(), 2);
+
+    // The offer is removed from both collections
+    for collection in [&nft, &nft_1] {
+        let res: MultipleCollectionOffersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &nft_loan_addr,
+                &QueryMsg::CollectionOffers {
+                    collection: collection.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(res.offers.is_empty());
+    }
+}
+
+#[test]
+pub fn trait_collection_offer() {
+    let (mut app, nft_loan_addr, _factory_addr, _minter, nft) = proper_instantiate();
+
+    let traits = vec![TraitConstraint {
+        trait_type: "background".to_string(),
+        value: "gold".to_string(),
+    }];
+    app.execute_contract(
+        Addr::unchecked(OFFERER_ADDR),
+        nft_loan_addr.clone(),
+        &ExecuteMsg::MakeCollectionOffer {
+            collections: vec![nft.to_string()],
+            token_count: None,
+            terms: LoanTerms {
+                principle: AssetInfo::coin(100, NATIVE_DENOM),
+                interest: Uint128::new(50),
+                duration: LoanDuration::Blocks(15),
+                pro_rata_interest: false,
+            },
+            comment: None,
+            traits: Some(traits.clone()),
+        },
+        &coins(100, NATIVE_DENOM),
+    )
+    .unwrap();
+
+    let res: MultipleCollectionOffersResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &nft_loan_addr,
+            &QueryMsg::CollectionOffers {
+                collection: nft.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.offers[0].collection_offer_info.traits, traits);
+
+    // Vending minter tokens don't have any on-chain metadata, so they can't match the traits
+    let res = app.execute_contract(
+        Addr::unchecked(OWNER_ADDR),
+        nft_loan_addr.clone(),
+        &ExecuteMsg::AcceptCollectionOffer {
+            collection_offer_id: 1,
+            tokens: vec![AssetInfo::Sg721Token(Sg721Token {
+                address: nft.to_string(),
+                token_id: "63".to_string(),
+            })],
+        },
+        &coins(LISTING_FEE_NATIVE, NATIVE_DENOM),
     );
+    assert_error(res, ContractError::TraitsNotMatched {}.to_string());
+
+    // The offer is still available
+    let res: MultipleCollectionOffersResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &nft_loan_addr,
+            &QueryMsg::CollectionOffers {
+                collection: nft.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.offers.len(), 1);
 }
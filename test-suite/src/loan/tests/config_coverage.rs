@@ -7,7 +7,7 @@ mod tests {
     use nft_loans_nc::{
         error::ContractError,
         msg::{ExecuteMsg, QueryMsg as LoanQueryMsg},
-        state::Config,
+        state::{Config, LoanDuration},
     };
     use utils::state::{Locks, SudoMsg as LoanSudoMsg};
 
@@ -194,6 +194,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
             // creation_fee: vec![coin(4, NATIVE_DENOM)],
             // ticket_price: None,
             // max_ticket_per_addr: None,
@@ -219,6 +220,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
 
         // confirm loans cannot be made,
@@ -234,6 +236,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
     }
 
@@ -246,6 +249,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
 
@@ -264,6 +268,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: Addr::unchecked("owner"),
+            duration: LoanDuration::Blocks(15),
             // creation_fee: vec![coin(4, NATIVE_DENOM)],
             // ticket_price: None,
             // max_ticket_per_addr: None,
@@ -280,6 +285,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: Addr::unchecked("owner"),
+            duration: LoanDuration::Blocks(15),
             // msg_senders: vec![one.clone()],
             // raffle_id: 0,
             // num_tickets: 1,
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, coins, Addr, Uint128};
+    use cw1155::Cw1155ExecuteMsg;
+    use cw_multi_test::{AppResponse, Executor};
+    use nft_loans_nc::{
+        error::ContractError,
+        msg::ExecuteMsg,
+        state::{LoanDuration, LoanTerms},
+    };
+    use sg_std::NATIVE_DENOM;
+    use utils::state::AssetInfo;
+
+    use crate::common_setup::{
+        app::StargazeApp,
+        helpers::{assert_error, cw1155_balance, instantiate_cw1155},
+        setup_accounts_and_block::setup_accounts,
+        setup_loan::{proper_loan_instantiate, NATIVE_LOAN_LISTING_AMT},
+    };
+
+    const TOKEN_ID: &str = "gem";
+    const COLLATERAL_VALUE: u128 = 5;
+    const PRINCIPLE: u128 = 100;
+    const INTEREST: u128 = 50;
+
+    fn list_cw1155_collateral(
+        app: &mut StargazeApp,
+        loan_addr: &Addr,
+        owner_addr: &Addr,
+        cw1155_addr: &Addr,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::ListCollaterals {
+                tokens: vec![AssetInfo::cw1155(
+                    cw1155_addr.as_str(),
+                    TOKEN_ID,
+                    COLLATERAL_VALUE,
+                )],
+                terms: Some(LoanTerms {
+                    principle: AssetInfo::coin(PRINCIPLE, NATIVE_DENOM),
+                    interest: Uint128::new(INTEREST),
+                    duration: LoanDuration::Blocks(15),
+                    pro_rata_interest: false,
+                }),
+                comment: None,
+                loan_preview: None,
+            },
+            &coins(NATIVE_LOAN_LISTING_AMT, NATIVE_DENOM),
+        )
+    }
+
+    #[test]
+    fn cw1155_collateral_loan() {
+        let (mut app, loan_addr, _) = proper_loan_instantiate();
+        let (owner_addr, _, lender_addr) = setup_accounts(&mut app);
+        let cw1155_addr = instantiate_cw1155(
+            &mut app,
+            vec![(owner_addr.clone(), TOKEN_ID, COLLATERAL_VALUE)],
+        );
+
+        // The loan contract needs to be approved before listing
+        let res = list_cw1155_collateral(&mut app, &loan_addr, &owner_addr, &cw1155_addr);
+        assert_error(res, ContractError::TokenApprovalIssue {}.to_string());
+
+        app.execute_contract(
+            owner_addr.clone(),
+            cw1155_addr.clone(),
+            &Cw1155ExecuteMsg::ApproveAll {
+                operator: loan_addr.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        list_cw1155_collateral(&mut app, &loan_addr, &owner_addr, &cw1155_addr).unwrap();
+
+        app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+                comment: None,
+            },
+            &[coin(PRINCIPLE, NATIVE_DENOM)],
+        )
+        .unwrap();
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &loan_addr, TOKEN_ID),
+            Uint128::new(COLLATERAL_VALUE)
+        );
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &owner_addr, TOKEN_ID),
+            Uint128::zero()
+        );
+
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::RepayBorrowedFunds { loan_id: 0 },
+            &[coin(PRINCIPLE + INTEREST, NATIVE_DENOM)],
+        )
+        .unwrap();
+
+        // The collateral is given back to the borrower
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &owner_addr, TOKEN_ID),
+            Uint128::new(COLLATERAL_VALUE)
+        );
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &loan_addr, TOKEN_ID),
+            Uint128::zero()
+        );
+    }
+}
@@ -0,0 +1,298 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, coins, to_json_binary, Addr, Uint128};
+    use cw20::{Cw20Coin, Cw20ExecuteMsg};
+    use cw_multi_test::{AppResponse, Executor};
+    use nft_loans_nc::{
+        error::ContractError,
+        msg::{ExecuteMsg, QueryMsg, ReceiveMsg},
+        state::{CollateralInfo, LoanDuration, LoanState, LoanTerms},
+    };
+    use sg_std::NATIVE_DENOM;
+    use utils::state::{AssetInfo, Sg721Token};
+
+    use crate::common_setup::{
+        app::StargazeApp,
+        helpers::{assert_error, cw20_balance, instantiate_cw20},
+        setup_accounts_and_block::setup_accounts,
+        setup_loan::{configure_loan_assets, proper_loan_instantiate, NATIVE_LOAN_LISTING_AMT},
+        setup_minter::common::constants::{SG721_CONTRACT, TREASURY_ADDR},
+    };
+
+    const INITIAL_CW20_BALANCE: u128 = 1_000;
+    const PRINCIPLE: u128 = 100;
+    const INTEREST: u128 = 50;
+
+    fn cw20_terms(cw20_addr: &Addr) -> LoanTerms {
+        LoanTerms {
+            principle: AssetInfo::cw20(PRINCIPLE, cw20_addr.as_str()),
+            interest: Uint128::new(INTEREST),
+            duration: LoanDuration::Blocks(15),
+            pro_rata_interest: false,
+        }
+    }
+
+    // Lists loan_id 0 with CW20 terms
+    fn setup_cw20_loan() -> (StargazeApp, Addr, Addr, Addr, Addr) {
+        let (mut app, loan_addr, factory_addr) = proper_loan_instantiate();
+        let (owner_addr, _, lender_addr) = setup_accounts(&mut app);
+        configure_loan_assets(&mut app, owner_addr.clone(), factory_addr);
+        let cw20_addr = instantiate_cw20(
+            &mut app,
+            vec![
+                Cw20Coin {
+                    address: owner_addr.to_string(),
+                    amount: INITIAL_CW20_BALANCE.into(),
+                },
+                Cw20Coin {
+                    address: lender_addr.to_string(),
+                    amount: INITIAL_CW20_BALANCE.into(),
+                },
+            ],
+        );
+
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::ListCollaterals {
+                tokens: vec![AssetInfo::Sg721Token(Sg721Token {
+                    address: SG721_CONTRACT.to_string(),
+                    token_id: "63".to_string(),
+                })],
+                terms: Some(cw20_terms(&cw20_addr)),
+                comment: None,
+                loan_preview: None,
+            },
+            &coins(NATIVE_LOAN_LISTING_AMT, NATIVE_DENOM),
+        )
+        .unwrap();
+
+        (app, loan_addr, cw20_addr, owner_addr, lender_addr)
+    }
+
+    fn send_cw20(
+        app: &mut StargazeApp,
+        cw20_addr: &Addr,
+        sender: &Addr,
+        loan_addr: &Addr,
+        amount: u128,
+        msg: ReceiveMsg,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            sender.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: loan_addr.to_string(),
+                amount: amount.into(),
+                msg: to_json_binary(&msg).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    #[test]
+    fn cw20_offer_accept_and_repay() {
+        let (mut app, loan_addr, cw20_addr, owner_addr, lender_addr) = setup_cw20_loan();
+
+        send_cw20(
+            &mut app,
+            &cw20_addr,
+            &lender_addr,
+            &loan_addr,
+            PRINCIPLE,
+            ReceiveMsg::MakeOffer {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+                terms: cw20_terms(&cw20_addr),
+                comment: None,
+                on_behalf_of: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &loan_addr),
+            Uint128::new(PRINCIPLE)
+        );
+
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptOffer {
+                global_offer_id: 1.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &owner_addr),
+            Uint128::new(INITIAL_CW20_BALANCE + PRINCIPLE)
+        );
+
+        // Native funds can't repay a CW20 loan
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::RepayBorrowedFunds { loan_id: 0 },
+            &[coin(PRINCIPLE + INTEREST, NATIVE_DENOM)],
+        );
+        assert_error(res, ContractError::FundsDontMatchTerms {}.to_string());
+
+        // Not enough tokens to cover the interest
+        let err = send_cw20(
+            &mut app,
+            &cw20_addr,
+            &owner_addr,
+            &loan_addr,
+            PRINCIPLE,
+            ReceiveMsg::RepayBorrowedFunds { loan_id: 0 },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::FundsDontMatchTermsAndPrinciple(
+                Uint128::new(PRINCIPLE + INTEREST),
+                Uint128::new(PRINCIPLE)
+            )
+            .to_string()
+        );
+
+        send_cw20(
+            &mut app,
+            &cw20_addr,
+            &owner_addr,
+            &loan_addr,
+            PRINCIPLE + INTEREST,
+            ReceiveMsg::RepayBorrowedFunds { loan_id: 0 },
+        )
+        .unwrap();
+
+        // 50% of the interest goes to the treasury
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &lender_addr),
+            Uint128::new(INITIAL_CW20_BALANCE + INTEREST / 2)
+        );
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &Addr::unchecked(TREASURY_ADDR)),
+            Uint128::new(INTEREST / 2)
+        );
+        let res: CollateralInfo = app
+            .wrap()
+            .query_wasm_smart(
+                loan_addr,
+                &QueryMsg::CollateralInfo {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.state, LoanState::Ended);
+    }
+
+    #[test]
+    fn cw20_accept_loan() {
+        let (mut app, loan_addr, cw20_addr, owner_addr, lender_addr) = setup_cw20_loan();
+
+        // Native funds don't match CW20 terms
+        let res = app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+                comment: None,
+            },
+            &[coin(PRINCIPLE, NATIVE_DENOM)],
+        );
+        assert_error(res, ContractError::FundsDontMatchTerms {}.to_string());
+
+        // Wrong CW20 token
+        let other_cw20 = instantiate_cw20(
+            &mut app,
+            vec![Cw20Coin {
+                address: lender_addr.to_string(),
+                amount: INITIAL_CW20_BALANCE.into(),
+            }],
+        );
+        let err = send_cw20(
+            &mut app,
+            &other_cw20,
+            &lender_addr,
+            &loan_addr,
+            PRINCIPLE,
+            ReceiveMsg::AcceptLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+                comment: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::FundsDontMatchTerms {}.to_string()
+        );
+
+        send_cw20(
+            &mut app,
+            &cw20_addr,
+            &lender_addr,
+            &loan_addr,
+            PRINCIPLE,
+            ReceiveMsg::AcceptLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+                comment: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &owner_addr),
+            Uint128::new(INITIAL_CW20_BALANCE + PRINCIPLE)
+        );
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &lender_addr),
+            Uint128::new(INITIAL_CW20_BALANCE - PRINCIPLE)
+        );
+    }
+
+    #[test]
+    fn cw20_cancel_offer() {
+        let (mut app, loan_addr, cw20_addr, owner_addr, lender_addr) = setup_cw20_loan();
+
+        send_cw20(
+            &mut app,
+            &cw20_addr,
+            &lender_addr,
+            &loan_addr,
+            PRINCIPLE,
+            ReceiveMsg::MakeOffer {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+                terms: cw20_terms(&cw20_addr),
+                comment: None,
+                on_behalf_of: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &lender_addr),
+            Uint128::new(INITIAL_CW20_BALANCE - PRINCIPLE)
+        );
+
+        app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::CancelOffer {
+                global_offer_id: 1.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &lender_addr),
+            Uint128::new(INITIAL_CW20_BALANCE)
+        );
+        assert_eq!(cw20_balance(&app, &cw20_addr, &loan_addr), Uint128::zero());
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coins, Uint128};
+    use cw_multi_test::Executor;
+    use nft_loans_nc::{
+        error::ContractError,
+        msg::{ExecuteMsg, MultipleCollateralsResponse, QueryMsg},
+        state::{LoanDuration, LoanState, LoanTerms},
+    };
+    use sg_std::NATIVE_DENOM;
+    use utils::state::AssetInfo;
+
+    use crate::common_setup::{
+        helpers::{assert_error, balance, setup_started_loan},
+        setup_minter::common::constants::TREASURY_ADDR,
+    };
+
+    // Terms of loan_id 0, lasting 10 blocks
+    fn terms() -> LoanTerms {
+        LoanTerms {
+            principle: AssetInfo::coin(100, NATIVE_DENOM),
+            interest: Uint128::new(50),
+            duration: LoanDuration::Blocks(10),
+            pro_rata_interest: false,
+        }
+    }
+
+    #[test]
+    fn extend_loan() {
+        let (mut app, loan_addr, owner_addr, lender_addr, _) = setup_started_loan(terms());
+
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::ProposeExtension {
+                loan_id: 0,
+                duration: 10,
+            },
+            &coins(20, NATIVE_DENOM),
+        )
+        .unwrap();
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::ProposeExtension {
+                loan_id: 0,
+                duration: 10,
+            },
+            &coins(20, NATIVE_DENOM),
+        );
+        assert_error(res, ContractError::ExtensionAlreadyProposed {}.to_string());
+
+        // Only the active lender can accept the extension
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptExtension {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+            },
+            &[],
+        );
+        assert_error(res, ContractError::Unauthorized {}.to_string());
+
+        let lender_balance = balance(&app, lender_addr.as_str());
+        let treasury_balance = balance(&app, TREASURY_ADDR);
+        app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptExtension {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, lender_addr.as_str()), lender_balance + 10);
+        assert_eq!(balance(&app, TREASURY_ADDR), treasury_balance + 10);
+
+        let res: MultipleCollateralsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::Collaterals {
+                    borrower: owner_addr.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let res = &res.collaterals[0];
+        assert_eq!(res.collateral.pending_extension, None);
+        assert_eq!(res.collateral.extensions.len(), 1);
+        assert_eq!(res.collateral.extensions[0].duration, 10);
+        assert_eq!(
+            res.collateral.extensions[0].accepted_block,
+            Some(app.block_info().height)
+        );
+
+        // The loan isn't defaulted after the initial duration anymore
+        app.update_block(|block| block.height += 15);
+        let res = app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::WithdrawDefaultedLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+            },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::WrongLoanState {
+                state: LoanState::Started,
+            }
+            .to_string(),
+        );
+
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::RepayBorrowedFunds { loan_id: 0 },
+            &coins(150, NATIVE_DENOM),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn cancel_extension() {
+        let (mut app, loan_addr, owner_addr, lender_addr, _) = setup_started_loan(terms());
+
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::CancelExtension { loan_id: 0 },
+            &[],
+        );
+        assert_error(res, ContractError::NoExtensionProposed {}.to_string());
+
+        let borrower_balance = balance(&app, owner_addr.as_str());
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::ProposeExtension {
+                loan_id: 0,
+                duration: 10,
+            },
+            &coins(20, NATIVE_DENOM),
+        )
+        .unwrap();
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::CancelExtension { loan_id: 0 },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, owner_addr.as_str()), borrower_balance);
+
+        let res = app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptExtension {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+            },
+            &[],
+        );
+        assert_error(res, ContractError::NoExtensionProposed {}.to_string());
+
+        // Without extension, the loan defaults after its initial duration
+        app.update_block(|block| block.height += 15);
+        app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::WithdrawDefaultedLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+}
@@ -11,7 +11,7 @@ mod tests {
         msg::{
             CollateralResponse, ExecuteMsg, MultipleCollateralsResponse, OfferResponse, QueryMsg,
         },
-        state::{CollateralInfo, LoanState, LoanTerms, OfferState},
+        state::{CollateralInfo, LoanDuration, LoanState, LoanTerms, OfferState},
     };
     use utils::state::{AssetInfo, Sg721Token};
 
@@ -42,6 +42,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
 
@@ -79,12 +80,13 @@ mod tests {
                         }),
                     ],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
@@ -142,9 +144,10 @@ mod tests {
                 loan_id: 1,
                 collateral: CollateralInfo {
                     terms: Some(LoanTerms {
-                        principle: coin(100, "ustars"),
+                        principle: AssetInfo::Coin(coin(100, "ustars")),
                         interest: Uint128::new(50u128),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     associated_assets: vec![
                         AssetInfo::Sg721Token(Sg721Token {
@@ -163,6 +166,13 @@ mod tests {
                     start_block: None,
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
+                    start_time: None,
+                    principle_repaid: Uint128::zero(),
+                    interest_repaid: Uint128::zero(),
+                    interest_checkpoint: Uint128::zero(),
+                    checkpoint_elapsed: 0,
+                    pending_extension: None,
+                    extensions: vec![],
                 },
                 loan_state: LoanState::Published,
             }
@@ -179,12 +189,13 @@ mod tests {
                         token_id: "34".to_string(),
                     })],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
@@ -205,12 +216,13 @@ mod tests {
                     borrower: OWNER_ADDR.to_string(),
                     loan_id: 0,
                     terms: LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     },
                     comment: Some("Obey the principles without being bound by them".to_string()),
                     on_behalf_of: None,
@@ -336,6 +348,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
 
@@ -350,12 +363,13 @@ mod tests {
                         token_id: "63".to_string(),
                     })],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
@@ -382,12 +396,13 @@ mod tests {
                         token_id: "63".to_string(),
                     })],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
@@ -420,12 +435,13 @@ mod tests {
                         token_id: "63".to_string(),
                     })],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
@@ -452,12 +468,13 @@ mod tests {
                         token_id: "63".to_string(),
                     })],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("be water, my friend".to_string()),
                     loan_preview: None,
@@ -490,12 +507,13 @@ mod tests {
                         }),
                     ],
                     terms: Some(LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     }),
                     comment: Some("Real living is living for others".to_string()),
                     loan_preview: None,
@@ -521,6 +539,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
 
@@ -621,6 +640,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
         // no funds sent in offer
@@ -632,12 +652,13 @@ mod tests {
                     borrower: OWNER_ADDR.to_string(),
                     loan_id: 0,
                     terms: LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(50),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     },
                     on_behalf_of: None,
                     comment: Some("Obey the principles without being bound by them".to_string()),
@@ -659,12 +680,13 @@ mod tests {
                     borrower: OWNER_ADDR.to_string(),
                     loan_id: 0,
                     terms: LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(50),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     },
                     on_behalf_of: None,
                     comment: Some("Obey the principles without being bound by them".to_string()),
@@ -689,12 +711,13 @@ mod tests {
                     borrower: OWNER_ADDR.to_string(),
                     loan_id: 0,
                     terms: LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(50),
-                        },
+                        }),
                         interest: Uint128::new(15),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     },
                     on_behalf_of: None,
                     comment: Some("Obey the principles without being bound by them".to_string()),
@@ -721,6 +744,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
         // not enough funds sent
@@ -797,6 +821,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
 
@@ -809,12 +834,13 @@ mod tests {
                     borrower: OWNER_ADDR.to_string(),
                     loan_id: 0,
                     terms: LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     },
                     on_behalf_of: None,
                     comment: Some("Obey the principles without being bound by them".to_string()),
@@ -871,6 +897,7 @@ mod tests {
             app: &mut app,
             loan_contract_addr: loan_addr.clone(),
             owner_addr: owner_address.clone(),
+            duration: LoanDuration::Blocks(15),
         };
         create_loan_function(create_loan_params).unwrap();
         let balance_before = app.wrap().query_all_balances(OFFERER_ADDR);
@@ -883,12 +910,13 @@ mod tests {
                     borrower: OWNER_ADDR.to_string(),
                     loan_id: 0,
                     terms: LoanTerms {
-                        principle: Coin {
+                        principle: AssetInfo::Coin(Coin {
                             denom: NATIVE_DENOM.to_string(),
                             amount: Uint128::new(100),
-                        },
+                        }),
                         interest: Uint128::new(50),
-                        duration_in_blocks: 15,
+                        duration: LoanDuration::Blocks(15),
+                        pro_rata_interest: false,
                     },
                     on_behalf_of: None,
                     comment: Some("Obey the principles without being bound by them".to_string()),
@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, coins, Addr, Uint128};
+    use cw_multi_test::Executor;
+    use nft_loans_nc::{
+        error::ContractError,
+        msg::{AmountOwedResponse, ExecuteMsg, QueryMsg},
+        state::{CollateralInfo, LoanDuration, LoanState, LoanTerms},
+    };
+    use sg_std::NATIVE_DENOM;
+    use utils::state::AssetInfo;
+
+    use crate::common_setup::{
+        app::StargazeApp,
+        helpers::{assert_error, balance, setup_started_loan},
+        setup_minter::common::constants::TREASURY_ADDR,
+    };
+
+    const PRINCIPLE: u128 = 100;
+    const INTEREST: u128 = 50;
+    const DURATION: u64 = 10;
+
+    // Terms of loan_id 0, with pro-rata interest
+    fn terms() -> LoanTerms {
+        LoanTerms {
+            principle: AssetInfo::coin(PRINCIPLE, NATIVE_DENOM),
+            interest: Uint128::new(INTEREST),
+            duration: LoanDuration::Blocks(DURATION),
+            pro_rata_interest: true,
+        }
+    }
+
+    fn amount_owed(app: &StargazeApp, loan_addr: &Addr, borrower: &Addr) -> AmountOwedResponse {
+        app.wrap()
+            .query_wasm_smart(
+                loan_addr,
+                &QueryMsg::AmountOwed {
+                    borrower: borrower.to_string(),
+                    loan_id: 0,
+                    height: None,
+                    time: None,
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn pro_rata_interest_accrual() {
+        let (app, loan_addr, owner_addr, _, _) = setup_started_loan(terms());
+
+        // No interest accrued at the start of the loan
+        assert_eq!(
+            amount_owed(&app, &loan_addr, &owner_addr),
+            AmountOwedResponse {
+                principle: Uint128::new(PRINCIPLE),
+                interest: Uint128::zero(),
+                total: Uint128::new(PRINCIPLE),
+            }
+        );
+
+        let height = app.block_info().height;
+        let res: AmountOwedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::AmountOwed {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                    height: Some(height + 4),
+                    time: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.interest, Uint128::new(20));
+
+        // The interest is capped at the end of the loan
+        let res: AmountOwedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::AmountOwed {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                    height: Some(height + 100),
+                    time: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.interest, Uint128::new(INTEREST));
+    }
+
+    #[test]
+    fn partial_repay_then_full_repay() {
+        let (mut app, loan_addr, owner_addr, lender_addr, _) = setup_started_loan(terms());
+        app.update_block(|block| block.height += 4);
+
+        // Wrong denom
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::PartialRepay { loan_id: 0 },
+            &[coin(50, "uflix")],
+        );
+        assert_error(res, ContractError::FundsDontMatchTerms {}.to_string());
+
+        let lender_balance = balance(&app, lender_addr.as_str());
+        let treasury_balance = balance(&app, TREASURY_ADDR);
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::PartialRepay { loan_id: 0 },
+            &coins(50, NATIVE_DENOM),
+        )
+        .unwrap();
+
+        // 20 of interest are paid first, 50% of it goes to the treasury
+        assert_eq!(balance(&app, lender_addr.as_str()), lender_balance + 40);
+        assert_eq!(balance(&app, TREASURY_ADDR), treasury_balance + 10);
+        assert_eq!(
+            amount_owed(&app, &loan_addr, &owner_addr),
+            AmountOwedResponse {
+                principle: Uint128::new(70),
+                interest: Uint128::zero(),
+                total: Uint128::new(70),
+            }
+        );
+
+        // Only the interest accrued since then on the remaining 70 is owed
+        app.update_block(|block| block.height += 4);
+        assert_eq!(
+            amount_owed(&app, &loan_addr, &owner_addr).interest,
+            Uint128::new(14)
+        );
+
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::RepayBorrowedFunds { loan_id: 0 },
+            &coins(83, NATIVE_DENOM),
+        );
+        assert_error(
+            res,
+            ContractError::FundsDontMatchTermsAndPrinciple(Uint128::new(84), Uint128::new(83))
+                .to_string(),
+        );
+
+        // Sending everything that is owed through a partial repayment ends the loan
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::PartialRepay { loan_id: 0 },
+            &coins(84, NATIVE_DENOM),
+        )
+        .unwrap();
+        assert_eq!(
+            balance(&app, lender_addr.as_str()),
+            lender_balance + 40 + 77
+        );
+        assert_eq!(balance(&app, TREASURY_ADDR), treasury_balance + 17);
+
+        let res: CollateralInfo = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::CollateralInfo {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.state, LoanState::Ended);
+        assert_eq!(res.principle_repaid, Uint128::new(PRINCIPLE));
+        assert_eq!(res.interest_repaid, Uint128::new(34));
+    }
+
+    #[test]
+    fn repaid_principle_stops_accruing_interest() {
+        let (mut app, loan_addr, owner_addr, _, _) = setup_started_loan(terms());
+
+        // Half of the principle is paid back right away
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::PartialRepay { loan_id: 0 },
+            &coins(PRINCIPLE / 2, NATIVE_DENOM),
+        )
+        .unwrap();
+
+        // Only half of the interest is owed at the end of the loan
+        app.update_block(|block| block.height += DURATION);
+        assert_eq!(
+            amount_owed(&app, &loan_addr, &owner_addr),
+            AmountOwedResponse {
+                principle: Uint128::new(PRINCIPLE / 2),
+                interest: Uint128::new(INTEREST / 2),
+                total: Uint128::new(PRINCIPLE / 2 + INTEREST / 2),
+            }
+        );
+    }
+}
@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coins, Addr, Uint128};
+    use cw_multi_test::Executor;
+    use nft_loans_nc::{
+        error::ContractError,
+        msg::{AmountOwedResponse, ExecuteMsg, OfferResponse, QueryMsg},
+        state::{CollateralInfo, LoanDuration, LoanState, LoanTerms, OfferState},
+    };
+    use sg721_base::QueryMsg as Sg721QueryMsg;
+    use sg_std::NATIVE_DENOM;
+    use utils::state::AssetInfo;
+
+    use crate::common_setup::{
+        app::StargazeApp,
+        helpers::{assert_error, balance, setup_started_loan},
+        setup_minter::common::constants::{SG721_CONTRACT, TREASURY_ADDR},
+    };
+
+    fn terms(principle: u128, interest: u128, duration: LoanDuration) -> LoanTerms {
+        LoanTerms {
+            principle: AssetInfo::coin(principle, NATIVE_DENOM),
+            interest: Uint128::new(interest),
+            duration,
+            pro_rata_interest: true,
+        }
+    }
+
+    fn make_offer(
+        app: &mut StargazeApp,
+        loan_addr: &Addr,
+        lender: &Addr,
+        borrower: &Addr,
+        terms: LoanTerms,
+    ) {
+        let funds = coins(terms.principle_amount().unwrap().u128(), NATIVE_DENOM);
+        app.execute_contract(
+            lender.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::MakeOffer {
+                borrower: borrower.to_string(),
+                loan_id: 0,
+                terms,
+                comment: None,
+                on_behalf_of: None,
+            },
+            &funds,
+        )
+        .unwrap();
+    }
+
+    fn offer_state(app: &StargazeApp, loan_addr: &Addr, global_offer_id: &str) -> OfferState {
+        let res: OfferResponse = app
+            .wrap()
+            .query_wasm_smart(
+                loan_addr,
+                &QueryMsg::OfferInfo {
+                    global_offer_id: global_offer_id.to_string(),
+                },
+            )
+            .unwrap();
+        res.offer_info.state
+    }
+
+    #[test]
+    fn refinance_started_loan() {
+        let (mut app, loan_addr, owner_addr, lender_addr, new_lender_addr) =
+            setup_started_loan(terms(100, 50, LoanDuration::Blocks(10)));
+        app.update_block(|block| block.height += 4);
+
+        // The borrower owes 100 + 20 at this point
+        make_offer(
+            &mut app,
+            &loan_addr,
+            &new_lender_addr,
+            &owner_addr,
+            terms(110, 10, LoanDuration::Blocks(20)),
+        );
+        assert_eq!(offer_state(&app, &loan_addr, "2"), OfferState::Published);
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptOffer {
+                global_offer_id: "2".to_string(),
+            },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::RefinanceNotCovered {
+                principle: Uint128::new(110),
+                owed: Uint128::new(120),
+            }
+            .to_string(),
+        );
+
+        make_offer(
+            &mut app,
+            &loan_addr,
+            &new_lender_addr,
+            &owner_addr,
+            terms(200, 30, LoanDuration::Blocks(20)),
+        );
+        let borrower_balance = balance(&app, owner_addr.as_str());
+        let lender_balance = balance(&app, lender_addr.as_str());
+        let treasury_balance = balance(&app, TREASURY_ADDR);
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptOffer {
+                global_offer_id: "3".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // The previous lender gets the principle and 50% of the interest
+        assert_eq!(balance(&app, lender_addr.as_str()), lender_balance + 110);
+        assert_eq!(balance(&app, TREASURY_ADDR), treasury_balance + 10);
+        assert_eq!(balance(&app, owner_addr.as_str()), borrower_balance + 80);
+
+        // The NFT stays in escrow
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                SG721_CONTRACT,
+                &Sg721QueryMsg::OwnerOf {
+                    token_id: "63".to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, loan_addr.to_string());
+
+        let collateral: CollateralInfo = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::CollateralInfo {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(collateral.state, LoanState::Started);
+        assert_eq!(collateral.active_offer, Some("3".to_string()));
+        assert_eq!(collateral.start_block, Some(app.block_info().height));
+        assert_eq!(offer_state(&app, &loan_addr, "3"), OfferState::Accepted);
+
+        // The new terms apply from now on
+        app.update_block(|block| block.height += 10);
+        let res: AmountOwedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::AmountOwed {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                    height: None,
+                    time: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.total, Uint128::new(215));
+
+        // The uncovered offer is still valid and can be cancelled
+        let new_lender_balance = balance(&app, new_lender_addr.as_str());
+        app.execute_contract(
+            new_lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::CancelOffer {
+                global_offer_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            balance(&app, new_lender_addr.as_str()),
+            new_lender_balance + 110
+        );
+    }
+
+    #[test]
+    fn refinance_offer_refused_after_repay() {
+        let (mut app, loan_addr, owner_addr, _, new_lender_addr) =
+            setup_started_loan(terms(100, 50, LoanDuration::Blocks(10)));
+        make_offer(
+            &mut app,
+            &loan_addr,
+            &new_lender_addr,
+            &owner_addr,
+            terms(200, 30, LoanDuration::Blocks(20)),
+        );
+
+        app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::RepayBorrowedFunds { loan_id: 0 },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+        assert_eq!(offer_state(&app, &loan_addr, "2"), OfferState::Refused);
+
+        // The funds of the refinance offer can be withdrawn once the loan is over
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::AcceptOffer {
+                global_offer_id: "2".to_string(),
+            },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::WrongLoanState {
+                state: LoanState::Ended,
+            }
+            .to_string(),
+        );
+        app.execute_contract(
+            new_lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::WithdrawRefusedOffer {
+                global_offer_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Uint128};
+    use cw_multi_test::Executor;
+    use nft_loans_nc::{
+        error::ContractError,
+        msg::{ExecuteMsg, QueryMsg},
+        state::{CollateralInfo, LoanDuration, LoanState, LoanTerms},
+    };
+    use sg_std::NATIVE_DENOM;
+    use utils::state::AssetInfo;
+
+    use crate::common_setup::{
+        app::StargazeApp,
+        helpers::{assert_error, setup_started_loan},
+    };
+
+    // Terms of loan_id 0, lasting the given duration
+    fn terms(duration: LoanDuration) -> LoanTerms {
+        LoanTerms {
+            principle: AssetInfo::coin(100, NATIVE_DENOM),
+            interest: Uint128::new(50),
+            duration,
+            pro_rata_interest: false,
+        }
+    }
+
+    fn withdraw_defaulted_loan(
+        app: &mut StargazeApp,
+        loan_addr: &Addr,
+        owner_addr: &Addr,
+        lender_addr: &Addr,
+    ) -> Result<cw_multi_test::AppResponse, anyhow::Error> {
+        app.execute_contract(
+            lender_addr.clone(),
+            loan_addr.clone(),
+            &ExecuteMsg::WithdrawDefaultedLoan {
+                borrower: owner_addr.to_string(),
+                loan_id: 0,
+            },
+            &[],
+        )
+    }
+
+    #[test]
+    fn seconds_duration_default() {
+        let (mut app, loan_addr, owner_addr, lender_addr, _) =
+            setup_started_loan(terms(LoanDuration::Seconds(3600)));
+
+        let collateral: CollateralInfo = app
+            .wrap()
+            .query_wasm_smart(
+                &loan_addr,
+                &QueryMsg::CollateralInfo {
+                    borrower: owner_addr.to_string(),
+                    loan_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(collateral.start_time, Some(app.block_info().time));
+
+        // Block height doesn't matter for time based loans
+        app.update_block(|block| {
+            block.height += 1000;
+            block.time = block.time.plus_seconds(3600);
+        });
+        let res = withdraw_defaulted_loan(&mut app, &loan_addr, &owner_addr, &lender_addr);
+        assert_error(
+            res,
+            ContractError::WrongLoanState {
+                state: LoanState::Started,
+            }
+            .to_string(),
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(1));
+        withdraw_defaulted_loan(&mut app, &loan_addr, &owner_addr, &lender_addr).unwrap();
+    }
+
+    #[test]
+    fn blocks_duration_default() {
+        let (mut app, loan_addr, owner_addr, lender_addr, _) =
+            setup_started_loan(terms(LoanDuration::Blocks(15)));
+
+        // Time doesn't matter for block based loans
+        app.update_block(|block| {
+            block.height += 15;
+            block.time = block.time.plus_seconds(100_000);
+        });
+        let res = withdraw_defaulted_loan(&mut app, &loan_addr, &owner_addr, &lender_addr);
+        assert_error(
+            res,
+            ContractError::WrongLoanState {
+                state: LoanState::Started,
+            }
+            .to_string(),
+        );
+
+        app.update_block(|block| block.height += 1);
+        withdraw_defaulted_loan(&mut app, &loan_addr, &owner_addr, &lender_addr).unwrap();
+    }
+}
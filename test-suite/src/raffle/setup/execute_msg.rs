@@ -50,7 +50,13 @@ pub fn create_raffle_function(params: CreateRaffleParams) -> Result<AppResponse,
                 gating_raffle: vec![],
                 min_ticket_number: None,
                 whitelist: None,
+                merkle_root: None,
+                prize_tiers: vec![],
+                ticket_pricing: None,
+                referral_share: None,
+                sponsors: None,
             },
+            additional_ticket_prices: vec![],
             raffle_ticket_price: AssetInfo::Coin(Coin {
                 denom: "ustars".to_string(),
                 amount: ticket_price,
@@ -78,6 +84,8 @@ pub fn buy_tickets_template(params: PurchaseTicketsParams) -> Result<AppResponse
             ticket_count: num_tickets,
             sent_assets: AssetInfo::Coin(funds_sent[0].clone()),
             on_behalf_of: None,
+            allowlist_proof: None,
+            referrer: None,
         },
         &funds_sent,
     )
@@ -121,7 +129,13 @@ pub fn create_raffle_setup(params: CreateRaffleParams) -> anyhow::Result<()> {
                 gating_raffle: params.gating,
                 min_ticket_number: params.min_ticket_number,
                 whitelist: None,
+                merkle_root: None,
+                prize_tiers: vec![],
+                ticket_pricing: None,
+                referral_share: None,
+                sponsors: None,
             },
+            additional_ticket_prices: vec![],
             raffle_ticket_price: AssetInfo::Coin(Coin {
                 denom: "ustars".to_string(),
                 amount: raffle_ticket_price,
@@ -268,7 +268,7 @@ pub fn send_update_randomness_message(
 pub fn rand(rand: DrandRandomnessConst) -> anyhow::Result<DrandRandomness> {
     Ok(DrandRandomness {
         round: rand.round,
-        previous_signature: Binary::from(rand.previous_signature.from_hex()?),
+        previous_signature: Some(Binary::from(rand.previous_signature.from_hex()?)),
         signature: Binary::from(rand.signature.from_hex()?),
     })
 }
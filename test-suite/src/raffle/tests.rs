@@ -1,12 +1,15 @@
 pub mod buying_tickets;
 pub mod config_coverage;
 pub mod create_and_modify;
+pub mod cw1155_prizes;
+pub mod cw20_tickets;
 pub mod determine_winner;
 pub mod fee_discounts;
 pub mod gating;
 pub mod init;
 pub mod integration_tests;
 pub mod multiple_winners;
+pub mod nois_randomness;
 pub mod randomness;
 pub mod ticket_limits;
 pub mod token_raffles;
@@ -3,7 +3,8 @@ mod tests {
     use crate::common_setup::app::StargazeApp;
     use crate::common_setup::helpers::assert_error;
     use crate::common_setup::msg::RaffleContracts;
-    use crate::raffle::setup::helpers::{mint_one_token, TokenMint};
+    use crate::common_setup::setup_minter::common::constants::OWNER_ADDR;
+    use crate::raffle::setup::helpers::{finish_raffle_timeout, mint_one_token, TokenMint};
     use crate::raffle::setup::{execute_msg::create_raffle_setup, test_msgs::CreateRaffleParams};
     use utils::state::{Sg721Token, NATIVE_DENOM};
 
@@ -14,10 +15,15 @@ mod tests {
         },
         raffle::setup::{execute_msg::buy_tickets_template, test_msgs::PurchaseTicketsParams},
     };
-    use cosmwasm_std::{coin, coins, Addr, Coin, Uint128};
-    use cw_multi_test::Executor;
+    use cosmwasm_std::{coin, coins, Addr, Coin, Decimal, HexBinary, StdError, Uint128};
+    use cw_multi_test::{AppResponse, Executor};
     use raffles::error::ContractError;
-    use raffles::msg::{ExecuteMsg as RaffleExecuteMsg, QueryMsg as RaffleQueryMsg};
+    use raffles::msg::{
+        AllowlistProof, ExecuteMsg as RaffleExecuteMsg, IsAllowlistedResponse, MerkleRootResponse,
+        QueryMsg as RaffleQueryMsg, ReferralResponse, ReferralsResponse, TicketCostResponse,
+    };
+    use raffles::state::{RaffleOptionsMsg, RaffleState, TicketPricing, VolumeDiscount};
+    use raffles::utils::{allowlist_leaf, allowlist_parent};
     use std::vec;
     use utils::state::AssetInfo;
 
@@ -104,6 +110,8 @@ mod tests {
                 ticket_count: 67,
                 sent_assets: AssetInfo::Coin(coin(4 * 67, "ustars")),
                 on_behalf_of: Some("any-user-really".to_string()),
+                allowlist_proof: None,
+                referrer: None,
             },
             &coins(4 * 67, "ustars"),
         )
@@ -122,6 +130,572 @@ mod tests {
         assert_eq!(res, 67);
     }
 
+    #[test]
+    fn large_purchases_are_stored_as_ranges() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, two, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        create_simple_raffle(&mut app, &contracts, &token, owner_addr, None, None);
+
+        for (buyer, ticket_count) in [(&one, 3u32), (&two, 1000), (&one, 2)] {
+            app.execute_contract(
+                buyer.clone(),
+                contracts.raffle.clone(),
+                &RaffleExecuteMsg::BuyTicket {
+                    raffle_id: 0,
+                    ticket_count,
+                    sent_assets: AssetInfo::Coin(coin(4 * ticket_count as u128, "ustars")),
+                    on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
+                },
+                &coins(4 * ticket_count as u128, "ustars"),
+            )
+            .unwrap();
+        }
+
+        // Tickets are listed one by one, even inside a range
+        let all_tickets = |start_after: Option<u32>| -> Vec<String> {
+            app.wrap()
+                .query_wasm_smart(
+                    contracts.raffle.clone(),
+                    &RaffleQueryMsg::AllTickets {
+                        raffle_id: 0,
+                        start_after,
+                        limit: Some(5),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(
+            all_tickets(None),
+            vec![
+                one.to_string(),
+                one.to_string(),
+                one.to_string(),
+                two.to_string(),
+                two.to_string()
+            ]
+        );
+        assert_eq!(all_tickets(Some(500)), vec![two.to_string(); 5]);
+        assert_eq!(
+            all_tickets(Some(1000)),
+            vec![
+                two.to_string(),
+                two.to_string(),
+                one.to_string(),
+                one.to_string()
+            ]
+        );
+        assert_eq!(all_tickets(Some(1004)), Vec::<String>::new());
+
+        let res: u32 = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::TicketCount {
+                    owner: one.to_string(),
+                    raffle_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(res, 5);
+    }
+
+    #[test]
+    fn merkle_allowlist_purchase() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, two, three, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        create_simple_raffle(&mut app, &contracts, &token, owner_addr.clone(), None, None);
+
+        // `one` can buy as many tickets as they want, `two` only 2
+        let one_leaf = allowlist_leaf(one.as_str(), None);
+        let two_leaf = allowlist_leaf(two.as_str(), Some(2));
+        let merkle_root = HexBinary::from(allowlist_parent(one_leaf, two_leaf));
+        app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ModifyRaffle {
+                raffle_id: 0,
+                raffle_ticket_price: None,
+                additional_ticket_prices: None,
+                raffle_options: RaffleOptionsMsg {
+                    raffle_start_timestamp: None,
+                    raffle_duration: None,
+                    comment: None,
+                    max_ticket_number: None,
+                    max_ticket_per_address: None,
+                    raffle_preview: None,
+                    one_winner_per_asset: false,
+                    gating_raffle: vec![],
+                    min_ticket_number: None,
+                    whitelist: None,
+                    merkle_root: Some(merkle_root.clone()),
+                    prize_tiers: vec![],
+                    ticket_pricing: None,
+                    referral_share: None,
+                    sponsors: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+        let res: MerkleRootResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::MerkleRoot { raffle_id: 0 },
+            )
+            .unwrap();
+        assert_eq!(res.merkle_root, merkle_root);
+
+        let one_proof = AllowlistProof {
+            proof: vec![HexBinary::from(two_leaf)],
+            allowance: None,
+        };
+        let two_proof = AllowlistProof {
+            proof: vec![HexBinary::from(one_leaf)],
+            allowance: Some(2),
+        };
+
+        let res: IsAllowlistedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::IsAllowlisted {
+                    raffle_id: 0,
+                    user: two.to_string(),
+                    proof: two_proof.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res,
+            IsAllowlistedResponse {
+                is_allowlisted: true,
+                allowance: Some(2)
+            }
+        );
+        let res: IsAllowlistedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::IsAllowlisted {
+                    raffle_id: 0,
+                    user: three.to_string(),
+                    proof: one_proof.clone(),
+                },
+            )
+            .unwrap();
+        assert!(!res.is_allowlisted);
+
+        let mut buy_tickets =
+            |buyer: &Addr, ticket_count: u32, allowlist_proof: Option<AllowlistProof>| {
+                app.execute_contract(
+                    buyer.clone(),
+                    contracts.raffle.clone(),
+                    &RaffleExecuteMsg::BuyTicket {
+                        raffle_id: 0,
+                        ticket_count,
+                        sent_assets: AssetInfo::Coin(coin(4 * ticket_count as u128, "ustars")),
+                        on_behalf_of: None,
+                        allowlist_proof,
+                        referrer: None,
+                    },
+                    &coins(4 * ticket_count as u128, "ustars"),
+                )
+            };
+
+        buy_tickets(&one, 10, Some(one_proof.clone())).unwrap();
+
+        // No proof, or someone else's proof
+        let res = buy_tickets(&one, 1, None);
+        assert_error(res, ContractError::NotAllowlisted {}.to_string());
+        let res = buy_tickets(&three, 1, Some(one_proof));
+        assert_error(res, ContractError::NotAllowlisted {}.to_string());
+
+        // The allowance can't be changed without invalidating the proof
+        let res = buy_tickets(
+            &two,
+            3,
+            Some(AllowlistProof {
+                allowance: Some(3),
+                ..two_proof.clone()
+            }),
+        );
+        assert_error(res, ContractError::NotAllowlisted {}.to_string());
+
+        buy_tickets(&two, 2, Some(two_proof.clone())).unwrap();
+        let res = buy_tickets(&two, 1, Some(two_proof));
+        assert_error(
+            res,
+            ContractError::TooMuchTicketsForUser {
+                max: 2,
+                nb_before: 2,
+                nb_after: 3,
+            }
+            .to_string(),
+        );
+    }
+
+    fn set_ticket_pricing(
+        app: &mut StargazeApp,
+        contracts: &RaffleContracts,
+        owner_addr: &Addr,
+        ticket_pricing: TicketPricing,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ModifyRaffle {
+                raffle_id: 0,
+                raffle_ticket_price: None,
+                additional_ticket_prices: None,
+                raffle_options: RaffleOptionsMsg {
+                    raffle_start_timestamp: None,
+                    raffle_duration: None,
+                    comment: None,
+                    max_ticket_number: None,
+                    max_ticket_per_address: None,
+                    raffle_preview: None,
+                    one_winner_per_asset: false,
+                    gating_raffle: vec![],
+                    min_ticket_number: None,
+                    whitelist: None,
+                    merkle_root: None,
+                    prize_tiers: vec![],
+                    ticket_pricing: Some(ticket_pricing),
+                    referral_share: None,
+                    sponsors: None,
+                },
+            },
+            &[],
+        )
+    }
+
+    fn ticket_cost(app: &StargazeApp, contracts: &RaffleContracts, ticket_count: u32) -> AssetInfo {
+        let res: TicketCostResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::TicketCost {
+                    raffle_id: 0,
+                    ticket_count,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.ticket_count, ticket_count);
+        assert_eq!(res.additional_costs, vec![]);
+        res.cost
+    }
+
+    #[test]
+    fn bundle_pricing() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        create_simple_raffle(&mut app, &contracts, &token, owner_addr.clone(), None, None);
+
+        // 5 tickets for the price of 4
+        set_ticket_pricing(
+            &mut app,
+            &contracts,
+            &owner_addr,
+            TicketPricing::Bundle {
+                tickets: 5,
+                paid_tickets: 4,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            ticket_cost(&app, &contracts, 3),
+            AssetInfo::coin(12, "ustars")
+        );
+        assert_eq!(
+            ticket_cost(&app, &contracts, 5),
+            AssetInfo::coin(16, "ustars")
+        );
+        assert_eq!(
+            ticket_cost(&app, &contracts, 7),
+            AssetInfo::coin(24, "ustars")
+        );
+
+        let balance_before = app.wrap().query_balance(&one, "ustars").unwrap().amount;
+        let mut buy_tickets = |ticket_count: u32, amount: u128| {
+            app.execute_contract(
+                one.clone(),
+                contracts.raffle.clone(),
+                &RaffleExecuteMsg::BuyTicket {
+                    raffle_id: 0,
+                    ticket_count,
+                    sent_assets: AssetInfo::coin(amount, "ustars"),
+                    on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
+                },
+                &coins(amount, "ustars"),
+            )
+        };
+
+        // The full price isn't accepted anymore
+        let res = buy_tickets(7, 28);
+        assert_error(
+            res,
+            ContractError::PaymentNotSufficient {
+                ticket_count: 7,
+                assets_wanted: AssetInfo::coin(24, "ustars"),
+                assets_received: AssetInfo::coin(28, "ustars"),
+            }
+            .to_string(),
+        );
+        buy_tickets(7, 24).unwrap();
+
+        let res: u32 = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::TicketCount {
+                    owner: one.to_string(),
+                    raffle_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(res, 7);
+        assert_eq!(
+            app.wrap().query_balance(&one, "ustars").unwrap().amount,
+            balance_before - Uint128::new(24)
+        );
+    }
+
+    #[test]
+    fn graduated_pricing() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        create_simple_raffle(&mut app, &contracts, &token, owner_addr.clone(), None, None);
+
+        let tier = |min_tickets: u32, percent: u64| VolumeDiscount {
+            min_tickets,
+            discount: Decimal::percent(percent),
+        };
+        let res = set_ticket_pricing(
+            &mut app,
+            &contracts,
+            &owner_addr,
+            TicketPricing::Graduated {
+                tiers: vec![tier(25, 20), tier(10, 10)],
+            },
+        );
+        assert_error(
+            res,
+            ContractError::Std(StdError::generic_err(
+                "Discount tiers must be sorted by ticket number",
+            ))
+            .to_string(),
+        );
+
+        // 10% off from 10 tickets, 20% off from 25 tickets
+        set_ticket_pricing(
+            &mut app,
+            &contracts,
+            &owner_addr,
+            TicketPricing::Graduated {
+                tiers: vec![tier(10, 10), tier(25, 20)],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            ticket_cost(&app, &contracts, 9),
+            AssetInfo::coin(36, "ustars")
+        );
+        assert_eq!(
+            ticket_cost(&app, &contracts, 10),
+            AssetInfo::coin(36, "ustars")
+        );
+        assert_eq!(
+            ticket_cost(&app, &contracts, 25),
+            AssetInfo::coin(80, "ustars")
+        );
+    }
+
+    #[test]
+    fn referral_rewards() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, two, three, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        create_simple_raffle(&mut app, &contracts, &token, owner_addr.clone(), None, None);
+
+        let set_referral_share = |app: &mut StargazeApp, referral_share: Decimal| {
+            app.execute_contract(
+                owner_addr.clone(),
+                contracts.raffle.clone(),
+                &RaffleExecuteMsg::ModifyRaffle {
+                    raffle_id: 0,
+                    raffle_ticket_price: None,
+                    additional_ticket_prices: None,
+                    raffle_options: RaffleOptionsMsg {
+                        raffle_start_timestamp: None,
+                        raffle_duration: None,
+                        comment: None,
+                        max_ticket_number: None,
+                        max_ticket_per_address: None,
+                        raffle_preview: None,
+                        one_winner_per_asset: false,
+                        gating_raffle: vec![],
+                        min_ticket_number: None,
+                        whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: Some(referral_share),
+                        sponsors: None,
+                    },
+                },
+                &[],
+            )
+        };
+        // The raffle fee already takes 50% of the ticket sales
+        let res = set_referral_share(&mut app, Decimal::percent(60));
+        assert_error(
+            res,
+            ContractError::Std(StdError::generic_err(
+                "The referral share can't exceed the ticket sales left after fees",
+            ))
+            .to_string(),
+        );
+        set_referral_share(&mut app, Decimal::percent(25)).unwrap();
+
+        let buy_tickets = |app: &mut StargazeApp, buyer: &Addr, referrer: &Addr| {
+            app.execute_contract(
+                buyer.clone(),
+                contracts.raffle.clone(),
+                &RaffleExecuteMsg::BuyTicket {
+                    raffle_id: 0,
+                    ticket_count: 2,
+                    sent_assets: AssetInfo::coin(8, "ustars"),
+                    on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: Some(referrer.to_string()),
+                },
+                &coins(8, "ustars"),
+            )
+        };
+        let res = buy_tickets(&mut app, &one, &one);
+        assert_error(res, ContractError::SelfReferral {}.to_string());
+        // Buying for someone else doesn't allow referring yourself
+        let res = app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::BuyTicket {
+                raffle_id: 0,
+                ticket_count: 2,
+                sent_assets: AssetInfo::coin(8, "ustars"),
+                on_behalf_of: Some(three.to_string()),
+                allowlist_proof: None,
+                referrer: Some(one.to_string()),
+            },
+            &coins(8, "ustars"),
+        );
+        assert_error(res, ContractError::SelfReferral {}.to_string());
+        buy_tickets(&mut app, &one, &two).unwrap();
+        buy_tickets(&mut app, &three, &two).unwrap();
+
+        let claim_rewards = |app: &mut StargazeApp, referrer: &Addr| {
+            app.execute_contract(
+                referrer.clone(),
+                contracts.raffle.clone(),
+                &RaffleExecuteMsg::ClaimReferralRewards { raffle_id: 0 },
+                &[],
+            )
+        };
+        let res = claim_rewards(&mut app, &two);
+        assert_error(
+            res,
+            ContractError::WrongStateForClaim {
+                status: RaffleState::Started,
+            }
+            .to_string(),
+        );
+
+        // Raising the fees doesn't change the split of raffles that already exist
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::UpdateConfig {
+                name: None,
+                owner: None,
+                fee_addr: None,
+                minimum_raffle_duration: None,
+                raffle_fee: Some(Decimal::percent(90)),
+                randomness_provider_fee: None,
+                creation_coins: None,
+                max_tickets_per_raffle: None,
+                fee_discounts: None,
+                drand_config: None,
+                randomness_source: None,
+                randomness_deadline: None,
+                referral_fee: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // The referral rewards are taken out of the owner share
+        let owner_balance = app.wrap().query_balance(&owner_addr, "ustars").unwrap();
+        finish_raffle_timeout(&mut app, &contracts, 0, 1000).unwrap();
+        assert_eq!(
+            app.wrap()
+                .query_balance(&owner_addr, "ustars")
+                .unwrap()
+                .amount,
+            owner_balance.amount + Uint128::new(4)
+        );
+
+        let referrals = |app: &StargazeApp| {
+            app.wrap()
+                .query_wasm_smart::<ReferralsResponse>(
+                    contracts.raffle.clone(),
+                    &RaffleQueryMsg::Referrals {
+                        referrer: two.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap()
+                .referrals
+        };
+        assert_eq!(
+            referrals(&app),
+            vec![ReferralResponse {
+                raffle_id: 0,
+                ticket_count: 4,
+                rewards: vec![AssetInfo::coin(4, "ustars")],
+                claimed: false,
+            }]
+        );
+
+        let res = claim_rewards(&mut app, &three);
+        assert_error(res, ContractError::NoReferralRewards {}.to_string());
+        let balance_before = app.wrap().query_balance(&two, "ustars").unwrap().amount;
+        claim_rewards(&mut app, &two).unwrap();
+        assert_eq!(
+            app.wrap().query_balance(&two, "ustars").unwrap().amount,
+            balance_before + Uint128::new(4)
+        );
+        let res = claim_rewards(&mut app, &two);
+        assert_error(
+            res,
+            ContractError::ReferralRewardsAlreadyClaimed {}.to_string(),
+        );
+        assert!(referrals(&app)[0].claimed);
+    }
+
     // bad scenarios, expect errors
     mod bad {
 
@@ -147,6 +721,8 @@ mod tests {
                         ticket_count: 2,
                         sent_assets: AssetInfo::Coin(Coin::new(8, "ustars".to_string())),
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[Coin::new(8, "ustars".to_string())],
                 )
@@ -180,6 +756,8 @@ mod tests {
                         ticket_count: 9,
                         sent_assets: AssetInfo::Coin(Coin::new(36, "ustars".to_string())),
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[Coin::new(36, "ustars".to_string())],
                 )
@@ -194,6 +772,8 @@ mod tests {
                         ticket_count: 2,
                         sent_assets: AssetInfo::Coin(Coin::new(8, "ustars".to_string())),
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[Coin::new(8, "ustars".to_string())],
                 )
@@ -251,6 +831,8 @@ mod tests {
                             amount: Uint128::new(69u128),
                         }),
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[],
                 )
@@ -294,6 +876,8 @@ mod tests {
                     ticket_count: 2,
                     sent_assets: AssetInfo::Coin(Coin::new(8, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(8, "ustars".to_string())],
             );
@@ -310,8 +894,8 @@ mod tests {
             let ticket_count = 2u32;
             let sent_coin = Coin::new(20, "ustars".to_string());
             let sent_assets = AssetInfo::Coin(sent_coin.clone());
-            let assets_wanted =
-                AssetInfo::Coin(Coin::new((ticket_count * ticket_count).into(), "ustars"));
+            // The ticket price is 4 ustars
+            let assets_wanted = AssetInfo::Coin(Coin::new((4 * ticket_count).into(), "ustars"));
             let token = mint_one_token(&mut app, &contracts);
             create_simple_raffle(&mut app, &contracts, &token, owner_addr, None, None);
 
@@ -325,6 +909,8 @@ mod tests {
                         ticket_count,
                         sent_assets,
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[sent_coin.clone()],
                 )
@@ -351,6 +937,8 @@ mod tests {
                         ticket_count,
                         sent_assets: sent_assets.clone(),
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[sent_coin.clone()],
                 )
@@ -378,6 +966,8 @@ mod tests {
                         ticket_count,
                         sent_assets: sent_assets.clone(),
                         on_behalf_of: None,
+                        allowlist_proof: None,
+                        referrer: None,
                     },
                     &[sent_coin.clone()],
                 )
@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{coin, Addr, Binary, Decimal, Uint128};
+    use cosmwasm_std::{coin, Addr, Binary, Decimal, Timestamp, Uint128};
     use cw_multi_test::Executor;
     use raffles::{
         error::ContractError,
         msg::{ConfigResponse, DrandConfig, ExecuteMsg, QueryMsg as RaffleQueryMsg},
+        state::{RandomnessSource, DEFAULT_RANDOMNESS_DEADLINE},
     };
+    use randomness::DrandScheme;
     use rustc_serialize::hex::FromHex;
     use utils::state::{AssetInfo, Locks, Sg721Token, SudoMsg as RaffleSudoMsg, NATIVE_DENOM};
 
@@ -19,7 +21,10 @@ mod tests {
                 CREATION_FEE_AMNT_NATIVE, CREATION_FEE_AMNT_STARS, OWNER_ADDR, RAFFLE_NAME,
                 RAFFLE_TAX, TREASURY_ADDR,
             },
-            setup_raffle::{proper_raffle_instantiate, DRAND_TIMEOUT, DRAND_URL, HEX_PUBKEY},
+            setup_raffle::{
+                proper_raffle_instantiate, DRAND_GENESIS_TIME, DRAND_PERIOD, DRAND_TIMEOUT,
+                DRAND_URL, HEX_PUBKEY,
+            },
         },
         raffle::setup::{
             execute_msg::{buy_tickets_template, create_raffle_function},
@@ -73,6 +78,7 @@ mod tests {
                 minimum_raffle_duration: 1,
                 max_tickets_per_raffle: Some(100_000),
                 raffle_fee: RAFFLE_TAX,
+                randomness_provider_fee: Decimal::zero(),
                 creation_coins: vec![
                     coin(CREATION_FEE_AMNT_NATIVE, NATIVE_DENOM),
                     coin(CREATION_FEE_AMNT_STARS, NATIVE_DENOM)
@@ -86,8 +92,14 @@ mod tests {
                     random_pubkey: Binary::from(HEX_PUBKEY.from_hex().unwrap()),
                     drand_url: DRAND_URL.to_string(),
                     verify_signature_contract: contracts.randomness_verifier.clone(),
-                    timeout: DRAND_TIMEOUT
+                    scheme: DrandScheme::Chained,
+                    timeout: DRAND_TIMEOUT,
+                    genesis_time: Timestamp::from_seconds(DRAND_GENESIS_TIME),
+                    period: DRAND_PERIOD,
                 },
+                randomness_source: RandomnessSource::Drand,
+                randomness_deadline: DEFAULT_RANDOMNESS_DEADLINE,
+                referral_fee: Decimal::zero(),
             }
         )
     }
@@ -107,10 +119,14 @@ mod tests {
                     fee_addr: None,
                     minimum_raffle_duration: None,
                     raffle_fee: None,
+                    randomness_provider_fee: None,
                     creation_coins: None,
                     max_tickets_per_raffle: None,
                     fee_discounts: None,
                     drand_config: None,
+                    randomness_source: None,
+                    randomness_deadline: None,
+                    referral_fee: None,
                 },
                 &[],
             )
@@ -143,10 +159,14 @@ mod tests {
                     fee_addr: Some("new-owner".to_string()),
                     minimum_raffle_duration: Some(60),
                     raffle_fee: Some(Decimal::percent(99)),
+                    randomness_provider_fee: None,
                     creation_coins: Some(vec![coin(420, "new-new")]),
                     max_tickets_per_raffle: None,
                     fee_discounts: None,
                     drand_config: None,
+                    randomness_source: None,
+                    randomness_deadline: None,
+                    referral_fee: None,
                 },
                 &[],
             )
@@ -166,6 +186,7 @@ mod tests {
                 minimum_raffle_duration: 60,
                 max_tickets_per_raffle: Some(100_000),
                 raffle_fee: Decimal::percent(99),
+                randomness_provider_fee: Decimal::zero(),
                 creation_coins: vec![coin(420, "new-new")],
                 locks: Locks {
                     lock: false,
@@ -176,8 +197,14 @@ mod tests {
                     random_pubkey: Binary::from(HEX_PUBKEY.from_hex().unwrap()),
                     drand_url: DRAND_URL.to_string(),
                     verify_signature_contract: contracts.randomness_verifier.clone(),
-                    timeout: DRAND_TIMEOUT
+                    scheme: DrandScheme::Chained,
+                    timeout: DRAND_TIMEOUT,
+                    genesis_time: Timestamp::from_seconds(DRAND_GENESIS_TIME),
+                    period: DRAND_PERIOD,
                 },
+                randomness_source: RandomnessSource::Drand,
+                randomness_deadline: DEFAULT_RANDOMNESS_DEADLINE,
+                referral_fee: Decimal::zero(),
             }
         )
     }
@@ -1,8 +1,11 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{coin, Addr, Coin, Timestamp, Uint128};
+    use cosmwasm_std::{coin, Addr, Coin, Decimal, Timestamp, Uint128};
     use cw_multi_test::Executor;
-    use raffles::state::{RaffleInfo, RaffleOptions, MAX_TICKET_NUMBER};
+    use raffles::state::{
+        RaffleFees, RaffleInfo, RaffleOptions, DEFAULT_RANDOMNESS_DEADLINE, MAX_RAFFLE_ASSETS,
+        MAX_TICKET_NUMBER,
+    };
     use std::vec;
     use utils::state::{AssetInfo, Sg721Token, NATIVE_DENOM};
 
@@ -65,6 +68,7 @@ mod tests {
             RaffleResponse {
                 raffle_id: 0,
                 raffle_state: RaffleState::Started,
+                prize_tiers: vec![],
                 raffle_info: Some(RaffleInfo {
                     owner: owner_addr,
                     assets: vec![AssetInfo::Sg721Token(Sg721Token {
@@ -77,6 +81,19 @@ mod tests {
                     drand_randomness: None,
                     winners: vec![],
                     is_cancelled: false,
+                    is_expired: false,
+                    ticket_discounts: vec![],
+                    referral_rewards: vec![],
+                    asset_depositors: vec![],
+                    fees: Some(RaffleFees {
+                        raffle_fee: Decimal::percent(50),
+                        randomness_provider_fee: Decimal::zero(),
+                        referral_fee: Decimal::zero(),
+                    }),
+                    nois_request_after: None,
+                    randomness_deadline: Some(DEFAULT_RANDOMNESS_DEADLINE),
+                    additional_ticket_prices: vec![],
+                    additional_tickets_sold: vec![],
                     raffle_options: RaffleOptions {
                         raffle_start_timestamp: Timestamp::from_nanos(1647032400000000000),
                         raffle_duration: 1,
@@ -86,6 +103,11 @@ mod tests {
                         raffle_preview: 0,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     },
@@ -108,6 +130,7 @@ mod tests {
             RaffleResponse {
                 raffle_id: 0,
                 raffle_state: RaffleState::Started,
+                prize_tiers: vec![],
                 raffle_info: Some(RaffleInfo {
                     owner: owner_addr,
                     assets: vec![AssetInfo::Sg721Token(Sg721Token {
@@ -120,6 +143,19 @@ mod tests {
                     drand_randomness: None,
                     winners: vec![],
                     is_cancelled: false,
+                    is_expired: false,
+                    ticket_discounts: vec![],
+                    referral_rewards: vec![],
+                    asset_depositors: vec![],
+                    fees: Some(RaffleFees {
+                        raffle_fee: Decimal::percent(50),
+                        randomness_provider_fee: Decimal::zero(),
+                        referral_fee: Decimal::zero(),
+                    }),
+                    nois_request_after: None,
+                    randomness_deadline: Some(DEFAULT_RANDOMNESS_DEADLINE),
+                    additional_ticket_prices: vec![],
+                    additional_tickets_sold: vec![],
                     raffle_options: RaffleOptions {
                         raffle_start_timestamp: Timestamp::from_nanos(1647032400000000000),
                         raffle_duration: 1,
@@ -130,6 +166,11 @@ mod tests {
                         raffle_preview: 0,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     }
@@ -254,6 +295,7 @@ mod tests {
                 &ExecuteMsg::ModifyRaffle {
                     raffle_id: 0,
                     raffle_ticket_price: None,
+                    additional_ticket_prices: None,
                     raffle_options: RaffleOptionsMsg {
                         raffle_start_timestamp: None,
                         raffle_duration: None,
@@ -264,6 +306,11 @@ mod tests {
                         raffle_preview: None,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     },
@@ -282,6 +329,7 @@ mod tests {
             &ExecuteMsg::ModifyRaffle {
                 raffle_id: 1,
                 raffle_ticket_price: None,
+                additional_ticket_prices: None,
                 raffle_options: RaffleOptionsMsg {
                     raffle_start_timestamp: None,
                     raffle_duration: None,
@@ -292,6 +340,11 @@ mod tests {
                     raffle_preview: None,
                     one_winner_per_asset: false,
                     whitelist: None,
+                    merkle_root: None,
+                    prize_tiers: vec![],
+                    ticket_pricing: None,
+                    referral_share: None,
+                    sponsors: None,
                     gating_raffle: vec![],
                     min_ticket_number: None,
                 },
@@ -314,6 +367,7 @@ mod tests {
                 &ExecuteMsg::ModifyRaffle {
                     raffle_id: 0,
                     raffle_ticket_price: None,
+                    additional_ticket_prices: None,
                     raffle_options: RaffleOptionsMsg {
                         raffle_start_timestamp: None,
                         raffle_duration: None,
@@ -324,6 +378,11 @@ mod tests {
                         raffle_preview: None,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     },
@@ -339,6 +398,7 @@ mod tests {
             RaffleResponse {
                 raffle_id: 0,
                 raffle_state: RaffleState::Started,
+                prize_tiers: vec![],
                 raffle_info: Some(RaffleInfo {
                     owner: owner_addr.clone(),
                     assets: vec![AssetInfo::Sg721Token(Sg721Token {
@@ -354,6 +414,19 @@ mod tests {
                     drand_randomness: None,
                     winners: vec![],
                     is_cancelled: false,
+                    is_expired: false,
+                    ticket_discounts: vec![],
+                    referral_rewards: vec![],
+                    asset_depositors: vec![],
+                    fees: Some(RaffleFees {
+                        raffle_fee: Decimal::percent(50),
+                        randomness_provider_fee: Decimal::zero(),
+                        referral_fee: Decimal::zero(),
+                    }),
+                    nois_request_after: None,
+                    randomness_deadline: Some(DEFAULT_RANDOMNESS_DEADLINE),
+                    additional_ticket_prices: vec![],
+                    additional_tickets_sold: vec![],
                     raffle_options: RaffleOptions {
                         raffle_start_timestamp: Timestamp::from_nanos(1647032400000000000),
                         raffle_duration: 1u64,
@@ -363,6 +436,11 @@ mod tests {
                         raffle_preview: 0,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     }
@@ -378,6 +456,7 @@ mod tests {
                 &ExecuteMsg::ModifyRaffle {
                     raffle_id: 0,
                     raffle_ticket_price: None,
+                    additional_ticket_prices: None,
                     raffle_options: RaffleOptionsMsg {
                         raffle_start_timestamp: Some(Timestamp::from_nanos(1647032399999999990)), // checks new raffle start time is < original
                         raffle_duration: Some(2),
@@ -388,6 +467,11 @@ mod tests {
                         raffle_preview: None,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     },
@@ -403,6 +487,7 @@ mod tests {
             RaffleResponse {
                 raffle_id: 0,
                 raffle_state: RaffleState::Started,
+                prize_tiers: vec![],
                 raffle_info: Some(RaffleInfo {
                     owner: owner_addr,
                     assets: vec![AssetInfo::Sg721Token(Sg721Token {
@@ -418,6 +503,19 @@ mod tests {
                     drand_randomness: None,
                     winners: vec![],
                     is_cancelled: false,
+                    is_expired: false,
+                    ticket_discounts: vec![],
+                    referral_rewards: vec![],
+                    asset_depositors: vec![],
+                    fees: Some(RaffleFees {
+                        raffle_fee: Decimal::percent(50),
+                        randomness_provider_fee: Decimal::zero(),
+                        referral_fee: Decimal::zero(),
+                    }),
+                    nois_request_after: None,
+                    randomness_deadline: Some(DEFAULT_RANDOMNESS_DEADLINE),
+                    additional_ticket_prices: vec![],
+                    additional_tickets_sold: vec![],
                     raffle_options: RaffleOptions {
                         raffle_start_timestamp: Timestamp::from_nanos(1647032400000000000),
                         raffle_duration: 2u64,
@@ -427,6 +525,11 @@ mod tests {
                         raffle_preview: 0,
                         one_winner_per_asset: false,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                         min_ticket_number: None,
                     }
@@ -434,4 +537,134 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn sponsor_added_prizes() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, sponsor, other) = setup_accounts(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        create_simple_raffle(&mut app, &contracts, &token, owner_addr.clone()).unwrap();
+
+        let add_prizes = |app: &mut StargazeApp, sender: &Addr, amount: u128, funds: u128| {
+            app.execute_contract(
+                sender.clone(),
+                contracts.raffle.clone(),
+                &ExecuteMsg::AddPrizes {
+                    raffle_id: 0,
+                    assets: vec![AssetInfo::Coin(coin(amount, NATIVE_DENOM))],
+                },
+                &[coin(funds, NATIVE_DENOM)],
+            )
+        };
+        // Only the owner can add prizes until sponsors are approved
+        let res = add_prizes(&mut app, &sponsor, 100, 100);
+        assert_error(res, ContractError::Unauthorized {}.to_string());
+
+        app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ModifyRaffle {
+                raffle_id: 0,
+                raffle_ticket_price: None,
+                additional_ticket_prices: None,
+                raffle_options: RaffleOptionsMsg {
+                    raffle_start_timestamp: None,
+                    raffle_duration: None,
+                    comment: None,
+                    max_ticket_number: None,
+                    max_ticket_per_address: None,
+                    raffle_preview: None,
+                    one_winner_per_asset: false,
+                    whitelist: None,
+                    merkle_root: None,
+                    prize_tiers: vec![],
+                    ticket_pricing: None,
+                    referral_share: None,
+                    sponsors: Some(vec![sponsor.to_string()]),
+                    gating_raffle: vec![],
+                    min_ticket_number: None,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        let res = add_prizes(&mut app, &other, 100, 100);
+        assert_error(res, ContractError::Unauthorized {}.to_string());
+        let res = add_prizes(&mut app, &sponsor, 100, 150);
+        assert_error(res, ContractError::AssetMismatch {}.to_string());
+        add_prizes(&mut app, &sponsor, 100, 100).unwrap();
+
+        // The number of prizes is capped
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::AddPrizes {
+                raffle_id: 0,
+                assets: (1..MAX_RAFFLE_ASSETS as u128)
+                    .map(|amount| AssetInfo::Coin(coin(amount, NATIVE_DENOM)))
+                    .collect(),
+            },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::TooManyAssets {
+                max: MAX_RAFFLE_ASSETS,
+            }
+            .to_string(),
+        );
+
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(
+            res.assets,
+            vec![
+                AssetInfo::Sg721Token(Sg721Token {
+                    address: token.nft.to_string(),
+                    token_id: token.token_id.clone(),
+                }),
+                AssetInfo::Coin(coin(100, NATIVE_DENOM)),
+            ]
+        );
+        assert_eq!(
+            res.asset_depositors,
+            vec![owner_addr.clone(), sponsor.clone()]
+        );
+
+        // Each prize goes back to its depositor when the raffle is cancelled
+        let sponsor_balance = app.wrap().query_balance(&sponsor, NATIVE_DENOM).unwrap();
+        app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::CancelRaffle { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            app.wrap()
+                .query_balance(&sponsor, NATIVE_DENOM)
+                .unwrap()
+                .amount,
+            sponsor_balance.amount + Uint128::new(100)
+        );
+        let nft_owner: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token.nft.to_string(),
+                &sg721_base::QueryMsg::OwnerOf {
+                    token_id: token.token_id.clone(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(nft_owner.owner, owner_addr.to_string());
+
+        let res = add_prizes(&mut app, &sponsor, 100, 100);
+        assert_error(
+            res,
+            ContractError::WrongStateForAddPrizes {
+                status: RaffleState::Cancelled,
+            }
+            .to_string(),
+        );
+    }
 }
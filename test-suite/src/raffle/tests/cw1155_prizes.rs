@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, Addr, StdError, Uint128};
+    use cw1155::Cw1155ExecuteMsg;
+    use cw_multi_test::Executor;
+    use std::vec;
+    use utils::state::{AssetInfo, NATIVE_DENOM};
+
+    use crate::{
+        common_setup::{
+            app::StargazeApp,
+            helpers::{cw1155_balance, instantiate_cw1155, setup_block_time},
+            setup_accounts_and_block::{setup_accounts, setup_raffle_participants},
+            setup_raffle::{proper_raffle_instantiate, DRAND_TIMEOUT},
+        },
+        raffle::setup::{
+            execute_msg::{buy_tickets_template, create_raffle_setup},
+            helpers::finish_raffle_timeout,
+            test_msgs::{CreateRaffleParams, PurchaseTicketsParams},
+        },
+    };
+
+    const TOKEN_ID: &str = "gem";
+    const RAFFLED_VALUE: u128 = 10;
+
+    fn create_cw1155_raffle(
+        app: &mut StargazeApp,
+        raffle_addr: &Addr,
+        owner_addr: &Addr,
+        cw1155_addr: &Addr,
+    ) -> anyhow::Result<()> {
+        create_raffle_setup(CreateRaffleParams {
+            app,
+            raffle_contract_addr: raffle_addr.clone(),
+            owner_addr: owner_addr.clone(),
+            creation_fee: vec![coin(4, NATIVE_DENOM)],
+            ticket_price: Uint128::new(4),
+            max_ticket_per_addr: None,
+            raffle_start_timestamp: None,
+            raffle_nfts: vec![AssetInfo::cw1155(
+                cw1155_addr.as_str(),
+                TOKEN_ID,
+                RAFFLED_VALUE,
+            )],
+            duration: None,
+            min_ticket_number: None,
+            max_tickets: None,
+            gating: vec![],
+        })
+    }
+
+    #[test]
+    fn cw1155_raffle_prize() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+        let cw1155_addr = instantiate_cw1155(
+            &mut app,
+            vec![(owner_addr.clone(), TOKEN_ID, RAFFLED_VALUE)],
+        );
+
+        // The raffle contract needs to be approved before the tokens can be raffled
+        let err = create_cw1155_raffle(&mut app, &contracts.raffle, &owner_addr, &cw1155_addr)
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            StdError::generic_err(
+                "the raffle contract is not approved to transfer the tokens being raffled"
+            )
+            .to_string()
+        );
+
+        app.execute_contract(
+            owner_addr.clone(),
+            cw1155_addr.clone(),
+            &Cw1155ExecuteMsg::ApproveAll {
+                operator: contracts.raffle.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        create_cw1155_raffle(&mut app, &contracts.raffle, &owner_addr, &cw1155_addr).unwrap();
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &contracts.raffle, TOKEN_ID),
+            Uint128::new(RAFFLED_VALUE)
+        );
+
+        buy_tickets_template(PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 1,
+            funds_send: vec![coin(4, NATIVE_DENOM)],
+        })
+        .unwrap();
+
+        let current_time = app.block_info().time;
+        let current_block = app.block_info().height;
+        let chainid = app.block_info().chain_id.clone();
+        setup_block_time(
+            &mut app,
+            current_time.plus_seconds(130).nanos(),
+            Some(current_block + 100),
+            &chainid,
+        );
+        finish_raffle_timeout(&mut app, &contracts, 0, DRAND_TIMEOUT).unwrap();
+
+        // The only ticket buyer wins all the raffled tokens
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &one, TOKEN_ID),
+            Uint128::new(RAFFLED_VALUE)
+        );
+        assert_eq!(
+            cw1155_balance(&app, &cw1155_addr, &contracts.raffle, TOKEN_ID),
+            Uint128::zero()
+        );
+    }
+}
@@ -0,0 +1,397 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, to_json_binary, Addr, Uint128};
+    use cw20::{Cw20Coin, Cw20ExecuteMsg};
+    use cw_multi_test::{AppResponse, Executor};
+    use raffles::{
+        error::ContractError,
+        msg::{
+            ExecuteMsg as RaffleExecuteMsg, QueryMsg as RaffleQueryMsg, ReceiveMsg, RefundResponse,
+        },
+        state::RaffleOptionsMsg,
+    };
+    use std::vec;
+    use utils::state::{AssetInfo, Sg721Token};
+
+    use crate::{
+        common_setup::{
+            app::StargazeApp,
+            helpers::{cw20_balance, instantiate_cw20, setup_block_time},
+            msg::RaffleContracts,
+            setup_accounts_and_block::setup_accounts,
+            setup_minter::common::constants::{CREATION_FEE_AMNT_STARS, TREASURY_ADDR},
+            setup_raffle::{proper_raffle_instantiate, DRAND_TIMEOUT},
+        },
+        raffle::setup::helpers::{finish_raffle_timeout, mint_one_token, raffle_info},
+    };
+
+    const TICKET_PRICE: u128 = 100;
+    const NATIVE_TICKET_PRICE: u128 = 10;
+
+    fn setup_cw20_raffle(
+        min_ticket_number: Option<u32>,
+    ) -> (StargazeApp, RaffleContracts, Addr, Addr, Addr, Addr) {
+        setup_cw20_raffle_with_prices(min_ticket_number, vec![])
+    }
+
+    fn setup_cw20_raffle_with_prices(
+        min_ticket_number: Option<u32>,
+        additional_ticket_prices: Vec<AssetInfo>,
+    ) -> (StargazeApp, RaffleContracts, Addr, Addr, Addr, Addr) {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, one, two) = setup_accounts(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let cw20_addr = instantiate_cw20(
+            &mut app,
+            vec![
+                Cw20Coin {
+                    address: one.to_string(),
+                    amount: 100_000_000u128.into(),
+                },
+                Cw20Coin {
+                    address: two.to_string(),
+                    amount: 100_000_000u128.into(),
+                },
+            ],
+        );
+
+        let current_time = app.block_info().time;
+        app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::CreateRaffle {
+                owner: None,
+                assets: vec![AssetInfo::Sg721Token(Sg721Token {
+                    address: token.nft.to_string(),
+                    token_id: token.token_id.clone(),
+                })],
+                raffle_options: RaffleOptionsMsg {
+                    raffle_start_timestamp: Some(current_time),
+                    raffle_duration: None,
+                    comment: None,
+                    max_ticket_number: None,
+                    max_ticket_per_address: None,
+                    raffle_preview: None,
+                    one_winner_per_asset: false,
+                    gating_raffle: vec![],
+                    min_ticket_number,
+                    whitelist: None,
+                    merkle_root: None,
+                    prize_tiers: vec![],
+                    ticket_pricing: None,
+                    referral_share: None,
+                    sponsors: None,
+                },
+                raffle_ticket_price: AssetInfo::cw20(TICKET_PRICE, cw20_addr.as_str()),
+                additional_ticket_prices,
+            },
+            &[coin(CREATION_FEE_AMNT_STARS, "ustars")],
+        )
+        .unwrap();
+
+        (app, contracts, cw20_addr, owner_addr, one, two)
+    }
+
+    fn buy_cw20_tickets(
+        app: &mut StargazeApp,
+        contracts: &RaffleContracts,
+        cw20_addr: &Addr,
+        buyer: &Addr,
+        ticket_count: u32,
+        amount: u128,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            buyer.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.raffle.to_string(),
+                amount: amount.into(),
+                msg: to_json_binary(&ReceiveMsg::BuyTicket {
+                    raffle_id: 0,
+                    ticket_count,
+                    on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn buy_native_tickets(
+        app: &mut StargazeApp,
+        contracts: &RaffleContracts,
+        buyer: &Addr,
+        ticket_count: u32,
+        amount: u128,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            buyer.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::BuyTicket {
+                raffle_id: 0,
+                ticket_count,
+                sent_assets: AssetInfo::coin(amount, "ustars"),
+                on_behalf_of: None,
+                allowlist_proof: None,
+                referrer: None,
+            },
+            &[coin(amount, "ustars")],
+        )
+    }
+
+    fn end_raffle(app: &mut StargazeApp, contracts: &RaffleContracts) {
+        let current_time = app.block_info().time;
+        let current_block = app.block_info().height;
+        let chainid = app.block_info().chain_id.clone();
+        setup_block_time(
+            app,
+            current_time.plus_seconds(130).nanos(),
+            Some(current_block + 100),
+            &chainid,
+        );
+        finish_raffle_timeout(app, contracts, 0, DRAND_TIMEOUT).unwrap();
+    }
+
+    #[test]
+    fn cw20_ticket_purchase_and_payout() {
+        let (mut app, contracts, cw20_addr, owner_addr, one, two) = setup_cw20_raffle(None);
+
+        buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &one, 2, 2 * TICKET_PRICE).unwrap();
+        buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &two, 1, TICKET_PRICE).unwrap();
+
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(res.number_of_tickets, 3);
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &contracts.raffle),
+            Uint128::new(3 * TICKET_PRICE)
+        );
+
+        end_raffle(&mut app, &contracts);
+
+        // 50% raffle fee goes to the treasury, the rest to the raffle owner
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &Addr::unchecked(TREASURY_ADDR)),
+            Uint128::new(150)
+        );
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &owner_addr),
+            Uint128::new(150)
+        );
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &contracts.raffle),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn cw20_ticket_wrong_amount() {
+        let (mut app, contracts, cw20_addr, _, one, _) = setup_cw20_raffle(None);
+
+        // The raffle error is nested below the cw20 `Send` execution
+        let err =
+            buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &one, 2, TICKET_PRICE).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::PaymentNotSufficient {
+                ticket_count: 2,
+                assets_wanted: AssetInfo::cw20(2 * TICKET_PRICE, cw20_addr.as_str()),
+                assets_received: AssetInfo::cw20(TICKET_PRICE, cw20_addr.as_str()),
+            }
+            .to_string(),
+        );
+    }
+
+    #[test]
+    fn cw20_ticket_wrong_token() {
+        let (mut app, contracts, _, _, one, _) = setup_cw20_raffle(None);
+        let other_cw20 = instantiate_cw20(
+            &mut app,
+            vec![Cw20Coin {
+                address: one.to_string(),
+                amount: 100_000_000u128.into(),
+            }],
+        );
+
+        buy_cw20_tickets(&mut app, &contracts, &other_cw20, &one, 1, TICKET_PRICE).unwrap_err();
+    }
+
+    #[test]
+    fn cw20_ticket_self_referral() {
+        let (mut app, contracts, cw20_addr, _, one, two) = setup_cw20_raffle(None);
+
+        // The token sender pays for the tickets, even when they are bought for someone else
+        let err = app
+            .execute_contract(
+                one.clone(),
+                cw20_addr.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: contracts.raffle.to_string(),
+                    amount: TICKET_PRICE.into(),
+                    msg: to_json_binary(&ReceiveMsg::BuyTicket {
+                        raffle_id: 0,
+                        ticket_count: 1,
+                        on_behalf_of: Some(two.to_string()),
+                        allowlist_proof: None,
+                        referrer: Some(one.to_string()),
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::SelfReferral {}.to_string()
+        );
+    }
+
+    #[test]
+    fn cw20_ticket_refund_below_min_tickets() {
+        let (mut app, contracts, cw20_addr, _, one, two) = setup_cw20_raffle(Some(4));
+
+        buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &one, 2, 2 * TICKET_PRICE).unwrap();
+        buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &two, 1, TICKET_PRICE).unwrap();
+
+        end_raffle(&mut app, &contracts);
+
+        // Ticket buyers claim their refund themselves
+        for buyer in [&one, &two] {
+            app.execute_contract(
+                buyer.clone(),
+                contracts.raffle.clone(),
+                &RaffleExecuteMsg::ClaimRefund { raffle_id: 0 },
+                &[],
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &one),
+            Uint128::new(100_000_000)
+        );
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &two),
+            Uint128::new(100_000_000)
+        );
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &Addr::unchecked(TREASURY_ADDR)),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn cw20_and_native_ticket_prices() {
+        let (mut app, contracts, cw20_addr, owner_addr, one, two) = setup_cw20_raffle_with_prices(
+            None,
+            vec![AssetInfo::coin(NATIVE_TICKET_PRICE, "ustars")],
+        );
+
+        // Native tickets must be paid with the native ticket price
+        let err =
+            buy_native_tickets(&mut app, &contracts, &two, 3, 2 * NATIVE_TICKET_PRICE).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::PaymentNotSufficient {
+                ticket_count: 3,
+                assets_wanted: AssetInfo::coin(3 * NATIVE_TICKET_PRICE, "ustars"),
+                assets_received: AssetInfo::coin(2 * NATIVE_TICKET_PRICE, "ustars"),
+            }
+            .to_string(),
+        );
+
+        buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &one, 2, 2 * TICKET_PRICE).unwrap();
+        buy_native_tickets(&mut app, &contracts, &two, 3, 3 * NATIVE_TICKET_PRICE).unwrap();
+
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(res.number_of_tickets, 5);
+        assert_eq!(res.additional_tickets_sold, vec![3]);
+
+        let treasury = Addr::unchecked(TREASURY_ADDR);
+        let native_balance = |app: &StargazeApp, addr: &Addr| {
+            app.wrap().query_balance(addr, "ustars").unwrap().amount
+        };
+        let treasury_balance_before = native_balance(&app, &treasury);
+        let owner_balance_before = native_balance(&app, &owner_addr);
+
+        end_raffle(&mut app, &contracts);
+
+        // Each currency is split between the treasury and the raffle owner
+        assert_eq!(cw20_balance(&app, &cw20_addr, &treasury), Uint128::new(100));
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &owner_addr),
+            Uint128::new(100)
+        );
+        assert_eq!(
+            native_balance(&app, &treasury),
+            treasury_balance_before + Uint128::new(15)
+        );
+        assert_eq!(
+            native_balance(&app, &owner_addr),
+            owner_balance_before + Uint128::new(15)
+        );
+    }
+
+    #[test]
+    fn cw20_and_native_ticket_refunds() {
+        let (mut app, contracts, cw20_addr, _, one, two) = setup_cw20_raffle_with_prices(
+            Some(4),
+            vec![AssetInfo::coin(NATIVE_TICKET_PRICE, "ustars")],
+        );
+
+        buy_cw20_tickets(&mut app, &contracts, &cw20_addr, &one, 1, TICKET_PRICE).unwrap();
+        buy_native_tickets(&mut app, &contracts, &one, 2, 2 * NATIVE_TICKET_PRICE).unwrap();
+        buy_native_tickets(&mut app, &contracts, &two, 1, NATIVE_TICKET_PRICE).unwrap();
+
+        end_raffle(&mut app, &contracts);
+
+        let refund = |app: &StargazeApp, user: &Addr| -> RefundResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    contracts.raffle.clone(),
+                    &RaffleQueryMsg::Refund {
+                        user: user.to_string(),
+                        raffle_id: 0,
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(
+            refund(&app, &one),
+            RefundResponse {
+                ticket_count: 3,
+                refund: Some(AssetInfo::cw20(TICKET_PRICE, cw20_addr.as_str())),
+                additional_refunds: vec![AssetInfo::coin(2 * NATIVE_TICKET_PRICE, "ustars")],
+                claimed: false,
+            }
+        );
+        assert_eq!(
+            refund(&app, &two),
+            RefundResponse {
+                ticket_count: 1,
+                refund: None,
+                additional_refunds: vec![AssetInfo::coin(NATIVE_TICKET_PRICE, "ustars")],
+                claimed: false,
+            }
+        );
+
+        // Refunds are paid back in every currency used
+        let native_balance_before = app.wrap().query_balance(&one, "ustars").unwrap().amount;
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            cw20_balance(&app, &cw20_addr, &one),
+            Uint128::new(100_000_000)
+        );
+        assert_eq!(
+            app.wrap().query_balance(&one, "ustars").unwrap().amount,
+            native_balance_before + Uint128::new(2 * NATIVE_TICKET_PRICE)
+        );
+    }
+}
@@ -3,7 +3,11 @@ mod tests {
     use cosmwasm_std::{coin, testing::mock_env, Addr, BlockInfo, Decimal, HexBinary, Uint128};
     use cw_multi_test::Executor;
     use nois::NoisCallback;
-    use raffles::{error::ContractError, msg::ExecuteMsg as RaffleExecuteMsg, state::RaffleState};
+    use raffles::{
+        error::ContractError,
+        msg::{ExecuteMsg as RaffleExecuteMsg, QueryMsg as RaffleQueryMsg, RefundResponse},
+        state::RaffleState,
+    };
     use std::vec;
     use utils::state::{AssetInfo, Sg721Token, NATIVE_DENOM};
 
@@ -429,10 +433,58 @@ mod tests {
             owner_balance_before.amount, // Nothing happens, because the minimum was not reached
             owner_balance_after.amount
         );
+        // Tickets are not refunded automatically
+        assert_eq!(one_balance_before, one_balance_after);
+
+        let refund: RefundResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &RaffleQueryMsg::Refund {
+                    user: one.to_string(),
+                    raffle_id: 0,
+                },
+            )
+            .unwrap();
         assert_eq!(
-            one_balance_before + Uint128::from(4 * 3u128), // 100% fee of 3 tickets
-            one_balance_after
+            refund,
+            RefundResponse {
+                ticket_count: 3,
+                refund: Some(AssetInfo::coin(12, "ustars")),
+                additional_refunds: vec![],
+                claimed: false,
+            }
+        );
+
+        // Only ticket buyers can claim a refund
+        let res = app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
         );
+        assert_error(res, ContractError::NothingToRefund {}.to_string());
+
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            one_balance_before + Uint128::from(4 * 3u128), // 100% of 3 tickets
+            app.wrap().query_balance(&one, "ustars").unwrap().amount
+        );
+
+        // The refund can only be claimed once
+        let res = app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        );
+        assert_error(res, ContractError::RefundAlreadyClaimed {}.to_string());
     }
 
     #[test]
@@ -522,9 +574,18 @@ mod tests {
             owner_balance_before.amount, // Nothing happens, because the minimum was not reached
             owner_balance_after.amount
         );
+        // Tickets are not refunded automatically
+        assert_eq!(one_balance_before, one_balance_after);
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &RaffleExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
         assert_eq!(
-            one_balance_before + Uint128::from(4 * 3u128), // 100% fee of 3 tickets
-            one_balance_after
+            one_balance_before + Uint128::from(4 * 3u128), // 100% of 3 tickets
+            app.wrap().query_balance(&one, "ustars").unwrap().amount
         );
     }
 
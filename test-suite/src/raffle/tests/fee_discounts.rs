@@ -53,7 +53,11 @@ mod tests {
                 minimum_raffle_duration: None,
                 max_tickets_per_raffle: None,
                 raffle_fee: None,
+                randomness_provider_fee: None,
                 drand_config: None,
+                randomness_source: None,
+                randomness_deadline: None,
+                referral_fee: None,
                 creation_coins: None,
                 fee_discounts: Some(vec![
                     FeeDiscountMsg {
@@ -3,6 +3,7 @@ mod tests {
     use crate::common_setup::app::StargazeApp;
     use crate::common_setup::contract_boxes::contract_cw20;
     use crate::raffle::setup::daodao::instantiate_with_staked_balances_governance;
+    use crate::raffle::setup::helpers::mint_additional_token;
     use crate::raffle::setup::helpers::mint_one_token;
     use crate::raffle::setup::helpers::TokenMint;
     use crate::raffle::setup::{execute_msg::create_raffle_setup, test_msgs::CreateRaffleParams};
@@ -17,7 +18,7 @@ mod tests {
     use cw20::Cw20Coin;
     use cw_multi_test::{BankSudo, Executor, SudoMsg};
     use raffles::msg::QueryMsg as RaffleQueryMsg;
-    use raffles::state::AdvantageOptionsMsg;
+    use raffles::state::{AdvantageOptionsMsg, MAX_GATING_DEPTH};
     use std::vec;
     use utils::state::AssetInfo;
     use utils::state::{Sg721Token, NATIVE_DENOM};
@@ -328,6 +329,52 @@ mod tests {
         assert_eq!(res, 1);
     }
 
+    #[test]
+    fn any_gating() {
+        // Holds GATED_DENOM, or holds GATED_DENOM_1 without holding GATED_DENOM
+        let (mut app, raffle, one) = setup_gating_raffle(vec![AdvantageOptionsMsg::Any(vec![
+            AdvantageOptionsMsg::Coin(coin(12783, GATED_DENOM)),
+            AdvantageOptionsMsg::All(vec![
+                AdvantageOptionsMsg::Coin(coin(12789, GATED_DENOM_1)),
+                AdvantageOptionsMsg::Not(Box::new(AdvantageOptionsMsg::Coin(coin(
+                    12783,
+                    GATED_DENOM,
+                )))),
+            ]),
+        ])]);
+
+        app.sudo(SudoMsg::Bank({
+            BankSudo::Mint {
+                to_address: one.to_string(),
+                amount: vec![coin(100_000_000, GATED_DENOM_1.to_string())],
+            }
+        }))
+        .unwrap();
+        // customize ticket purchase params
+        let params = PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 1,
+            funds_send: vec![coin(4, "ustars")],
+        };
+        // simulate the puchase of tickets
+        let _purchase_tickets = buy_tickets_template(params).unwrap();
+
+        let res: u32 = app
+            .wrap()
+            .query_wasm_smart(
+                raffle.clone(),
+                &RaffleQueryMsg::TicketCount {
+                    owner: one.to_string(),
+                    raffle_id: 0,
+                },
+            )
+            .unwrap();
+        assert_eq!(res, 1);
+    }
+
     // bad scenarios, expect errors
     mod bad {
 
@@ -557,6 +604,107 @@ mod tests {
             // simulate the puchase of tickets
             buy_tickets_template(params).unwrap_err();
         }
+
+        #[test]
+        fn not_gating() {
+            let (mut app, raffle, one) = setup_gating_raffle(vec![AdvantageOptionsMsg::Not(
+                Box::new(AdvantageOptionsMsg::Coin(coin(12783, GATED_DENOM))),
+            )]);
+
+            app.sudo(SudoMsg::Bank({
+                BankSudo::Mint {
+                    to_address: one.to_string(),
+                    amount: vec![coin(100_000_000, GATED_DENOM.to_string())],
+                }
+            }))
+            .unwrap();
+
+            // customize ticket purchase params
+            let params = PurchaseTicketsParams {
+                app: &mut app,
+                raffle_contract_addr: raffle.clone(),
+                msg_senders: vec![one.clone()],
+                raffle_id: 0,
+                num_tickets: 1,
+                funds_send: vec![coin(4, "ustars")],
+            };
+            // Buying tickets fails because the buyer holds the excluded tokens
+            buy_tickets_template(params).unwrap_err();
+        }
+
+        #[test]
+        fn failed_gating_query() {
+            let (mut app, contracts) = proper_raffle_instantiate();
+            let (owner_addr, _, _) = setup_accounts(&mut app);
+            let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+            let token = mint_one_token(&mut app, &contracts);
+            let token1 = mint_additional_token(&mut app, &contracts, &token);
+            let tokens = [token, token1];
+
+            // The raffle contract is not a collection, so querying its tokens fails
+            let not_a_collection = AdvantageOptionsMsg::Sg721Token {
+                nft_count: 1,
+                nft_address: contracts.raffle.to_string(),
+            };
+            let gating = vec![
+                AdvantageOptionsMsg::Not(Box::new(not_a_collection.clone())),
+                AdvantageOptionsMsg::Any(vec![
+                    not_a_collection,
+                    AdvantageOptionsMsg::Coin(coin(1, NATIVE_DENOM)),
+                ]),
+            ];
+            for (raffle_id, (condition, token)) in gating.into_iter().zip(&tokens).enumerate() {
+                create_raffle(
+                    &mut app,
+                    contracts.raffle.clone(),
+                    owner_addr.clone(),
+                    vec![condition],
+                    token,
+                );
+                // A failed query is not an unmet condition, so the purchase fails with it
+                let params = PurchaseTicketsParams {
+                    app: &mut app,
+                    raffle_contract_addr: contracts.raffle.clone(),
+                    msg_senders: vec![one.clone()],
+                    raffle_id: raffle_id as u64,
+                    num_tickets: 1,
+                    funds_send: vec![coin(4, "ustars")],
+                };
+                let err = buy_tickets_template(params).unwrap_err();
+                assert!(!err.root_cause().to_string().contains("is not met"));
+            }
+        }
+
+        #[test]
+        fn too_deep_gating() {
+            let (mut app, contracts) = proper_raffle_instantiate();
+            let (owner_addr, _, _) = setup_accounts(&mut app);
+            let token = mint_one_token(&mut app, &contracts);
+
+            let mut condition = AdvantageOptionsMsg::Coin(coin(12783, GATED_DENOM));
+            for _ in 0..=MAX_GATING_DEPTH {
+                condition = AdvantageOptionsMsg::Not(Box::new(condition));
+            }
+
+            let params = CreateRaffleParams {
+                app: &mut app,
+                raffle_contract_addr: contracts.raffle.clone(),
+                owner_addr,
+                creation_fee: vec![coin(4, NATIVE_DENOM)],
+                ticket_price: Uint128::new(4),
+                max_ticket_per_addr: None,
+                raffle_start_timestamp: None,
+                raffle_nfts: vec![AssetInfo::Sg721Token(Sg721Token {
+                    address: token.nft.to_string(),
+                    token_id: token.token_id.to_string(),
+                })],
+                duration: None,
+                min_ticket_number: None,
+                max_tickets: None,
+                gating: vec![condition],
+            };
+            create_raffle_setup(params).unwrap_err();
+        }
     }
     /// Query tests
     pub mod query {
@@ -649,5 +797,47 @@ mod tests {
                 .unwrap();
             assert_eq!(res.raffles.len(), 0);
         }
+
+        #[test]
+        fn any_gated_query_works() {
+            let (mut app, raffle, one) = setup_gating_raffle(vec![AdvantageOptionsMsg::Any(vec![
+                AdvantageOptionsMsg::Coin(coin(12783, GATED_DENOM)),
+                AdvantageOptionsMsg::Coin(coin(12789, GATED_DENOM_1)),
+            ])]);
+
+            app.sudo(SudoMsg::Bank({
+                BankSudo::Mint {
+                    to_address: one.to_string(),
+                    amount: vec![coin(100_000_000, GATED_DENOM_1.to_string())],
+                }
+            }))
+            .unwrap();
+
+            let gated_raffles = |buyer: String| -> usize {
+                let res: AllRafflesResponse = app
+                    .wrap()
+                    .query_wasm_smart(
+                        raffle.clone(),
+                        &RaffleQueryMsg::AllRaffles {
+                            start_after: None,
+                            limit: None,
+                            filters: Some(QueryFilters {
+                                states: None,
+                                owner: None,
+                                ticket_depositor: None,
+                                contains_token: None,
+                                gated_rights_ticket_buyer: Some(buyer),
+                            }),
+                        },
+                    )
+                    .unwrap();
+                res.raffles.len()
+            };
+            assert_eq!(gated_raffles(one.to_string()), 1);
+            assert_eq!(
+                gated_raffles("someone-with-no-conditions-met".to_string()),
+                0
+            );
+        }
     }
 }
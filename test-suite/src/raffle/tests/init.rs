@@ -32,6 +32,7 @@ mod tests {
                     minimum_raffle_duration: None,
                     max_ticket_number: None,
                     raffle_fee: Decimal::percent(200),
+                    randomness_provider_fee: None,
                     creation_coins: vec![
                         coin(4, NATIVE_DENOM.to_string()),
                         coin(20, "ustars".to_string()),
@@ -39,6 +40,9 @@ mod tests {
                     .into(),
                     fee_discounts: vec![],
                     drand_config: default_drand_config(&Addr::unchecked("any")),
+                    randomness_source: None,
+                    randomness_deadline: None,
+                    referral_fee: None,
                 },
                 &[],
                 "raffle",
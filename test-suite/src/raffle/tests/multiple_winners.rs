@@ -1,11 +1,18 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{coin, Addr, Coin, Uint128};
-    use cw_multi_test::Executor;
-    use raffles::{msg::ExecuteMsg, state::RaffleOptionsMsg};
+    use cosmwasm_std::{coin, Addr, Coin, StdError, Uint128};
+    use cw_multi_test::{BankSudo, Executor, SudoMsg};
+    use raffles::{
+        error::ContractError,
+        msg::{ExecuteMsg, PrizeTierResponse},
+        state::{PrizeTier, RaffleOptionsMsg},
+        utils::pick_m_single_winners_among_n,
+    };
 
     use utils::state::{AssetInfo, Sg721Token};
 
+    use crate::common_setup::app::StargazeApp;
+    use crate::common_setup::helpers::assert_error;
     use crate::common_setup::setup_accounts_and_block::setup_accounts;
     use crate::common_setup::setup_accounts_and_block::setup_n_accounts;
     use crate::common_setup::setup_raffle::proper_raffle_instantiate;
@@ -17,6 +24,8 @@ mod tests {
     use crate::raffle::setup::helpers::mint_one_token;
     use crate::raffle::setup::helpers::{finish_raffle_timeout, raffle_info};
 
+    const PRIZE_DENOM: &str = "uprize";
+
     #[test]
     fn multiple_winners() {
         // create testing app
@@ -54,8 +63,14 @@ mod tests {
                         one_winner_per_asset: true,
                         min_ticket_number: None,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                     },
+                    additional_ticket_prices: vec![],
                     raffle_ticket_price: AssetInfo::Coin(Coin {
                         denom: "ustars".to_string(),
                         amount: Uint128::new(100u128),
@@ -86,6 +101,8 @@ mod tests {
                     ticket_count: 1,
                     sent_assets: AssetInfo::Coin(Coin::new(100, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(100, "ustars".to_string())],
             )
@@ -99,10 +116,24 @@ mod tests {
                     ticket_count: 1,
                     sent_assets: AssetInfo::Coin(Coin::new(100, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(100, "ustars".to_string())],
             )
             .unwrap();
+
+        // The minimum ticket number can't grow with new prizes once tickets were sold
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &ExecuteMsg::AddPrizes {
+                raffle_id: 0,
+                assets: vec![AssetInfo::Coin(coin(100, "ustars"))],
+            },
+            &[],
+        );
+        assert_error(res, ContractError::RaffleAlreadyStarted {}.to_string());
         finish_raffle_timeout(&mut app, &contracts, 0, 1000).unwrap();
 
         let res: cw721::OwnerOfResponse = app
@@ -171,8 +202,14 @@ mod tests {
                         one_winner_per_asset: true,
                         min_ticket_number: None,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                     },
+                    additional_ticket_prices: vec![],
                     raffle_ticket_price: AssetInfo::Coin(Coin {
                         denom: "ustars".to_string(),
                         amount: Uint128::new(100u128),
@@ -211,6 +248,8 @@ mod tests {
                     ticket_count: 1,
                     sent_assets: AssetInfo::Coin(Coin::new(100, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(100, "ustars".to_string())],
             )
@@ -294,8 +333,14 @@ mod tests {
                         one_winner_per_asset: true,
                         min_ticket_number: None,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                     },
+                    additional_ticket_prices: vec![],
                     raffle_ticket_price: AssetInfo::Coin(Coin {
                         denom: "ustars".to_string(),
                         amount: Uint128::new(100u128),
@@ -315,6 +360,8 @@ mod tests {
                     ticket_count: 20,
                     sent_assets: AssetInfo::Coin(Coin::new(20 * 100, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(20 * 100, "ustars".to_string())],
             )
@@ -333,4 +380,315 @@ mod tests {
     fn random_with_other() {
         test_n_randomness(5, 1);
     }
+
+    #[test]
+    fn prize_tiers() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let participants = setup_n_accounts(&mut app, 5);
+        let current_time = app.block_info().time;
+
+        let token = mint_one_token(&mut app, &contracts);
+        let token1 = mint_additional_token(&mut app, &contracts, &token);
+        let token2 = mint_additional_token(&mut app, &contracts, &token);
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: OWNER_ADDR.to_string(),
+            amount: vec![coin(200, PRIZE_DENOM)],
+        }))
+        .unwrap();
+
+        let nft = |token_id: &str| {
+            AssetInfo::Sg721Token(Sg721Token {
+                address: token.nft.to_string(),
+                token_id: token_id.to_string(),
+            })
+        };
+        let assets = vec![
+            nft(&token.token_id),
+            nft(&token1.token_id),
+            nft(&token2.token_id),
+            AssetInfo::Coin(coin(200, PRIZE_DENOM)),
+        ];
+        let raffle_options = |prize_tiers: Vec<PrizeTier>| RaffleOptionsMsg {
+            raffle_start_timestamp: Some(current_time),
+            raffle_duration: None,
+            comment: None,
+            max_ticket_number: None,
+            max_ticket_per_address: None,
+            raffle_preview: None,
+            one_winner_per_asset: false,
+            min_ticket_number: None,
+            whitelist: None,
+            merkle_root: None,
+            prize_tiers,
+            ticket_pricing: None,
+            referral_share: None,
+            sponsors: None,
+            gating_raffle: vec![],
+        };
+        let tier = |assets: Vec<u32>, winners: u32| PrizeTier { assets, winners };
+        let create_raffle = |app: &mut StargazeApp, prize_tiers: Vec<PrizeTier>| {
+            app.execute_contract(
+                Addr::unchecked(OWNER_ADDR),
+                contracts.raffle.clone(),
+                &ExecuteMsg::CreateRaffle {
+                    owner: None,
+                    assets: assets.clone(),
+                    raffle_options: raffle_options(prize_tiers),
+                    additional_ticket_prices: vec![],
+                    raffle_ticket_price: AssetInfo::Coin(Coin {
+                        denom: "ustars".to_string(),
+                        amount: Uint128::new(100u128),
+                    }),
+                },
+                &[coin(50, "ustars"), coin(200, PRIZE_DENOM)],
+            )
+        };
+
+        // Every asset must be in exactly one tier
+        let res = create_raffle(&mut app, vec![tier(vec![0], 1), tier(vec![1, 2], 2)]);
+        assert_error(
+            res,
+            ContractError::Std(StdError::generic_err(
+                "Every raffle asset must be in a prize tier",
+            ))
+            .to_string(),
+        );
+        let res = create_raffle(&mut app, vec![tier(vec![0, 3], 2), tier(vec![1, 2, 3], 3)]);
+        assert_error(
+            res,
+            ContractError::Std(StdError::generic_err(
+                "Prize 3 is not a raffle asset or is in multiple tiers",
+            ))
+            .to_string(),
+        );
+        // Only a single coin prize can be shared, evenly
+        for prize_tiers in [
+            vec![tier(vec![0], 1), tier(vec![3], 3), tier(vec![1, 2], 2)],
+            vec![tier(vec![0], 1), tier(vec![3], 2), tier(vec![1, 2], 1)],
+        ] {
+            let res = create_raffle(&mut app, prize_tiers);
+            assert_error(
+                res,
+                ContractError::Std(StdError::generic_err(
+                    "A prize tier needs one winner per prize, or a single coin prize split evenly between its winners",
+                ))
+                .to_string(),
+            );
+        }
+
+        // 1 winner of the rare NFT, 2 winners of 100 tokens each and 2 winners of the common NFTs
+        let prize_tiers = vec![tier(vec![0], 1), tier(vec![3], 2), tier(vec![1, 2], 2)];
+        create_raffle(&mut app, prize_tiers.clone()).unwrap();
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert!(res.raffle_options.one_winner_per_asset);
+        assert_eq!(res.raffle_options.min_ticket_number, Some(5));
+
+        // The tiers are kept when the raffle is modified without sending them,
+        // and there are always enough tickets for all the tier winners
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ModifyRaffle {
+                raffle_id: 0,
+                raffle_ticket_price: None,
+                additional_ticket_prices: None,
+                raffle_options: RaffleOptionsMsg {
+                    min_ticket_number: Some(2),
+                    ..raffle_options(vec![])
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(res.raffle_options.prize_tiers, prize_tiers);
+        assert_eq!(res.raffle_options.min_ticket_number, Some(5));
+
+        // The owner chose the prizes of each tier
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &ExecuteMsg::AddPrizes {
+                raffle_id: 0,
+                assets: vec![AssetInfo::Coin(coin(100, "ustars"))],
+            },
+            &[],
+        );
+        assert_error(res, ContractError::PrizeTiersLocked {}.to_string());
+
+        for addr in &participants {
+            app.execute_contract(
+                addr.clone(),
+                contracts.raffle.clone(),
+                &ExecuteMsg::BuyTicket {
+                    raffle_id: 0,
+                    ticket_count: 1,
+                    sent_assets: AssetInfo::Coin(Coin::new(100, "ustars".to_string())),
+                    on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
+                },
+                &[Coin::new(100, "ustars".to_string())],
+            )
+            .unwrap();
+        }
+        finish_raffle_timeout(&mut app, &contracts, 0, 1000).unwrap();
+
+        // Tiers are drawn in order, without repeat winners
+        let res = raffle_info(&app, &contracts, 0);
+        let randomness = res
+            .raffle_info
+            .unwrap()
+            .drand_randomness
+            .unwrap()
+            .randomness;
+        let draws: Vec<Addr> = pick_m_single_winners_among_n(randomness, 5, 5)
+            .unwrap()
+            .into_iter()
+            .map(|ticket| participants[ticket as usize].clone())
+            .collect();
+        assert_eq!(
+            res.prize_tiers,
+            vec![
+                PrizeTierResponse {
+                    assets: vec![assets[0].clone()],
+                    winners: vec![draws[0].clone()],
+                },
+                PrizeTierResponse {
+                    assets: vec![assets[3].clone()],
+                    winners: vec![draws[1].clone(), draws[2].clone()],
+                },
+                PrizeTierResponse {
+                    assets: vec![assets[1].clone(), assets[2].clone()],
+                    winners: vec![draws[3].clone(), draws[4].clone()],
+                },
+            ]
+        );
+
+        let nft_owner = |app: &StargazeApp, token_id: &str| {
+            app.wrap()
+                .query_wasm_smart::<cw721::OwnerOfResponse>(
+                    token.nft.to_string(),
+                    &sg721_base::QueryMsg::OwnerOf {
+                        token_id: token_id.to_string(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap()
+                .owner
+        };
+        assert_eq!(nft_owner(&app, &token.token_id), draws[0].to_string());
+        assert_eq!(nft_owner(&app, &token1.token_id), draws[3].to_string());
+        assert_eq!(nft_owner(&app, &token2.token_id), draws[4].to_string());
+        for winner in &draws[1..3] {
+            let balance = app
+                .wrap()
+                .query_balance(winner.to_string(), PRIZE_DENOM)
+                .unwrap();
+            assert_eq!(balance.amount, Uint128::new(100));
+        }
+    }
+
+    #[test]
+    fn tier_winners_are_distinct() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (_, one, _) = setup_accounts(&mut app);
+        let current_time = app.block_info().time;
+
+        let token = mint_one_token(&mut app, &contracts);
+        let token1 = mint_additional_token(&mut app, &contracts, &token);
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &ExecuteMsg::CreateRaffle {
+                owner: None,
+                assets: vec![
+                    AssetInfo::Sg721Token(Sg721Token {
+                        address: token.nft.to_string(),
+                        token_id: token.token_id.to_string(),
+                    }),
+                    AssetInfo::Sg721Token(Sg721Token {
+                        address: token1.nft.to_string(),
+                        token_id: token1.token_id.to_string(),
+                    }),
+                ],
+                raffle_options: RaffleOptionsMsg {
+                    raffle_start_timestamp: Some(current_time),
+                    raffle_duration: None,
+                    comment: None,
+                    max_ticket_number: None,
+                    max_ticket_per_address: None,
+                    raffle_preview: None,
+                    one_winner_per_asset: false,
+                    min_ticket_number: None,
+                    whitelist: None,
+                    merkle_root: None,
+                    prize_tiers: vec![
+                        PrizeTier {
+                            assets: vec![0],
+                            winners: 1,
+                        },
+                        PrizeTier {
+                            assets: vec![1],
+                            winners: 1,
+                        },
+                    ],
+                    ticket_pricing: None,
+                    referral_share: None,
+                    sponsors: None,
+                    gating_raffle: vec![],
+                },
+                additional_ticket_prices: vec![],
+                raffle_ticket_price: AssetInfo::Coin(Coin {
+                    denom: "ustars".to_string(),
+                    amount: Uint128::new(100u128),
+                }),
+            },
+            &[coin(50, "ustars")],
+        )
+        .unwrap();
+
+        // A single buyer holds all the tickets, but can only win one tier
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::BuyTicket {
+                raffle_id: 0,
+                ticket_count: 2,
+                sent_assets: AssetInfo::Coin(Coin::new(200, "ustars".to_string())),
+                on_behalf_of: None,
+                allowlist_proof: None,
+                referrer: None,
+            },
+            &[Coin::new(200, "ustars".to_string())],
+        )
+        .unwrap();
+        finish_raffle_timeout(&mut app, &contracts, 0, 1000).unwrap();
+
+        // The second tier has no other ticket owner left, so its prize goes back to the raffle owner
+        let res = raffle_info(&app, &contracts, 0);
+        assert_eq!(
+            res.prize_tiers
+                .into_iter()
+                .map(|tier| tier.winners)
+                .collect::<Vec<_>>(),
+            vec![vec![one.clone()], vec![Addr::unchecked(OWNER_ADDR)]]
+        );
+        for (token_id, owner) in [
+            (&token.token_id, one.to_string()),
+            (&token1.token_id, OWNER_ADDR.to_string()),
+        ] {
+            let res: cw721::OwnerOfResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    token.nft.to_string(),
+                    &sg721_base::QueryMsg::OwnerOf {
+                        token_id: token_id.to_string(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(res.owner, owner);
+        }
+    }
 }
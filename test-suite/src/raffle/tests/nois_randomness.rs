@@ -0,0 +1,320 @@
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, coins, Addr, Coin, HexBinary};
+    use cw_multi_test::{AppResponse, Executor};
+    use nois::NoisCallback;
+    use raffles::{
+        error::ContractError,
+        execute::NOIS_TIMEOUT,
+        msg::ExecuteMsg,
+        state::{RaffleOptionsMsg, RaffleState},
+    };
+    use std::vec;
+    use utils::state::{AssetInfo, Sg721Token, NATIVE_DENOM};
+
+    use crate::{
+        common_setup::{
+            app::StargazeApp,
+            helpers::{assert_error, plus_block_seconds},
+            msg::RaffleContracts,
+            nois_proxy::{MockNoisQueryMsg, MockNoisSudoMsg, NoisJob},
+            setup_accounts_and_block::{setup_accounts, setup_raffle_participants},
+            setup_minter::common::constants::CREATION_FEE_AMNT_STARS,
+            setup_raffle::{nois_raffle_instantiate, DRAND_TIMEOUT, NOIS_PRICE},
+        },
+        raffle::setup::{
+            execute_msg::buy_tickets_template,
+            helpers::{mint_one_token, raffle_info, send_update_randomness_message, TokenMint},
+            test_msgs::PurchaseTicketsParams,
+        },
+    };
+
+    const RAFFLE_DURATION: u64 = 100;
+
+    fn nois_randomness() -> HexBinary {
+        HexBinary::from(vec![42u8; 32])
+    }
+
+    fn create_nois_raffle(
+        app: &mut StargazeApp,
+        contracts: &RaffleContracts,
+        owner_addr: &Addr,
+        token: &TokenMint,
+        funds: &[Coin],
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            owner_addr.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::CreateRaffle {
+                owner: None,
+                assets: vec![AssetInfo::Sg721Token(Sg721Token {
+                    address: token.nft.to_string(),
+                    token_id: token.token_id.to_string(),
+                })],
+                raffle_options: raffle_options(RAFFLE_DURATION),
+                raffle_ticket_price: AssetInfo::coin(4, NATIVE_DENOM),
+                additional_ticket_prices: vec![],
+            },
+            funds,
+        )
+    }
+
+    fn raffle_options(raffle_duration: u64) -> RaffleOptionsMsg {
+        RaffleOptionsMsg {
+            raffle_start_timestamp: None,
+            raffle_duration: Some(raffle_duration),
+            comment: None,
+            max_ticket_number: None,
+            max_ticket_per_address: None,
+            raffle_preview: None,
+            one_winner_per_asset: false,
+            gating_raffle: vec![],
+            min_ticket_number: None,
+            whitelist: None,
+            merkle_root: None,
+            prize_tiers: vec![],
+            ticket_pricing: None,
+            referral_share: None,
+            sponsors: None,
+        }
+    }
+
+    #[test]
+    fn nois_randomness_decides_raffle() {
+        let (mut app, contracts, nois_proxy) = nois_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let raffle_start = app.block_info().time;
+
+        // The owner pays for the randomness request along with the creation fee
+        let res = create_nois_raffle(&mut app, &contracts, &owner_addr, &token, &[]);
+        assert_error(
+            res,
+            ContractError::RandomnessPaymentMissing {
+                price: coin(NOIS_PRICE, NATIVE_DENOM),
+            }
+            .to_string(),
+        );
+        create_nois_raffle(
+            &mut app,
+            &contracts,
+            &owner_addr,
+            &token,
+            &coins(CREATION_FEE_AMNT_STARS + NOIS_PRICE, NATIVE_DENOM),
+        )
+        .unwrap();
+
+        // The randomness was requested when the raffle was created, the owner paid for it
+        let jobs: Vec<NoisJob> = app
+            .wrap()
+            .query_wasm_smart(nois_proxy.clone(), &MockNoisQueryMsg::Jobs {})
+            .unwrap();
+        assert_eq!(
+            jobs,
+            vec![NoisJob {
+                requester: contracts.raffle.clone(),
+                job_id: "raffle-0".to_string(),
+                after: raffle_start.plus_seconds(RAFFLE_DURATION + NOIS_TIMEOUT),
+            }]
+        );
+        let proxy_balance = app
+            .wrap()
+            .query_balance(nois_proxy.clone(), NATIVE_DENOM)
+            .unwrap();
+        assert_eq!(proxy_balance, coin(NOIS_PRICE, NATIVE_DENOM));
+
+        let params = PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 10,
+            funds_send: vec![coin(40, NATIVE_DENOM)],
+        };
+        buy_tickets_template(params).unwrap();
+
+        // Beacons published during the raffle don't answer the job
+        app.wasm_sudo(
+            nois_proxy.clone(),
+            &MockNoisSudoMsg::Publish {
+                randomness: nois_randomness(),
+            },
+        )
+        .unwrap();
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(res.randomness, None);
+
+        plus_block_seconds(&mut app, RAFFLE_DURATION + NOIS_TIMEOUT);
+
+        // drand randomness is not accepted by a Nois raffle contract
+        let err = send_update_randomness_message(&mut app, &contracts, 0, 0).unwrap_err();
+        assert_error(
+            Err(err),
+            ContractError::WrongRandomnessSource {}.to_string(),
+        );
+
+        app.wasm_sudo(
+            nois_proxy.clone(),
+            &MockNoisSudoMsg::Publish {
+                randomness: nois_randomness(),
+            },
+        )
+        .unwrap();
+        let res = raffle_info(&app, &contracts, 0);
+        assert_eq!(res.raffle_state, RaffleState::Closed);
+        assert_eq!(res.raffle_info.unwrap().randomness, Some(nois_randomness()));
+        let jobs: Vec<NoisJob> = app
+            .wrap()
+            .query_wasm_smart(nois_proxy, &MockNoisQueryMsg::Jobs {})
+            .unwrap();
+        assert!(jobs.is_empty());
+
+        plus_block_seconds(&mut app, DRAND_TIMEOUT);
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ClaimRaffle { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+
+        let res = raffle_info(&app, &contracts, 0);
+        assert_eq!(res.raffle_state, RaffleState::Claimed);
+        assert_eq!(res.raffle_info.unwrap().winners, vec![one]);
+    }
+
+    #[test]
+    fn nois_callbacks_are_checked() {
+        let (mut app, contracts, nois_proxy) = nois_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let raffle_start = app.block_info().time;
+
+        create_nois_raffle(
+            &mut app,
+            &contracts,
+            &owner_addr,
+            &token,
+            &coins(CREATION_FEE_AMNT_STARS + NOIS_PRICE, NATIVE_DENOM),
+        )
+        .unwrap();
+
+        let callback = |published| ExecuteMsg::NoisReceive {
+            callback: NoisCallback {
+                job_id: "raffle-0".to_string(),
+                published,
+                randomness: nois_randomness(),
+            },
+        };
+
+        // Only the proxy can provide randomness
+        let res = app.execute_contract(
+            Addr::unchecked("fake-proxy"),
+            contracts.raffle.clone(),
+            &callback(raffle_start.plus_seconds(2 * RAFFLE_DURATION)),
+            &[],
+        );
+        assert_error(res, ContractError::UnauthorizedReceive.to_string());
+
+        // Randomness can't be provided while tickets are sold
+        let res = app.execute_contract(
+            nois_proxy.clone(),
+            contracts.raffle.clone(),
+            &callback(raffle_start),
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::WrongStateForRandomness {
+                status: RaffleState::Started,
+            }
+            .to_string(),
+        );
+
+        // Extending the raffle requests randomness for the new end, paid by the owner
+        let modify_raffle = |app: &mut StargazeApp, funds: &[Coin]| {
+            app.execute_contract(
+                owner_addr.clone(),
+                contracts.raffle.clone(),
+                &ExecuteMsg::ModifyRaffle {
+                    raffle_id: 0,
+                    raffle_ticket_price: None,
+                    additional_ticket_prices: None,
+                    raffle_options: raffle_options(10 * RAFFLE_DURATION),
+                },
+                funds,
+            )
+        };
+        let res = modify_raffle(&mut app, &[]);
+        assert_error(
+            res,
+            ContractError::RandomnessPaymentMissing {
+                price: coin(NOIS_PRICE, NATIVE_DENOM),
+            }
+            .to_string(),
+        );
+        modify_raffle(&mut app, &coins(NOIS_PRICE, NATIVE_DENOM)).unwrap();
+        // The randomness for that end was already paid
+        let res = modify_raffle(&mut app, &coins(NOIS_PRICE, NATIVE_DENOM));
+        assert_error(res, ContractError::AssetMismatch {}.to_string());
+        modify_raffle(&mut app, &[]).unwrap();
+        assert_eq!(
+            app.wrap()
+                .query_balance(nois_proxy.clone(), NATIVE_DENOM)
+                .unwrap(),
+            coin(2 * NOIS_PRICE, NATIVE_DENOM)
+        );
+        let raffle_end = raffle_start.plus_seconds(10 * RAFFLE_DURATION);
+        let jobs: Vec<NoisJob> = app
+            .wrap()
+            .query_wasm_smart(nois_proxy.clone(), &MockNoisQueryMsg::Jobs {})
+            .unwrap();
+        assert_eq!(
+            jobs,
+            vec![NoisJob {
+                requester: contracts.raffle.clone(),
+                job_id: "raffle-0".to_string(),
+                after: raffle_end.plus_seconds(NOIS_TIMEOUT),
+            }]
+        );
+
+        plus_block_seconds(&mut app, 10 * RAFFLE_DURATION + NOIS_TIMEOUT);
+
+        // A beacon published before the new raffle end is refused
+        let published = raffle_start.plus_seconds(RAFFLE_DURATION + NOIS_TIMEOUT);
+        let res = app.execute_contract(
+            nois_proxy.clone(),
+            contracts.raffle.clone(),
+            &callback(published),
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::RandomnessPublishedTooEarly {
+                published,
+                raffle_end,
+            }
+            .to_string(),
+        );
+
+        app.wasm_sudo(
+            nois_proxy.clone(),
+            &MockNoisSudoMsg::Publish {
+                randomness: nois_randomness(),
+            },
+        )
+        .unwrap();
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(res.randomness, Some(nois_randomness()));
+
+        // The randomness can't be replaced
+        let res = app.execute_contract(
+            nois_proxy,
+            contracts.raffle.clone(),
+            &callback(raffle_end.plus_seconds(NOIS_TIMEOUT)),
+            &[],
+        );
+        assert_error(res, ContractError::RandomnessAlreadyProvided {}.to_string());
+    }
+}
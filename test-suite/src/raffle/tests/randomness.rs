@@ -1,18 +1,32 @@
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{coin, Uint128};
+    use cosmwasm_std::{coin, Addr, Decimal, Uint128};
+    use cw721::{Cw721QueryMsg, OwnerOfResponse};
+    use cw_multi_test::Executor;
+    use raffles::{
+        error::ContractError,
+        msg::{AllRafflesResponse, DrandConfig, ExecuteMsg, QueryFilters, QueryMsg},
+        state::{RaffleState, DEFAULT_RANDOMNESS_DEADLINE},
+    };
     use std::vec;
     use utils::state::{AssetInfo, Sg721Token, NATIVE_DENOM};
 
     use crate::{
         common_setup::{
-            helpers::plus_block_seconds,
+            app::StargazeApp,
+            helpers::{assert_error, plus_block_seconds},
             setup_accounts_and_block::{setup_accounts, setup_raffle_participants},
-            setup_raffle::{proper_raffle_instantiate, DRAND_TIMEOUT},
+            setup_minter::common::constants::{OWNER_ADDR, TREASURY_ADDR},
+            setup_raffle::{
+                default_drand_config, proper_raffle_instantiate, DRAND_PERIOD, DRAND_TIMEOUT,
+            },
         },
         raffle::setup::{
             execute_msg::{buy_tickets_template, create_raffle_setup},
-            helpers::{finish_raffle_timeout, mint_one_token, raffle_info},
+            helpers::{
+                finish_raffle_timeout, mint_one_token, raffle_info, rand,
+                send_update_randomness_message, RANDOMNESS_1,
+            },
             test_msgs::{CreateRaffleParams, PurchaseTicketsParams},
         },
     };
@@ -66,10 +80,459 @@ mod tests {
         assert_eq!(res.winners[0], one);
     }
 
+    #[test]
+    fn randomness_provider_is_paid() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let provider = Addr::unchecked("randomness-provider");
+
+        let update_provider_fee = |app: &mut StargazeApp, fee: Decimal| {
+            app.execute_contract(
+                Addr::unchecked(OWNER_ADDR),
+                contracts.raffle.clone(),
+                &ExecuteMsg::UpdateConfig {
+                    name: None,
+                    owner: None,
+                    fee_addr: None,
+                    minimum_raffle_duration: None,
+                    max_tickets_per_raffle: None,
+                    raffle_fee: None,
+                    randomness_provider_fee: Some(fee),
+                    creation_coins: None,
+                    fee_discounts: None,
+                    drand_config: None,
+                    randomness_source: None,
+                    randomness_deadline: None,
+                    referral_fee: None,
+                },
+                &[],
+            )
+        };
+        // The raffle fee is already 50%
+        let res = update_provider_fee(&mut app, Decimal::percent(60));
+        assert_error(res, ContractError::InvalidFeeRate {}.to_string());
+        update_provider_fee(&mut app, Decimal::percent(10)).unwrap();
+
+        let params = CreateRaffleParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            owner_addr: owner_addr.clone(),
+            creation_fee: vec![coin(4, NATIVE_DENOM)],
+            ticket_price: Uint128::new(4),
+            max_ticket_per_addr: None,
+            raffle_start_timestamp: None,
+            raffle_nfts: vec![AssetInfo::Sg721Token(Sg721Token {
+                address: token.nft.to_string(),
+                token_id: token.token_id.to_string(),
+            })],
+            duration: None,
+            min_ticket_number: None,
+            max_tickets: None,
+            gating: vec![],
+        };
+        create_raffle_setup(params).unwrap();
+
+        let params = PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 10,
+            funds_send: vec![coin(40, "ustars")],
+        };
+        buy_tickets_template(params).unwrap();
+        plus_block_seconds(&mut app, 100);
+
+        app.execute_contract(
+            provider.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::UpdateRandomness {
+                raffle_id: 0,
+                randomness: rand(RANDOMNESS_1).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+        plus_block_seconds(&mut app, DRAND_TIMEOUT);
+
+        let balance = |app: &StargazeApp, addr: &str| {
+            app.wrap()
+                .query_balance(addr, NATIVE_DENOM)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        let owner_balance = balance(&app, owner_addr.as_str());
+        let treasury_balance = balance(&app, TREASURY_ADDR);
+
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ClaimRaffle { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+
+        // 40 ustars of tickets : 50% to the treasury, 10% to the randomness provider
+        assert_eq!(balance(&app, provider.as_str()), 4);
+        assert_eq!(balance(&app, TREASURY_ADDR), treasury_balance + 20);
+        assert_eq!(balance(&app, owner_addr.as_str()), owner_balance + 16);
+    }
+
+    #[test]
+    fn randomness_published_before_close_is_rejected() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let params = CreateRaffleParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            owner_addr: owner_addr.clone(),
+            creation_fee: vec![coin(4, NATIVE_DENOM)],
+            ticket_price: Uint128::new(4),
+            max_ticket_per_addr: None,
+            raffle_start_timestamp: None,
+            raffle_nfts: vec![AssetInfo::Sg721Token(Sg721Token {
+                address: token.nft.to_string(),
+                token_id: token.token_id.to_string(),
+            })],
+            duration: None,
+            min_ticket_number: None,
+            max_tickets: None,
+            gating: vec![],
+        };
+        create_raffle_setup(params).unwrap();
+
+        // We shift the drand chain so that RANDOMNESS_1 is published just before the raffle closes
+        let raffle_options = raffle_info(&app, &contracts, 0)
+            .raffle_info
+            .unwrap()
+            .raffle_options;
+        let raffle_end = raffle_options
+            .raffle_start_timestamp
+            .plus_seconds(raffle_options.raffle_duration);
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &ExecuteMsg::UpdateConfig {
+                name: None,
+                owner: None,
+                fee_addr: None,
+                minimum_raffle_duration: None,
+                max_tickets_per_raffle: None,
+                raffle_fee: None,
+                randomness_provider_fee: None,
+                creation_coins: None,
+                fee_discounts: None,
+                drand_config: Some(DrandConfig {
+                    genesis_time: raffle_end.minus_seconds(RANDOMNESS_1.round * DRAND_PERIOD),
+                    ..default_drand_config(&contracts.randomness_verifier)
+                }),
+                randomness_source: None,
+                randomness_deadline: None,
+                referral_fee: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let earliest_round: u64 = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &QueryMsg::EarliestRandomnessRound { raffle_id: 0 },
+            )
+            .unwrap();
+        assert_eq!(earliest_round, RANDOMNESS_1.round + 1);
+
+        let params = PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 10,
+            funds_send: vec![coin(40, "ustars")],
+        };
+        buy_tickets_template(params).unwrap();
+        plus_block_seconds(&mut app, 100);
+
+        let res = app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::UpdateRandomness {
+                raffle_id: 0,
+                randomness: rand(RANDOMNESS_1).unwrap(),
+            },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::RandomnessTooEarly {
+                round: RANDOMNESS_1.round,
+                earliest_round,
+            }
+            .to_string(),
+        );
+
+        // RANDOMNESS_2 is published after the raffle closed
+        send_update_randomness_message(&mut app, &contracts, 0, 1).unwrap();
+        plus_block_seconds(&mut app, DRAND_TIMEOUT);
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ClaimRaffle { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+
+        let res = raffle_info(&app, &contracts, 0).raffle_info.unwrap();
+        assert_eq!(res.winners[0], one);
+    }
+
+    #[test]
+    fn raffle_expires_without_randomness() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, two, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let params = CreateRaffleParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            owner_addr: owner_addr.clone(),
+            creation_fee: vec![coin(4, NATIVE_DENOM)],
+            ticket_price: Uint128::new(4),
+            max_ticket_per_addr: None,
+            raffle_start_timestamp: None,
+            raffle_nfts: vec![AssetInfo::Sg721Token(Sg721Token {
+                address: token.nft.to_string(),
+                token_id: token.token_id.to_string(),
+            })],
+            duration: None,
+            min_ticket_number: None,
+            max_tickets: None,
+            gating: vec![],
+        };
+        create_raffle_setup(params).unwrap();
+
+        let params = PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 10,
+            funds_send: vec![coin(40, NATIVE_DENOM)],
+        };
+        buy_tickets_template(params).unwrap();
+
+        // Before the deadline, the raffle waits for its randomness
+        plus_block_seconds(&mut app, 100 + DRAND_TIMEOUT);
+        let res = app.execute_contract(
+            two.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ExpireRaffle { raffle_id: 0 },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::WrongStateForExpire {
+                status: RaffleState::Closed,
+            }
+            .to_string(),
+        );
+        let res = app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        );
+        assert_error(
+            res,
+            ContractError::WrongStateForRefund {
+                status: RaffleState::Closed,
+            }
+            .to_string(),
+        );
+
+        plus_block_seconds(&mut app, DEFAULT_RANDOMNESS_DEADLINE);
+        let err = send_update_randomness_message(&mut app, &contracts, 0, 1).unwrap_err();
+        assert_error(
+            Err(err),
+            ContractError::WrongStateForRandomness {
+                status: RaffleState::Expired,
+            }
+            .to_string(),
+        );
+        let res: AllRafflesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contracts.raffle.clone(),
+                &QueryMsg::AllRaffles {
+                    start_after: None,
+                    limit: None,
+                    filters: Some(QueryFilters {
+                        states: Some(vec![RaffleState::Expired.to_string()]),
+                        owner: None,
+                        ticket_depositor: None,
+                        contains_token: None,
+                        gated_rights_ticket_buyer: None,
+                    }),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.raffles.len(), 1);
+        assert_eq!(res.raffles[0].raffle_state, RaffleState::Expired);
+
+        // Anyone can send the prize back to the raffle owner
+        app.execute_contract(
+            two.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ExpireRaffle { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+        let res: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token.nft.to_string(),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token.token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, owner_addr.to_string());
+        let res = app.execute_contract(
+            two,
+            contracts.raffle.clone(),
+            &ExecuteMsg::ExpireRaffle { raffle_id: 0 },
+            &[],
+        );
+        assert_error(res, ContractError::RaffleAlreadyExpired {}.to_string());
+
+        // Ticket buyers get their funds back
+        let balance_before = app.wrap().query_balance(&one, NATIVE_DENOM).unwrap();
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+        let balance_after = app.wrap().query_balance(&one, NATIVE_DENOM).unwrap();
+        assert_eq!(
+            balance_after.amount,
+            balance_before.amount + Uint128::new(40)
+        );
+    }
+
+    #[test]
+    fn raffle_deadline_is_fixed_at_creation() {
+        let (mut app, contracts) = proper_raffle_instantiate();
+        let (owner_addr, _, _) = setup_accounts(&mut app);
+        let (one, _, _, _, _, _) = setup_raffle_participants(&mut app);
+        let token = mint_one_token(&mut app, &contracts);
+        let params = CreateRaffleParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            owner_addr: owner_addr.clone(),
+            creation_fee: vec![coin(4, NATIVE_DENOM)],
+            ticket_price: Uint128::new(4),
+            max_ticket_per_addr: None,
+            raffle_start_timestamp: None,
+            raffle_nfts: vec![AssetInfo::Sg721Token(Sg721Token {
+                address: token.nft.to_string(),
+                token_id: token.token_id.to_string(),
+            })],
+            duration: None,
+            min_ticket_number: None,
+            max_tickets: None,
+            gating: vec![],
+        };
+        create_raffle_setup(params).unwrap();
+
+        let params = PurchaseTicketsParams {
+            app: &mut app,
+            raffle_contract_addr: contracts.raffle.clone(),
+            msg_senders: vec![one.clone()],
+            raffle_id: 0,
+            num_tickets: 10,
+            funds_send: vec![coin(40, NATIVE_DENOM)],
+        };
+        buy_tickets_template(params).unwrap();
+
+        // Refunds can be claimed as soon as the raffle expires
+        plus_block_seconds(&mut app, 100 + DRAND_TIMEOUT + DEFAULT_RANDOMNESS_DEADLINE);
+        app.execute_contract(
+            one.clone(),
+            contracts.raffle.clone(),
+            &ExecuteMsg::ClaimRefund { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+
+        // A longer deadline only applies to the next raffles
+        app.execute_contract(
+            Addr::unchecked(OWNER_ADDR),
+            contracts.raffle.clone(),
+            &ExecuteMsg::UpdateConfig {
+                name: None,
+                owner: None,
+                fee_addr: None,
+                minimum_raffle_duration: None,
+                max_tickets_per_raffle: None,
+                raffle_fee: None,
+                randomness_provider_fee: None,
+                creation_coins: None,
+                fee_discounts: None,
+                drand_config: None,
+                randomness_source: None,
+                randomness_deadline: Some(10 * DEFAULT_RANDOMNESS_DEADLINE),
+                referral_fee: None,
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            raffle_info(&app, &contracts, 0).raffle_state,
+            RaffleState::Expired
+        );
+        let err = send_update_randomness_message(&mut app, &contracts, 0, 1).unwrap_err();
+        assert_error(
+            Err(err),
+            ContractError::WrongStateForRandomness {
+                status: RaffleState::Expired,
+            }
+            .to_string(),
+        );
+
+        // The prize still goes back to the raffle owner
+        app.execute_contract(
+            one,
+            contracts.raffle.clone(),
+            &ExecuteMsg::ExpireRaffle { raffle_id: 0 },
+            &[],
+        )
+        .unwrap();
+        let res: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token.nft.to_string(),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token.token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, owner_addr.to_string());
+    }
+
     pub mod clone_testing {
         use std::io::Read;
 
-        use cosmwasm_std::{coin, coins, Addr, Binary};
+        use cosmwasm_std::{coin, coins, Addr, Binary, Timestamp};
         use cw721_base::{interface::Cw721, ExecuteMsg};
         use cw_orch::daemon::RUNTIME;
         use cw_orch_clone_testing::{
@@ -81,6 +544,7 @@ mod tests {
             state::{RaffleOptionsMsg, RaffleState},
             Raffles,
         };
+        use randomness::DrandScheme;
         use randomness_verifier::Verifier;
         use rustc_serialize::hex::FromHex;
         use utils::state::{AssetInfo, Sg721Token};
@@ -88,7 +552,9 @@ mod tests {
         use crate::{
             common_setup::{
                 setup_minter::common::constants::CREATION_FEE_AMNT_STARS,
-                setup_raffle::{DRAND_TIMEOUT, DRAND_URL, HEX_PUBKEY},
+                setup_raffle::{
+                    DRAND_GENESIS_TIME, DRAND_PERIOD, DRAND_TIMEOUT, DRAND_URL, HEX_PUBKEY,
+                },
             },
             raffle::setup::helpers::{self, RANDOMNESS_1},
             trading::{COUNTER_ID, COUNTER_TRADER, GECKIES_ADDRESS, STARGAZE_1},
@@ -130,7 +596,10 @@ mod tests {
                             random_pubkey: Binary::from(HEX_PUBKEY.from_hex()?),
                             drand_url: DRAND_URL.to_string(),
                             verify_signature_contract: verifier.address()?,
+                            scheme: DrandScheme::Chained,
                             timeout: DRAND_TIMEOUT,
+                            genesis_time: Timestamp::from_seconds(DRAND_GENESIS_TIME),
+                            period: DRAND_PERIOD,
                         },
                     },
                     new_code_id,
@@ -213,9 +682,15 @@ mod tests {
                         one_winner_per_asset: true,
                         min_ticket_number: None,
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         gating_raffle: vec![],
                     },
                     AssetInfo::Coin(ticket_price.clone()),
+                    vec![],
                     None,
                     &[config.creation_coins[0].clone()],
                 )
@@ -227,6 +702,8 @@ mod tests {
                     AssetInfo::Coin(ticket_price.clone()),
                     1,
                     None,
+                    None,
+                    None,
                     &[ticket_price],
                 )
                 .unwrap();
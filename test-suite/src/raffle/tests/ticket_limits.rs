@@ -4,11 +4,13 @@ mod tests {
     use cw_multi_test::Executor;
     use cw_multi_test::{BankSudo, SudoMsg};
 
-    use raffles::state::MINIMUM_RAFFLE_DURATION;
+    use raffles::state::{DEFAULT_RANDOMNESS_DEADLINE, MINIMUM_RAFFLE_DURATION};
     use raffles::{
         error::ContractError,
         msg::{ConfigResponse, ExecuteMsg},
-        state::{RaffleInfo, RaffleOptions, RaffleOptionsMsg, RaffleState},
+        state::{
+            RaffleFees, RaffleInfo, RaffleOptions, RaffleOptionsMsg, RaffleState, RandomnessSource,
+        },
     };
 
     use utils::state::{AssetInfo, Locks, Sg721Token, NATIVE_DENOM};
@@ -79,7 +81,13 @@ mod tests {
                         one_winner_per_asset: false,
                         gating_raffle: vec![],
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                     },
+                    additional_ticket_prices: vec![],
                     raffle_ticket_price: AssetInfo::Coin(Coin {
                         denom: "ustars".to_string(),
                         amount: Uint128::new(100u128),
@@ -101,6 +109,8 @@ mod tests {
                     ticket_count: 80,
                     sent_assets: AssetInfo::Coin(Coin::new(8000, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(8000, "ustars".to_string())],
             )
@@ -161,6 +171,7 @@ mod tests {
                 last_raffle_id: 0,
                 minimum_raffle_duration: 1,
                 raffle_fee: Decimal::percent(50),
+                randomness_provider_fee: Decimal::zero(),
                 locks: Locks {
                     lock: false,
                     sudo_lock: false,
@@ -171,7 +182,10 @@ mod tests {
                 ],
                 fee_discounts: vec![],
                 max_tickets_per_raffle: Some(80),
-                drand_config: default_drand_config(&contracts.randomness_verifier)
+                drand_config: default_drand_config(&contracts.randomness_verifier),
+                randomness_source: RandomnessSource::Drand,
+                randomness_deadline: DEFAULT_RANDOMNESS_DEADLINE,
+                referral_fee: Decimal::zero(),
             }
         );
 
@@ -255,8 +269,14 @@ mod tests {
                         one_winner_per_asset: false,
                         gating_raffle: vec![],
                         whitelist: None,
+                        merkle_root: None,
+                        prize_tiers: vec![],
+                        ticket_pricing: None,
+                        referral_share: None,
+                        sponsors: None,
                         min_ticket_number: None,
                     },
+                    additional_ticket_prices: vec![],
                     raffle_ticket_price: AssetInfo::Coin(Coin {
                         denom: "ustars".to_string(),
                         amount: Uint128::new(100u128),
@@ -295,6 +315,19 @@ mod tests {
                 drand_randomness: None,
                 winners: vec![],
                 is_cancelled: false,
+                is_expired: false,
+                ticket_discounts: vec![],
+                referral_rewards: vec![],
+                asset_depositors: vec![],
+                fees: Some(RaffleFees {
+                    raffle_fee: Decimal::percent(50),
+                    randomness_provider_fee: Decimal::zero(),
+                    referral_fee: Decimal::zero(),
+                }),
+                nois_request_after: None,
+                randomness_deadline: Some(DEFAULT_RANDOMNESS_DEADLINE),
+                additional_ticket_prices: vec![],
+                additional_tickets_sold: vec![],
                 raffle_options: RaffleOptions {
                     raffle_start_timestamp: Timestamp::from_nanos(1647032600000000000),
                     raffle_duration: 1,
@@ -305,6 +338,11 @@ mod tests {
                     one_winner_per_asset: false,
                     gating_raffle: vec![],
                     whitelist: None,
+                    merkle_root: None,
+                    prize_tiers: vec![],
+                    ticket_pricing: None,
+                    referral_share: None,
+                    sponsors: None,
                     min_ticket_number: None,
                 }
             }
@@ -335,6 +373,8 @@ mod tests {
                     ticket_count: 1,
                     sent_assets: AssetInfo::Coin(Coin::new(100, "ustars".to_string())),
                     on_behalf_of: None,
+                    allowlist_proof: None,
+                    referrer: None,
                 },
                 &[Coin::new(100, "ustars".to_string())],
             )
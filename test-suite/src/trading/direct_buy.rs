@@ -87,7 +87,10 @@ fn direct_buy_works() -> anyhow::Result<()> {
         treasury,
     } = direct_buy_init()?;
 
-    p2p.add_tokens_wanted(coins(FIRST_FUND_AMOUNT, "ujuno"), None)?;
+    p2p.add_tokens_wanted(
+        vec![AssetInfo::Coin(coin(FIRST_FUND_AMOUNT, "ujuno"))],
+        None,
+    )?;
 
     p2p.confirm_trade(None)?;
     let trade_id = 0;
@@ -129,7 +132,10 @@ fn direct_buy_insufficient_funds() -> anyhow::Result<()> {
         treasury: _,
     } = direct_buy_init()?;
 
-    p2p.add_tokens_wanted(coins(FIRST_FUND_AMOUNT, "ujuno"), None)?;
+    p2p.add_tokens_wanted(
+        vec![AssetInfo::Coin(coin(FIRST_FUND_AMOUNT, "ujuno"))],
+        None,
+    )?;
 
     p2p.confirm_trade(None)?;
     let trade_id = 0;
@@ -160,7 +166,10 @@ fn direct_buy_cant_counter_after() -> anyhow::Result<()> {
         treasury: _,
     } = direct_buy_init()?;
 
-    p2p.add_tokens_wanted(coins(FIRST_FUND_AMOUNT, "ujuno"), None)?;
+    p2p.add_tokens_wanted(
+        vec![AssetInfo::Coin(coin(FIRST_FUND_AMOUNT, "ujuno"))],
+        None,
+    )?;
 
     p2p.confirm_trade(None)?;
     let trade_id = 0;
@@ -204,7 +213,10 @@ fn direct_buy_respects_royalties() -> anyhow::Result<()> {
         Some("ujuno".to_string()),
     )?;
 
-    p2p.add_tokens_wanted(coins(FIRST_FUND_AMOUNT, "ujuno"), None)?;
+    p2p.add_tokens_wanted(
+        vec![AssetInfo::Coin(coin(FIRST_FUND_AMOUNT, "ujuno"))],
+        None,
+    )?;
 
     p2p.confirm_trade(None)?;
     let trade_id = 0;